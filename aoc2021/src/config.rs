@@ -0,0 +1,98 @@
+use common::{Params, Solution};
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Name of the file, in the current directory, with overrides for the
+/// puzzle parameters.
+pub const CONFIG_FILE: &str = "aoc.toml";
+
+/// Puzzle parameters that have been changed from the values in the puzzle
+/// descriptions, by day.
+///
+/// Each day's parameters go in a `[dayNN]` table; only the parameters that
+/// its solution declares are accepted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config(BTreeMap<u8, BTreeMap<String, u64>>);
+
+impl Config {
+    /// Reads the configuration from `path`, falling back to the defaults if
+    /// the file doesn't exist.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match read_to_string(path) {
+            Ok(contents) => contents
+                .parse()
+                .map_err(|err| format!("{}: {}", path.display(), err)),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                Ok(Self::default())
+            }
+            Err(err) => Err(format!("{}: {}", path.display(), err)),
+        }
+    }
+
+    /// The parameters for `solution`, with the values in the puzzle
+    /// description unless overridden.
+    pub fn params(&self, solution: &Solution) -> Params {
+        let mut params = solution.default_params();
+        for (name, &value) in self.0.get(&solution.day).into_iter().flatten() {
+            params.set(name, value);
+        }
+        params
+    }
+}
+
+fn parse_table(name: &str) -> Option<&'static Solution> {
+    name.strip_prefix("day")
+        .filter(|day| day.len() == 2)
+        .and_then(|day| day.parse().ok())
+        .and_then(Solution::find)
+}
+
+impl FromStr for Config {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tables: BTreeMap<String, BTreeMap<String, u64>> =
+            toml::from_str(s).map_err(|err| err.to_string())?;
+
+        let mut config = BTreeMap::new();
+        for (table, values) in tables {
+            let solution = parse_table(&table)
+                .ok_or_else(|| format!("Unknown table [{}]", table))?;
+            if let Some(name) = values.keys().find(|name| {
+                solution.params.iter().all(|(param, _)| param != name)
+            }) {
+                return Err(format!(
+                    "Unknown parameter '{}' in [{}]",
+                    name, table
+                ));
+            }
+            config.insert(solution.day, values);
+        }
+        Ok(Self(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_defaults() {
+        let config: Config = "[day06]\npart2_days = 18\n\n[day21]\n\
+            part1_target = 500\n"
+            .parse()
+            .unwrap();
+        let params = config.params(Solution::find(6).unwrap());
+        assert_eq!(params.get::<u32>("part1_days"), Ok(80));
+        assert_eq!(params.get::<u32>("part2_days"), Ok(18));
+        let params = config.params(Solution::find(21).unwrap());
+        assert_eq!(params.get::<u64>("part1_target"), Ok(500));
+        let solution = Solution::find(14).unwrap();
+        assert_eq!(config.params(solution), solution.default_params());
+        assert!("[day06]\nsteps = 1\n".parse::<Config>().is_err());
+        assert!("[day26]\nsteps = 1\n".parse::<Config>().is_err());
+    }
+}
@@ -0,0 +1,7 @@
+pub mod config;
+#[cfg(feature = "peak_memory")]
+pub mod memory;
+pub mod solver;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
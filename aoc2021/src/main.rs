@@ -0,0 +1,138 @@
+mod serve;
+mod watch;
+
+use aoc2021::config::{Config, CONFIG_FILE};
+#[cfg(feature = "peak_memory")]
+use aoc2021::memory;
+use aoc2021::solver::{parse_day, solve_with_config};
+use clap::{crate_description, App, AppSettings, Arg, ArgMatches, SubCommand};
+#[cfg(feature = "peak_memory")]
+use common::peak_alloc::format_bytes;
+use common::{Pretty, COLOR_CHOICES};
+use serve::serve;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::process::exit;
+use std::time::Instant;
+use watch::watch;
+
+fn main() {
+    let day_arg = Arg::with_name("DAY")
+        .help("Puzzle day (1-25)")
+        .required(true)
+        .validator(|value| parse_day(&value).map(|_| ()))
+        .index(1);
+    let port_arg = Arg::with_name("port")
+        .long("port")
+        .short("p")
+        .takes_value(true)
+        .default_value("8080")
+        .validator(|value| {
+            value
+                .parse::<u16>()
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        })
+        .help("Port to listen on");
+
+    let args = App::new(crate_description!())
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .global(true)
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
+        .subcommand(
+            SubCommand::with_name("watch")
+                .about("Re-runs a solver every time its input file changes")
+                .arg(day_arg)
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("File with puzzle input")
+                        .required(true)
+                        .index(2),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("Serves the solvers over HTTP")
+                .arg(port_arg),
+        )
+        .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
+
+    let config = match Config::load(Path::new(CONFIG_FILE)) {
+        Ok(config) => config,
+        Err(err) => {
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read config: {}", err))
+            );
+            exit(2);
+        }
+    };
+
+    match args.subcommand() {
+        ("watch", Some(sub_args)) => watch(
+            day_of(sub_args),
+            sub_args.value_of("INPUT").unwrap(),
+            &config,
+            pretty,
+        ),
+        ("serve", Some(sub_args)) => {
+            let port = sub_args.value_of("port").unwrap().parse().unwrap();
+            if let Err(err) = serve(port, &config) {
+                println!(
+                    "{}",
+                    pretty.error(format!("Failed to start server: {}", err))
+                );
+                exit(2);
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn day_of(args: &ArgMatches) -> u8 {
+    // Already checked by the argument validator
+    parse_day(args.value_of("DAY").unwrap()).unwrap()
+}
+
+pub fn run(
+    day: u8,
+    filename: &str,
+    config: &Config,
+    pretty: Pretty,
+) -> Result<(), String> {
+    let input = read_to_string(filename).map_err(|err| err.to_string())?;
+
+    #[cfg(feature = "peak_memory")]
+    memory::start();
+    let start = Instant::now();
+    let answers = solve_with_config(day, &input, config)?;
+    let elapsed = start.elapsed();
+
+    println!("Advent of Code 2021 - Day {:02}", day);
+    for (answer, part) in answers.iter().zip(1..) {
+        match answer {
+            Some(answer) if answer.contains('\n') => {
+                println!("Part {}:\n{}", part, pretty.answer(answer))
+            }
+            Some(answer) => {
+                println!("Part {}: {}", part, pretty.answer(answer))
+            }
+            None => println!("Part {}: Not found", part),
+        }
+    }
+    println!("Solved in {:.3?}", elapsed);
+    #[cfg(feature = "peak_memory")]
+    for (peak, part) in memory::part_peaks().into_iter().zip(1..) {
+        println!("Part {} peak heap: {}", part, format_bytes(peak));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use common::PeakAlloc;
+use std::cell::RefCell;
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc::new();
+
+thread_local! {
+    static PART_PEAKS: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Starts measuring the parts solved next on this thread.
+pub fn start() {
+    PART_PEAKS.with_borrow_mut(Vec::clear);
+    ALLOCATOR.reset_peak();
+}
+
+/// Records the peak heap usage since the previous part ended, or since
+/// [`start`] for the first part.
+pub(crate) fn end_part() {
+    let peak = ALLOCATOR.peak();
+    PART_PEAKS.with_borrow_mut(|peaks| peaks.push(peak));
+    ALLOCATOR.reset_peak();
+}
+
+/// Peak heap usage of each part solved since [`start`].
+pub fn part_peaks() -> Vec<usize> {
+    PART_PEAKS.with_borrow(Vec::clone)
+}
@@ -0,0 +1,85 @@
+use aoc2021::config::Config;
+use aoc2021::solver::{days, parse_day, solve_with_config};
+use serde_json::{json, Value};
+use std::panic::catch_unwind;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+pub fn serve(port: u16, config: &Config) -> Result<(), String> {
+    let server =
+        Server::http(("0.0.0.0", port)).map_err(|err| err.to_string())?;
+    println!("Listening on port {} (press Ctrl-C to stop)", port);
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = handle(&mut request, config);
+        println!("{} {} - {}", request.method(), request.url(), status);
+
+        let content_type =
+            Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type);
+        if let Err(err) = request.respond(response) {
+            println!("Failed to send response: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle(request: &mut Request, config: &Config) -> (u16, Value) {
+    if request.url().split('?').next() == Some("/days") {
+        return match request.method() {
+            Method::Get => (200, json!({ "days": days() })),
+            _ => (405, json!({ "error": "Method not allowed" })),
+        };
+    }
+
+    let day = match route(request.url()) {
+        Some(day_str) => match parse_day(day_str) {
+            Ok(day) => day,
+            Err(err) => return (404, json!({ "error": err })),
+        },
+        None => return (404, json!({ "error": "Not found" })),
+    };
+
+    if *request.method() != Method::Post {
+        return (405, json!({ "error": "Method not allowed" }));
+    }
+
+    let mut input = String::new();
+    if let Err(err) = request.as_reader().read_to_string(&mut input) {
+        return (400, json!({ "error": err.to_string() }));
+    }
+
+    // Solvers should reject bad input with an error rather than panic, so
+    // this is only defence in depth: a panic is still a bug in that day, which
+    // is logged for fixing while the server keeps going
+    match catch_unwind(|| solve_with_config(day, &input, config)) {
+        Ok(Ok(answers)) => {
+            let mut body = json!({ "day": day });
+            for (answer, part) in answers.into_iter().zip(1..) {
+                body[format!("part{}", part)] = json!(answer);
+            }
+            (200, body)
+        }
+        Ok(Err(err)) => (422, json!({ "error": err })),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown cause");
+            println!("Day {} solver panicked: {}", day, message);
+            (500, json!({ "error": "Solver failed" }))
+        }
+    }
+}
+
+/// Extracts the day from a `/day/{n}/solve` path.
+fn route(url: &str) -> Option<&str> {
+    let path = url.split('?').next()?;
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        ["", "day", day, "solve"] => Some(day),
+        _ => None,
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::Config;
+use common::{Report, Solution};
+// Each day registers its solution from its own crate, which only ends up in
+// the binary if it is used here
+use day01 as _;
+use day02 as _;
+use day03 as _;
+use day04 as _;
+use day05 as _;
+use day06 as _;
+use day07 as _;
+use day08 as _;
+use day09 as _;
+use day10 as _;
+use day11 as _;
+use day12 as _;
+use day13 as _;
+use day14 as _;
+use day15 as _;
+use day16 as _;
+use day17 as _;
+use day18 as _;
+use day19 as _;
+use day20 as _;
+use day21 as _;
+use day22 as _;
+use day23 as _;
+use day24 as _;
+use day25 as _;
+
+pub type Answers = Vec<Option<String>>;
+
+/// Every day with a registered solution, in order.
+pub fn days() -> Vec<u8> {
+    Solution::all()
+        .into_iter()
+        .map(|solution| solution.day)
+        .collect()
+}
+
+pub fn parse_day(value: &str) -> Result<u8, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|&day| Solution::find(day).is_some())
+        .ok_or_else(|| {
+            let days = days();
+            format!(
+                "Day must be between {} and {}",
+                days.first().unwrap_or(&0),
+                days.last().unwrap_or(&0)
+            )
+        })
+}
+
+pub fn solve(day: u8, input: &str) -> Result<Answers, String> {
+    solve_with_config(day, input, &Config::default())
+}
+
+/// Same as [`solve`], with the puzzle parameters taken from `config`.
+pub fn solve_with_config(
+    day: u8,
+    input: &str,
+    config: &Config,
+) -> Result<Answers, String> {
+    let solution =
+        Solution::find(day).ok_or_else(|| format!("Invalid day {}", day))?;
+    let mut answers = vec![];
+    // Every answer is reported as soon as its part is solved, which makes
+    // this the place to close per-part measurements
+    let mut on_answer = |answer| {
+        #[cfg(feature = "peak_memory")]
+        crate::memory::end_part();
+        answers.push(answer);
+    };
+    (solution.solve)(
+        input,
+        &config.params(solution),
+        &mut Report::new(&mut on_answer),
+    )?;
+    Ok(answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_examples() {
+        let examples = [
+            (1, day01::EXAMPLE, vec!["7", "5"]),
+            (2, day02::EXAMPLE, vec!["150", "900"]),
+            (3, day03::EXAMPLE, vec!["198", "230"]),
+            (4, day04::EXAMPLE, vec!["4512", "1924"]),
+            (5, day05::EXAMPLE, vec!["5", "12"]),
+            (6, day06::EXAMPLE, vec!["5934", "26984457539"]),
+            (7, day07::EXAMPLE, vec!["37", "168"]),
+            (8, day08::EXAMPLE, vec!["26", "66582"]),
+            (9, day09::EXAMPLE, vec!["15", "1134"]),
+            (10, day10::EXAMPLE, vec!["26397", "288957"]),
+            (11, day11::EXAMPLE, vec!["1656", "195"]),
+            (12, day12::EXAMPLE, vec!["10", "36"]),
+            (
+                13,
+                day13::EXAMPLE,
+                vec!["17", "#####\n#...#\n#...#\n#...#\n#####"],
+            ),
+            (14, day14::EXAMPLE, vec!["1588", "2188189693529"]),
+            (15, day15::EXAMPLE, vec!["40", "315"]),
+            (16, day16::EXAMPLE, vec!["16", "15"]),
+            (17, day17::EXAMPLE, vec!["45", "112"]),
+            (18, day18::EXAMPLE, vec!["4140", "3993"]),
+            (19, day19::EXAMPLE, vec!["79", "3621"]),
+            (20, day20::EXAMPLE, vec!["35", "3351"]),
+            (21, day21::EXAMPLE, vec!["739785", "444356092776315"]),
+            (22, day22::EXAMPLE, vec!["474140", "2758514936282235"]),
+            (24, day24::EXAMPLE, vec!["8", "2"]),
+            (25, day25::EXAMPLE, vec!["58"]),
+        ];
+        for (day, example, answers) in examples {
+            assert_solves(day, example, answers);
+        }
+    }
+
+    #[test]
+    #[ignore = "takes over a minute without optimizations"]
+    fn solves_day23_example() {
+        assert_solves(23, day23::EXAMPLE, vec!["12521", "44169"]);
+    }
+
+    fn assert_solves(day: u8, example: &str, answers: Vec<&str>) {
+        let answers = answers
+            .into_iter()
+            .map(|answer| Some(answer.to_string()))
+            .collect::<Answers>();
+        assert_eq!(solve(day, example), Ok(answers), "Day {}", day);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::solver::solve;
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_family = "wasm")]
+extern "C" {
+    fn __wasm_call_ctors();
+}
+
+/// Registers every day's solution when the module is instantiated, which the
+/// linker leaves to the embedder for library modules.
+#[wasm_bindgen(start)]
+fn start() {
+    #[cfg(target_family = "wasm")]
+    unsafe {
+        __wasm_call_ctors();
+    }
+}
+
+/// Solves the puzzle for `day` from its raw input, returning one entry per
+/// part: the answer as a string, or `null` if no answer was found.
+#[wasm_bindgen(js_name = solveStr)]
+pub fn solve_str(day: u8, input: &str) -> Result<Vec<JsValue>, JsError> {
+    let answers = solve(day, input).map_err(|err| JsError::new(&err))?;
+    Ok(answers.into_iter().map(JsValue::from).collect())
+}
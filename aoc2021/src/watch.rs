@@ -0,0 +1,32 @@
+use crate::run;
+use aoc2021::config::Config;
+use common::Pretty;
+use std::fs::metadata;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+fn last_modified(filename: &str) -> Option<SystemTime> {
+    metadata(filename).and_then(|meta| meta.modified()).ok()
+}
+
+pub fn watch(day: u8, filename: &str, config: &Config, pretty: Pretty) {
+    println!("Watching {} (press Ctrl-C to stop)", filename);
+
+    let mut last_run = None;
+    loop {
+        let modified = last_modified(filename);
+        if modified.is_some() && modified != last_run {
+            last_run = modified;
+            println!();
+            if let Err(err) = run(day, filename, config, pretty) {
+                println!(
+                    "{}",
+                    pretty.error(format!("Failed to solve: {}", err))
+                );
+            }
+        }
+        sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::Grid;
+use gif::{Encoder, Frame, Repeat};
+use std::borrow::Cow;
+use std::fs::{create_dir_all, File};
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::Duration;
+
+/// Smallest number of pixels on the longest side of an exported frame;
+/// smaller grids are scaled up to reach it.
+const MIN_SIZE: usize = 400;
+
+/// A puzzle simulation that can be exported as images, one frame per step.
+pub trait Animate {
+    /// Colours of the cells in [`images`](Animate::images), as RGB triples;
+    /// the first one is also used to fill any frame smaller than the rest.
+    const PALETTE: &'static [[u8; 3]];
+
+    /// Frames to export, in order, starting from the initial state, with
+    /// each cell holding an index into [`PALETTE`](Animate::PALETTE).
+    fn images(&self) -> impl Iterator<Item = Grid<u8>> + '_;
+
+    /// Writes the frames as an animated GIF if `path` ends in `.gif`, or
+    /// else as numbered PNG files in the directory `path`.
+    fn export(&self, path: &Path, delay: Duration) -> Result<(), String> {
+        let frames = Frames::new(self.images().collect(), Self::PALETTE);
+        let is_gif = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("gif"))
+            .unwrap_or(false);
+        if is_gif {
+            frames.write_gif(path, delay)
+        } else {
+            frames.write_pngs(path)
+        }
+        .map_err(|err| format!("{}: {}", path.display(), err))
+    }
+}
+
+/// Writes a single image as a PNG file, scaled up like animation frames,
+/// with each cell holding an index into `palette`.
+pub fn save_png(
+    image: Grid<u8>,
+    palette: &[[u8; 3]],
+    path: &Path,
+) -> Result<(), String> {
+    let frames = Frames::new(vec![image], palette);
+    frames
+        .write_png(&frames.pixels[0], path)
+        .map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Same as [`save_png`], with each cell `scale` pixels wide and high.
+pub fn save_png_scaled(
+    image: Grid<u8>,
+    palette: &[[u8; 3]],
+    scale: usize,
+    path: &Path,
+) -> Result<(), String> {
+    let frames = Frames::with_scale(vec![image], palette, scale.max(1));
+    frames
+        .write_png(&frames.pixels[0], path)
+        .map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Frames scaled up and padded to a common size.
+struct Frames {
+    width: usize,
+    height: usize,
+    palette: Vec<u8>,
+    pixels: Vec<Vec<u8>>,
+}
+
+impl Frames {
+    fn new(images: Vec<Grid<u8>>, palette: &[[u8; 3]]) -> Self {
+        let width = images.iter().map(Grid::width).max().unwrap_or(0);
+        let height = images.iter().map(Grid::height).max().unwrap_or(0);
+        let scale = (MIN_SIZE / width.max(height).max(1)).max(1);
+        Self::with_scale(images, palette, scale)
+    }
+
+    fn with_scale(
+        images: Vec<Grid<u8>>,
+        palette: &[[u8; 3]],
+        scale: usize,
+    ) -> Self {
+        let width = images.iter().map(Grid::width).max().unwrap_or(0);
+        let height = images.iter().map(Grid::height).max().unwrap_or(0);
+        let pixels = images
+            .iter()
+            .map(|image| {
+                let mut pixels = vec![0; width * height * scale * scale];
+                for ((x, y), &color) in image.iter() {
+                    for row in y * scale..(y + 1) * scale {
+                        let start = row * width * scale + x * scale;
+                        pixels[start..start + scale].fill(color);
+                    }
+                }
+                pixels
+            })
+            .collect();
+
+        Self {
+            width: width * scale,
+            height: height * scale,
+            palette: palette.concat(),
+            pixels,
+        }
+    }
+
+    fn write_gif(&self, path: &Path, delay: Duration) -> Result<(), String> {
+        let size = |len: usize| {
+            u16::try_from(len).map_err(|_| "Image too large".to_string())
+        };
+        let (width, height) = (size(self.width)?, size(self.height)?);
+        let file = File::create(path).map_err(|err| err.to_string())?;
+        let mut encoder =
+            Encoder::new(BufWriter::new(file), width, height, &self.palette)
+                .map_err(|err| err.to_string())?;
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|err| err.to_string())?;
+
+        // GIF delays are in hundredths of a second
+        let delay = u16::try_from(delay.as_millis() / 10).unwrap_or(u16::MAX);
+        for pixels in &self.pixels {
+            let frame = Frame {
+                width,
+                height,
+                delay,
+                buffer: Cow::Borrowed(pixels),
+                ..Frame::default()
+            };
+            encoder.write_frame(&frame).map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn write_pngs(&self, dir: &Path) -> Result<(), String> {
+        create_dir_all(dir).map_err(|err| err.to_string())?;
+        for (pixels, step) in self.pixels.iter().zip(0..) {
+            self.write_png(pixels, &dir.join(format!("{:04}.png", step)))?;
+        }
+        Ok(())
+    }
+
+    fn write_png(&self, pixels: &[u8], path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(|err| err.to_string())?;
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(file),
+            self.width as u32,
+            self.height as u32,
+        );
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(self.palette.as_slice());
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(pixels))
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_and_pads_frames() {
+        let images = vec![Grid::new(1, 1, 1), Grid::new(2, 4, 2)];
+        let frames =
+            Frames::new(images, &[[0, 0, 0], [255, 0, 0], [0, 0, 255]]);
+        assert_eq!((frames.width, frames.height), (200, 400));
+        assert_eq!(frames.palette.len(), 9);
+        assert_eq!(frames.pixels[0][0], 1);
+        assert_eq!(frames.pixels[0][99], 1);
+        assert_eq!(frames.pixels[0][100], 0);
+        assert_eq!(frames.pixels[0][100 * 200], 0);
+        assert!(frames.pixels[1].iter().all(|&color| color == 2));
+
+        let frames = Frames::with_scale(vec![Grid::new(2, 1, 1)], &[], 3);
+        assert_eq!((frames.width, frames.height), (6, 3));
+        assert_eq!(frames.pixels[0].len(), 18);
+    }
+}
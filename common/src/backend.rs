@@ -0,0 +1,69 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Alternative algorithms that solve the same puzzle, so that one can be
+/// picked explicitly and the results compared.
+///
+/// Each day with more than one implementation defines its own `Backend` enum
+/// implementing this trait, along with `part1_with` and `part2_with`
+/// functions taking it.
+pub trait Backend: Copy + Default + PartialEq + 'static {
+    /// Every backend, starting with the default one.
+    const ALL: &'static [Self];
+
+    /// Short name used to pick the backend on the command line.
+    fn name(&self) -> &'static str;
+
+    fn names() -> Vec<&'static str> {
+        Self::ALL.iter().map(Self::name).collect()
+    }
+
+    fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .find(|backend| backend.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "Unknown backend '{}', expected one of: {}",
+                    name,
+                    Self::names().join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    enum Sort {
+        #[default]
+        Quick,
+        Bubble,
+    }
+
+    impl Backend for Sort {
+        const ALL: &'static [Self] = &[Sort::Quick, Sort::Bubble];
+
+        fn name(&self) -> &'static str {
+            match self {
+                Sort::Quick => "quick",
+                Sort::Bubble => "bubble",
+            }
+        }
+    }
+
+    #[test]
+    fn finds_backend_by_name() {
+        assert_eq!(Sort::from_name("bubble"), Ok(Sort::Bubble));
+        assert_eq!(Sort::names(), ["quick", "bubble"]);
+        assert_eq!(
+            Sort::from_name("merge"),
+            Err("Unknown backend 'merge', expected one of: quick, bubble"
+                .into())
+        );
+    }
+}
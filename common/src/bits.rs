@@ -0,0 +1,140 @@
+use alloc::vec::Vec;
+
+/// A cursor over the bits of a byte slice, most significant bit first.
+#[derive(Clone, Debug)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    /// Number of bits read so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    pub fn is_aligned(&self) -> bool {
+        self.offset.is_multiple_of(8)
+    }
+
+    /// Reads the next `num_bits` (at most 64) as an unsigned number without
+    /// moving the cursor.
+    pub fn peek(&self, num_bits: usize) -> Option<u64> {
+        if num_bits > 64 || num_bits > self.remaining() {
+            return None;
+        }
+
+        let mut value = 0;
+        let mut offset = self.offset;
+        let mut pending = num_bits;
+        while pending > 0 {
+            let available = 8 - offset % 8;
+            let take = available.min(pending);
+            let byte = u64::from(self.bytes[offset / 8]);
+            let chunk = (byte >> (available - take)) & ((1 << take) - 1);
+            value = value << take | chunk;
+            offset += take;
+            pending -= take;
+        }
+        Some(value)
+    }
+
+    pub fn pop(&mut self, num_bits: usize) -> Option<u64> {
+        let value = self.peek(num_bits)?;
+        self.offset += num_bits;
+        Some(value)
+    }
+
+    pub fn skip(&mut self, num_bits: usize) -> Option<()> {
+        if num_bits > self.remaining() {
+            return None;
+        }
+        self.offset += num_bits;
+        Some(())
+    }
+
+    /// Skips to the start of the next byte, unless already there.
+    pub fn align(&mut self) {
+        self.offset = self.offset.next_multiple_of(8);
+    }
+}
+
+/// Packs bits into bytes, most significant bit first; the last byte is
+/// padded with zeros.
+#[derive(Clone, Debug, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of bits written.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends the lowest `num_bits` (at most 64) of `value`.
+    pub fn push(&mut self, value: u64, num_bits: usize) {
+        assert!(num_bits <= 64, "Cannot push more than 64 bits at once");
+        for bit in (0..num_bits).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if value >> bit & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut writer = BitWriter::new();
+        writer.push(0b110, 3);
+        writer.push(0xABCDE, 20);
+        writer.push(1, 1);
+        assert_eq!(writer.len(), 24);
+
+        let mut reader = BitReader::new(writer.as_bytes());
+        assert_eq!(reader.peek(3), Some(0b110));
+        assert_eq!(reader.pop(3), Some(0b110));
+        assert_eq!(reader.pop(20), Some(0xABCDE));
+        assert_eq!(reader.offset(), 23);
+        assert_eq!(reader.pop(2), None);
+        assert_eq!(reader.pop(1), Some(1));
+        assert!(reader.is_empty() && reader.is_aligned());
+    }
+}
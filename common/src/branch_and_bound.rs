@@ -0,0 +1,226 @@
+use crate::SearchStats;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// A minimisation problem over a space of states.
+pub trait Problem {
+    type State: Clone + Eq + Hash;
+    type Cost: Copy + Default + Ord + Add<Output = Self::Cost>;
+
+    /// States reachable from `state` in one move, with the cost of the move.
+    fn successors(&self, state: &Self::State)
+        -> Vec<(Self::State, Self::Cost)>;
+
+    fn is_goal(&self, state: &Self::State) -> bool;
+
+    /// A cost that reaching a goal from `state` can never beat; the tighter
+    /// it is, the more of the search space gets pruned.
+    fn lower_bound(&self, _state: &Self::State) -> Self::Cost {
+        Self::Cost::default()
+    }
+}
+
+/// Bound, cost so far and index of a state waiting to be expanded.
+type Candidate<C> = Reverse<(C, C, usize)>;
+
+/// States waiting to be expanded, best bound first, along with the cheapest
+/// goal found so far.
+struct Frontier<'a, P: Problem> {
+    problem: &'a P,
+    stats: &'a mut SearchStats,
+    // Popped states are taken out so that only the map keeps a copy
+    states: Vec<Option<P::State>>,
+    lowest: HashMap<P::State, P::Cost>,
+    heap: BinaryHeap<Candidate<P::Cost>>,
+    best: Option<P::Cost>,
+}
+
+impl<'a, P: Problem> Frontier<'a, P> {
+    fn new(
+        problem: &'a P,
+        start: P::State,
+        stats: &'a mut SearchStats,
+    ) -> Self {
+        let mut frontier = Self {
+            problem,
+            stats,
+            states: Vec::new(),
+            lowest: HashMap::new(),
+            heap: BinaryHeap::new(),
+            best: None,
+        };
+        frontier.push(start, P::Cost::default());
+        frontier
+    }
+
+    fn beaten(&self, bound: P::Cost) -> bool {
+        self.best.map(|best| bound >= best).unwrap_or(false)
+    }
+
+    fn push(&mut self, state: P::State, cost: P::Cost) {
+        if self.problem.is_goal(&state) {
+            if !self.beaten(cost) {
+                self.best = Some(cost);
+            }
+            return;
+        }
+
+        let bound = cost + self.problem.lower_bound(&state);
+        if self.beaten(bound) {
+            return;
+        }
+
+        match self.lowest.entry(state.clone()) {
+            Entry::Occupied(entry) if *entry.get() <= cost => {
+                self.stats.duplicates += 1;
+                return;
+            }
+            Entry::Occupied(mut entry) => {
+                entry.insert(cost);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(cost);
+            }
+        }
+        self.states.push(Some(state));
+        self.heap
+            .push(Reverse((bound, cost, self.states.len() - 1)));
+        self.stats.record_frontier(self.heap.len());
+        self.stats.record_visited(self.lowest.len());
+    }
+
+    fn pop(&mut self) -> Option<(P::State, P::Cost)> {
+        while let Some(Reverse((bound, cost, index))) = self.heap.pop() {
+            if self.beaten(bound) {
+                // Nothing left can lead to a cheaper goal
+                self.heap.clear();
+                return None;
+            }
+            let state = self.states[index].take()?;
+            if self.lowest[&state] < cost {
+                // Already reached this state through a cheaper path
+                self.stats.duplicates += 1;
+                continue;
+            }
+            self.stats.expanded += 1;
+            return Some((state, cost));
+        }
+        None
+    }
+}
+
+/// Finds the cost of the cheapest path from `start` to a goal state by
+/// expanding the states with the lowest bound first and pruning those that
+/// can't beat the best goal found so far.
+pub fn branch_and_bound<P: Problem>(
+    problem: &P,
+    start: P::State,
+) -> Option<P::Cost> {
+    branch_and_bound_with_stats(problem, start, &mut SearchStats::default())
+}
+
+/// Same as [`branch_and_bound`], adding the work done to `stats`.
+pub fn branch_and_bound_with_stats<P: Problem>(
+    problem: &P,
+    start: P::State,
+    stats: &mut SearchStats,
+) -> Option<P::Cost> {
+    let mut frontier = Frontier::new(problem, start, stats);
+    while let Some((state, cost)) = frontier.pop() {
+        for (next, move_cost) in problem.successors(&state) {
+            frontier.push(next, cost + move_cost);
+        }
+    }
+    frontier.best
+}
+
+/// Same as [`branch_and_bound`], but expands batches of the most promising
+/// states in parallel.
+#[cfg(feature = "parallel")]
+pub fn par_branch_and_bound<P>(problem: &P, start: P::State) -> Option<P::Cost>
+where
+    P: Problem + Sync,
+    P::State: Send + Sync,
+    P::Cost: Send + Sync,
+{
+    par_branch_and_bound_with_stats(problem, start, &mut SearchStats::default())
+}
+
+/// Same as [`par_branch_and_bound`], adding the work done to `stats`.
+#[cfg(feature = "parallel")]
+pub fn par_branch_and_bound_with_stats<P>(
+    problem: &P,
+    start: P::State,
+    stats: &mut SearchStats,
+) -> Option<P::Cost>
+where
+    P: Problem + Sync,
+    P::State: Send + Sync,
+    P::Cost: Send + Sync,
+{
+    const BATCH_SIZE: usize = 256;
+
+    let mut frontier = Frontier::new(problem, start, stats);
+    loop {
+        let batch = (0..BATCH_SIZE)
+            .map_while(|_| frontier.pop())
+            .collect::<Vec<_>>();
+        if batch.is_empty() {
+            break;
+        }
+
+        let successors = batch
+            .par_iter()
+            .map(|(state, cost)| {
+                problem
+                    .successors(state)
+                    .into_iter()
+                    .map(|(next, move_cost)| (next, *cost + move_cost))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for (next, cost) in successors.into_iter().flatten() {
+            frontier.push(next, cost);
+        }
+    }
+    frontier.best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reach a target number by adding 3 (cost 1) or doubling (cost 2).
+    struct Reach(u32);
+
+    impl Problem for Reach {
+        type State = u32;
+        type Cost = u32;
+
+        fn successors(&self, state: &u32) -> Vec<(u32, u32)> {
+            [(state + 3, 1), (state * 2, 2)]
+                .into_iter()
+                .filter(|(next, _)| *next <= self.0)
+                .collect()
+        }
+
+        fn is_goal(&self, state: &u32) -> bool {
+            *state == self.0
+        }
+    }
+
+    #[test]
+    fn finds_cheapest_goal() {
+        // Both +3 all the way and 1 -> 4 -> 8 -> 16 -> 19 cost 6
+        assert_eq!(branch_and_bound(&Reach(19), 1), Some(6));
+        assert_eq!(branch_and_bound(&Reach(2), 1), Some(2));
+        assert_eq!(branch_and_bound(&Reach(3), 1), None);
+        #[cfg(feature = "parallel")]
+        assert_eq!(par_branch_and_bound(&Reach(19), 1), Some(6));
+    }
+}
@@ -0,0 +1,334 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::ops::{Index, IndexMut};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use Connectivity::*;
+
+const ORTHOGONAL: [(i64, i64); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+const ALL_AROUND: [(i64, i64); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Which cells count as neighbours of a given cell.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Connectivity {
+    /// Up, down, left and right.
+    Four,
+    /// Orthogonal and diagonal.
+    Eight,
+}
+
+impl Connectivity {
+    pub fn offsets(&self) -> &'static [(i64, i64)] {
+        match self {
+            Four => &ORTHOGONAL,
+            Eight => &ALL_AROUND,
+        }
+    }
+}
+
+/// A dense, row-major 2D grid addressed by `(x, y)` coordinates, where `x`
+/// is the column and `y` the row, both starting at zero on the top left.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    pub fn from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize, usize) -> T,
+    {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Builds a grid from its rows, which must all have the same length.
+    pub fn from_rows<I>(rows: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = Vec<T>>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+        for row in rows {
+            match width {
+                None => width = Some(row.len()),
+                Some(width) if width != row.len() => {
+                    return Err(format!(
+                        "Row {} has {} cells, expected {}",
+                        height + 1,
+                        row.len(),
+                        width
+                    ))
+                }
+                _ => (),
+            }
+            cells.extend(row);
+            height += 1;
+        }
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+    }
+
+    pub fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn coords_of(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(move |index| &mut self.cells[index])
+    }
+
+    /// Like `get`, but accepts coordinates that may be out of bounds on
+    /// either side, as produced by adding offsets to a position.
+    pub fn get_signed(&self, x: i64, y: i64) -> Option<&T> {
+        if self.contains(x, y) {
+            self.get(x as usize, y as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Coordinates of the neighbours of `(x, y)` that lie within the grid.
+    pub fn neighbors(
+        &self,
+        x: usize,
+        y: usize,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        connectivity.offsets().iter().filter_map(move |(dx, dy)| {
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            if nx >= 0 && ny >= 0 && nx < width && ny < height {
+                Some((nx as usize, ny as usize))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Coordinates of the neighbours of `(x, y)`, wrapping around the edges
+    /// as if the grid were drawn on a torus.
+    pub fn wrapping_neighbors(
+        &self,
+        x: usize,
+        y: usize,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        connectivity.offsets().iter().map(move |(dx, dy)| {
+            (
+                (x as i64 + dx).rem_euclid(width) as usize,
+                (y as i64 + dy).rem_euclid(height) as usize,
+            )
+        })
+    }
+
+    pub fn coords(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.coords().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl Iterator<Item = ((usize, usize), &mut T)> {
+        self.coords().zip(self.cells.iter_mut())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// The cells row by row, each at its index from [`index_of`].
+    ///
+    /// [`index_of`]: Grid::index_of
+    pub fn as_slice(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        self.get(x, y).expect("Grid coordinates out of bounds")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        self.get_mut(x, y).expect("Grid coordinates out of bounds")
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses lines of single decimal digits, such as puzzle height maps, into a
+/// grid of their values.
+pub fn parse_digit_grid(s: &str) -> Result<Grid<u8>, String> {
+    s.trim()
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            line.trim()
+                .chars()
+                .zip(1..)
+                .map(|(ch, col)| {
+                    ch.to_digit(10).map(|digit| digit as u8).ok_or_else(|| {
+                        format!(
+                            "Line {}, column {}: Invalid digit '{}'",
+                            line_num, col, ch
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(Grid::from_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbors_respect_bounds() {
+        let grid = Grid::new(3, 2, 0);
+        let mut four = grid.neighbors(0, 0, Four).collect::<Vec<_>>();
+        four.sort_unstable();
+        assert_eq!(four, vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbors(1, 0, Eight).count(), 5);
+        assert_eq!(grid.wrapping_neighbors(0, 0, Eight).count(), 8);
+        assert!(grid.wrapping_neighbors(0, 0, Four).any(|pos| pos == (2, 0)));
+    }
+
+    #[test]
+    fn builds_and_displays_rows() {
+        let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get_signed(-1, 0), None);
+        assert_eq!(grid.coords_of(grid.index_of(1, 1).unwrap()), (1, 1));
+        assert_eq!(grid.to_string(), "123\n456\n");
+        assert!(Grid::from_rows(vec![vec![1], vec![2, 3]]).is_err());
+    }
+
+    #[test]
+    fn parses_digits() {
+        let grid = parse_digit_grid("012\n345\n").unwrap();
+        assert_eq!(grid.to_string(), "012\n345\n");
+        assert_eq!(
+            parse_digit_grid("01\n2x"),
+            Err("Line 2, column 2: Invalid digit 'x'".to_string())
+        );
+        assert!(parse_digit_grid("01\n234").is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use std::fs::{read_to_string, File};
+use std::io::{BufRead, BufReader};
+
+/// The contents of the file named `filename`, or the puzzle's `example` if
+/// no file was given.
+pub fn read_or_example(
+    filename: Option<&str>,
+    example: &str,
+) -> Result<String, String> {
+    match filename {
+        Some(filename) => {
+            read_to_string(filename).map_err(|err| err.to_string())
+        }
+        None => Ok(example.to_string()),
+    }
+}
+
+/// Same as [`read_or_example`], for reading the input a line at a time.
+pub fn open_or_example<'a>(
+    filename: Option<&str>,
+    example: &'a str,
+) -> Result<Box<dyn BufRead + 'a>, String> {
+    match filename {
+        Some(filename) => {
+            let file = File::open(filename).map_err(|err| err.to_string())?;
+            Ok(Box::new(BufReader::new(file)))
+        }
+        None => Ok(Box::new(example.as_bytes())),
+    }
+}
@@ -0,0 +1,273 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{max, min};
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A non-empty, inclusive range of integers.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Interval {
+    start: i32,
+    end: i32,
+}
+
+impl Interval {
+    /// Panics if `end` is lower than `start`.
+    pub fn new(start: i32, end: i32) -> Self {
+        Self::try_new(start, end).expect("Interval end is before its start")
+    }
+
+    pub fn try_new(start: i32, end: i32) -> Option<Self> {
+        if end >= start {
+            Some(Self { start, end })
+        } else {
+            None
+        }
+    }
+
+    pub fn start(&self) -> i32 {
+        self.start
+    }
+
+    pub fn end(&self) -> i32 {
+        self.end
+    }
+
+    /// Number of integers in the interval, which is never zero.
+    pub fn size(&self) -> u64 {
+        (i64::from(self.end) - i64::from(self.start) + 1) as u64
+    }
+
+    pub fn contains(&self, value: i32) -> bool {
+        value >= self.start && value <= self.end
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        Self::try_new(max(self.start, other.start), min(self.end, other.end))
+    }
+
+    /// The parts of `self` not in `other`: none, one or two intervals, in
+    /// ascending order.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        match self.intersect(other) {
+            Some(overlap) => [
+                overlap
+                    .start
+                    .checked_sub(1)
+                    .and_then(|end| Self::try_new(self.start, end)),
+                overlap
+                    .end
+                    .checked_add(1)
+                    .and_then(|start| Self::try_new(start, self.end)),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            None => vec![*self],
+        }
+    }
+
+    /// The smallest interval covering both, if they overlap or touch.
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        if i64::from(other.start) <= i64::from(self.end) + 1
+            && i64::from(self.start) <= i64::from(other.end) + 1
+        {
+            Some(Self::new(
+                min(self.start, other.start),
+                max(self.end, other.end),
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = i32> {
+        self.start..=self.end
+    }
+}
+
+/// An axis-aligned box of integer coordinates.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Cuboid {
+    pub x: Interval,
+    pub y: Interval,
+    pub z: Interval,
+}
+
+impl Cuboid {
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Number of integer points in the cuboid, which can be up to 2^96.
+    pub fn volume(&self) -> u128 {
+        u128::from(self.x.size())
+            * u128::from(self.y.size())
+            * u128::from(self.z.size())
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        Some(Self::new(
+            self.x.intersect(&other.x)?,
+            self.y.intersect(&other.y)?,
+            self.z.intersect(&other.z)?,
+        ))
+    }
+
+    /// Splits the parts of `self` not in `other` into at most six disjoint
+    /// cuboids.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let overlap = match self.intersect(other) {
+            Some(overlap) => overlap,
+            None => return vec![*self],
+        };
+
+        let x_parts = self.x.subtract(&overlap.x).into_iter();
+        let y_parts = self.y.subtract(&overlap.y).into_iter();
+        let z_parts = self.z.subtract(&overlap.z).into_iter();
+        x_parts
+            .map(|x| Self::new(x, self.y, self.z))
+            .chain(y_parts.map(|y| Self::new(overlap.x, y, self.z)))
+            .chain(z_parts.map(|z| Self::new(overlap.x, overlap.y, z)))
+            .collect()
+    }
+}
+
+/// A union of cuboids, kept as a list of disjoint cuboids.
+#[derive(Clone, Debug, Default)]
+pub struct CuboidSet(Vec<Cuboid>);
+
+impl CuboidSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, cuboid: Cuboid) {
+        let mut pending = vec![cuboid];
+        while let Some(part) = pending.pop() {
+            match self.0.iter().find_map(|c| c.intersect(&part)) {
+                Some(overlap) => pending.extend(part.subtract(&overlap)),
+                None => self.0.push(part),
+            }
+        }
+    }
+
+    pub fn remove(&mut self, cuboid: &Cuboid) {
+        self.0 = self.0.iter().flat_map(|c| c.subtract(cuboid)).collect();
+    }
+
+    pub fn volume(&self) -> u128 {
+        self.0.iter().map(Cuboid::volume).sum()
+    }
+
+    /// Number of disjoint cuboids the set is split into.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Cuboid> {
+        self.0.iter()
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl Display for Cuboid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "x={},y={},z={}", self.x, self.y, self.z)
+    }
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    /// Parses a range like `-3..7`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .trim()
+            .split_once("..")
+            .ok_or_else(|| format!("Invalid range '{}'", s))?;
+        let parse = |num: &str| {
+            num.parse()
+                .map_err(|_| format!("Invalid range number '{}'", num))
+        };
+        Self::try_new(parse(start)?, parse(end)?)
+            .ok_or_else(|| format!("Invalid range '{}'", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_algebra() {
+        let a = Interval::new(1, 10);
+        let b: Interval = "5..15".parse().unwrap();
+        assert_eq!(a.intersect(&b), Some(Interval::new(5, 10)));
+        assert_eq!(a.subtract(&b), vec![Interval::new(1, 4)]);
+        assert_eq!(
+            a.subtract(&Interval::new(3, 4)),
+            vec![Interval::new(1, 2), Interval::new(5, 10)]
+        );
+        assert!(a.subtract(&Interval::new(0, 11)).is_empty());
+        assert_eq!(a.union(&Interval::new(11, 12)), Some(Interval::new(1, 12)));
+        assert_eq!(a.union(&Interval::new(12, 12)), None);
+        assert!("3..1".parse::<Interval>().is_err());
+        assert_eq!(b.to_string(), "5..15");
+    }
+
+    #[test]
+    fn interval_bounds() {
+        let all = Interval::new(i32::MIN, i32::MAX);
+        assert_eq!(all.size(), 1 << 32);
+        assert_eq!(
+            all.subtract(&Interval::new(i32::MIN, -5)),
+            vec![Interval::new(-4, i32::MAX)]
+        );
+        assert_eq!(
+            all.subtract(&Interval::new(5, i32::MAX)),
+            vec![Interval::new(i32::MIN, 4)]
+        );
+        assert_eq!(
+            all.subtract(&Interval::new(0, 0)),
+            vec![Interval::new(i32::MIN, -1), Interval::new(1, i32::MAX)]
+        );
+        assert!(all.subtract(&all).is_empty());
+        assert_eq!(Cuboid::new(all, all, all).volume(), 1 << 96);
+        assert_eq!(
+            all.union(&Interval::new(0, 0)),
+            Some(Interval::new(i32::MIN, i32::MAX))
+        );
+    }
+
+    #[test]
+    fn cuboid_set_volume() {
+        let cube = |start, end| {
+            let range = Interval::new(start, end);
+            Cuboid::new(range, range, range)
+        };
+        let mut set = CuboidSet::new();
+        set.insert(cube(10, 12));
+        set.insert(cube(11, 13));
+        assert_eq!(set.volume(), 27 + 19);
+        set.remove(&cube(9, 11));
+        assert_eq!(set.volume(), 38);
+        set.insert(cube(10, 10));
+        assert_eq!(set.volume(), 39);
+    }
+}
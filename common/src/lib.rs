@@ -0,0 +1,72 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "animation")]
+pub mod animation;
+pub mod backend;
+pub mod bits;
+#[cfg(feature = "std")]
+pub mod branch_and_bound;
+pub mod grid;
+#[cfg(feature = "std")]
+pub mod input;
+pub mod interval;
+#[cfg(feature = "std")]
+pub mod lines;
+pub mod ocr;
+#[cfg(feature = "std")]
+pub mod peak_alloc;
+pub mod point;
+#[cfg(feature = "std")]
+pub mod pretty;
+#[cfg(feature = "registry")]
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod search;
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod svg;
+pub mod tree;
+pub mod union_find;
+#[cfg(feature = "std")]
+pub mod visualize;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "animation")]
+pub use animation::{save_png, save_png_scaled, Animate};
+pub use backend::Backend;
+pub use bits::{BitReader, BitWriter};
+#[cfg(feature = "std")]
+pub use branch_and_bound::{
+    branch_and_bound, branch_and_bound_with_stats, Problem,
+};
+#[cfg(feature = "parallel")]
+pub use branch_and_bound::{
+    par_branch_and_bound, par_branch_and_bound_with_stats,
+};
+pub use grid::{parse_digit_grid, Connectivity, Grid};
+#[cfg(feature = "std")]
+pub use input::{open_or_example, read_or_example};
+pub use interval::{Cuboid, CuboidSet, Interval};
+#[cfg(feature = "registry")]
+pub use inventory;
+#[cfg(feature = "std")]
+pub use lines::parse_lines;
+#[cfg(feature = "std")]
+pub use peak_alloc::PeakAlloc;
+pub use point::{Plane, Point2, Point3};
+#[cfg(feature = "std")]
+pub use pretty::{Color, ColorChoice, Pretty, COLOR_CHOICES};
+#[cfg(feature = "registry")]
+pub use registry::{Params, Report, Solution};
+#[cfg(feature = "std")]
+pub use search::{shortest_path, shortest_path_with_stats};
+pub use stats::SearchStats;
+#[cfg(feature = "std")]
+pub use svg::Svg;
+pub use tree::{NodeId, Tree};
+pub use union_find::UnionFind;
+#[cfg(feature = "std")]
+pub use visualize::Visualize;
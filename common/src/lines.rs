@@ -0,0 +1,40 @@
+use std::io::BufRead;
+
+/// Parses the lines of `reader` one at a time as they're read, so that the
+/// whole input never needs to be in memory. Errors, including failures to
+/// read, name the line they happened on.
+pub fn parse_lines<R, T, F>(
+    reader: R,
+    mut parse: F,
+) -> impl Iterator<Item = Result<T, String>>
+where
+    R: BufRead,
+    F: FnMut(&str) -> Result<T, String>,
+{
+    reader.lines().zip(1..).map(move |(line, line_num)| {
+        line.map_err(|err| err.to_string())
+            .and_then(|line| parse(&line))
+            .map_err(|err| format!("Line {}: {}", line_num, err))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_each_line() {
+        let parse =
+            |line: &str| line.parse::<u8>().map_err(|err| err.to_string());
+        let parsed =
+            parse_lines("1\n2\nx\n".as_bytes(), parse).collect::<Vec<_>>();
+        assert_eq!(
+            parsed,
+            [
+                Ok(1),
+                Ok(2),
+                Err("Line 3: invalid digit found in string".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+
+/// Letters as drawn by the puzzles, with `#` for lit and `.` for unlit dots.
+const GLYPHS: [(char, [&str; GLYPH_HEIGHT]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...", "#...", ".#.#", "..#.", "..#.", "..#."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Reads the letters drawn in `art`, six lines of `#` (lit) and any other
+/// character (unlit), with letters four dots wide and one dot apart.
+///
+/// Returns `None` if the art doesn't have six lines or contains a glyph
+/// that isn't a known letter.
+pub fn decode(art: &str) -> Option<String> {
+    let rows = art
+        .lines()
+        .map(|line| line.chars().map(|ch| ch == '#').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    if rows.len() != GLYPH_HEIGHT {
+        return None;
+    }
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    (0..width)
+        .step_by(GLYPH_WIDTH + 1)
+        .map(|start| {
+            let lit =
+                |row: &[bool], col| row.get(col).copied().unwrap_or(false);
+            GLYPHS
+                .iter()
+                .find(|(_, glyph)| {
+                    glyph.iter().zip(&rows).all(|(glyph_row, row)| {
+                        glyph_row
+                            .chars()
+                            .zip(start..)
+                            .all(|(ch, col)| (ch == '#') == lit(row, col))
+                    })
+                })
+                .map(|(letter, _)| *letter)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_letters() {
+        let art = "\
+            #..#.####\n\
+            #..#....#\n\
+            ####...#.\n\
+            #..#..#..\n\
+            #..#.#...\n\
+            #..#.####\n";
+        assert_eq!(decode(art), Some("HZ".to_string()));
+        assert_eq!(decode(&art.replace("####\n", "###.\n")), None);
+        assert_eq!(decode("#..#\n"), None);
+    }
+}
@@ -0,0 +1,130 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A global allocator that forwards to the system allocator while keeping
+/// track of how many bytes are allocated and the most there have been at
+/// once.
+///
+/// Install it with `#[global_allocator]`; counts cover every thread.
+pub struct PeakAlloc {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl PeakAlloc {
+    pub const fn new() -> Self {
+        Self {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Starts measuring a new peak from the bytes allocated right now.
+    pub fn reset_peak(&self) {
+        self.peak.store(self.current(), Ordering::Relaxed);
+    }
+
+    fn grow(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn shrink(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+impl Default for PeakAlloc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.shrink(layout.size());
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                self.grow(new_size - layout.size());
+            } else {
+                self.shrink(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+/// Formats a number of bytes with a binary unit, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_peak() {
+        let alloc = PeakAlloc::new();
+        let layout = Layout::from_size_align(1000, 8).unwrap();
+        unsafe {
+            let ptr = alloc.alloc(layout);
+            let ptr = alloc.realloc(ptr, layout, 3000);
+            assert_eq!((alloc.current(), alloc.peak()), (3000, 3000));
+            let bigger = Layout::from_size_align(3000, 8).unwrap();
+            let ptr = alloc.realloc(ptr, bigger, 500);
+            assert_eq!((alloc.current(), alloc.peak()), (500, 3000));
+            alloc.reset_peak();
+            assert_eq!(alloc.peak(), 500);
+            alloc.dealloc(ptr, Layout::from_size_align(500, 8).unwrap());
+        }
+        assert_eq!(alloc.current(), 0);
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536 * 1024), "1.5 MiB");
+    }
+}
@@ -0,0 +1,227 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A point (or displacement) on a 2D integer plane.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Point2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// A point (or displacement) in 3D integer space.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Point3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// Plane of a quarter-turn rotation in 3D space.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Plane {
+    XY,
+    XZ,
+    YZ,
+}
+
+impl Point2 {
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> i32 {
+        (other.x - self.x).abs() + (other.y - self.y).abs()
+    }
+
+    /// Component-wise sign, i.e. a unit step in the direction of `self`.
+    pub fn signum(&self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Quarter turn counter-clockwise, with y growing upwards.
+    pub fn rotate_left(&self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Quarter turn clockwise, with y growing upwards.
+    pub fn rotate_right(&self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+}
+
+impl Point3 {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> i32 {
+        (other.x - self.x).abs()
+            + (other.y - self.y).abs()
+            + (other.z - self.z).abs()
+    }
+
+    pub fn signum(&self) -> Self {
+        Self::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    /// Quarter turn within the given plane, leaving the third axis intact.
+    pub fn rotate(&self, plane: Plane) -> Self {
+        match plane {
+            Plane::XY => Self::new(self.y, -self.x, self.z),
+            Plane::XZ => Self::new(self.z, self.y, -self.x),
+            Plane::YZ => Self::new(self.x, self.z, -self.y),
+        }
+    }
+}
+
+impl Add for Point2 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point2 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Neg for Point2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Point2 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point2 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Add for Point3 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Neg for Point3 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for Point3 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point3 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl From<Point2> for (i64, i64) {
+    fn from(point: Point2) -> Self {
+        (i64::from(point.x), i64::from(point.y))
+    }
+}
+
+impl Display for Point2 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl Display for Point3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+fn parse_coordinates(s: &str) -> Result<Vec<i32>, String> {
+    s.split(',')
+        .map(|num| {
+            num.trim()
+                .parse()
+                .map_err(|err| format!("Invalid coordinate '{}': {}", num, err))
+        })
+        .collect()
+}
+
+impl FromStr for Point2 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_coordinates(s)?[..] {
+            [x, y] => Ok(Self::new(x, y)),
+            _ => Err(format!("Invalid point '{}'", s)),
+        }
+    }
+}
+
+impl FromStr for Point3 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_coordinates(s)?[..] {
+            [x, y, z] => Ok(Self::new(x, y, z)),
+            _ => Err(format!("Invalid point '{}'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_and_parsing() {
+        let a: Point2 = "3, -4".parse().unwrap();
+        let b = Point2::new(1, 1);
+        assert_eq!(a - b, Point2::new(2, -5));
+        assert_eq!((b - a).signum(), Point2::new(-1, 1));
+        assert_eq!(a.manhattan_distance(&b), 7);
+        assert_eq!(b.rotate_left().rotate_right(), b);
+        assert!("1,2,3".parse::<Point2>().is_err());
+
+        let p: Point3 = "1,2,3".parse().unwrap();
+        assert_eq!(p.to_string(), "1,2,3");
+        assert_eq!(p + -p, Point3::default());
+        let full_turn = (0..4).fold(p, |p, _| p.rotate(Plane::XZ));
+        assert_eq!(full_turn, p);
+    }
+}
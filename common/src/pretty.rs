@@ -0,0 +1,139 @@
+use crate::Grid;
+use std::env::var_os;
+use std::fmt::Display;
+use std::io::{stdout, IsTerminal};
+use std::str::FromStr;
+use Color::*;
+
+/// Values accepted by `--color`.
+pub const COLOR_CHOICES: [&str; 3] = ["auto", "always", "never"];
+
+/// When to color terminal output.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ColorChoice {
+    /// Only when writing to a terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("Invalid color choice '{}'", s)),
+        }
+    }
+}
+
+/// Foreground colors of the standard ANSI palette.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    /// Colors that are easy to tell apart, for marking separate regions.
+    pub const DISTINCT: [Color; 5] = [Yellow, Blue, Magenta, Cyan, Green];
+
+    fn code(&self) -> u8 {
+        match self {
+            Red => 31,
+            Green => 32,
+            Yellow => 33,
+            Blue => 34,
+            Magenta => 35,
+            Cyan => 36,
+        }
+    }
+}
+
+/// Formats output for the terminal, adding ANSI colors only if enabled.
+#[derive(Clone, Copy, Debug)]
+pub struct Pretty {
+    color: bool,
+}
+
+impl Pretty {
+    pub fn new(choice: ColorChoice) -> Self {
+        let color = match choice {
+            ColorChoice::Auto => {
+                stdout().is_terminal() && var_os("NO_COLOR").is_none()
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        Self { color }
+    }
+
+    pub fn paint<T: Display>(&self, text: T, color: Color) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub fn answer<T: Display>(&self, answer: T) -> String {
+        self.paint(answer, Green)
+    }
+
+    pub fn error<T: Display>(&self, error: T) -> String {
+        self.paint(error, Red)
+    }
+
+    /// Draws one character per cell, as given by `cell` along with the
+    /// cell's color, if any.
+    pub fn grid<T, F>(&self, grid: &Grid<T>, mut cell: F) -> String
+    where
+        F: FnMut(&T) -> (char, Option<Color>),
+    {
+        let mut drawing = String::new();
+        for row in grid.rows() {
+            for value in row {
+                match cell(value) {
+                    (ch, Some(color)) => {
+                        drawing.push_str(&self.paint(ch, color))
+                    }
+                    (ch, None) => drawing.push(ch),
+                }
+            }
+            drawing.push('\n');
+        }
+        drawing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paints_only_when_enabled() {
+        let grid = Grid::from_fn(2, 2, |x, y| x == y);
+        let draw =
+            |&dot: &bool| if dot { ('#', Some(Red)) } else { ('.', None) };
+
+        let plain = Pretty::new(ColorChoice::Never);
+        assert_eq!(plain.answer(42), "42");
+        assert_eq!(plain.grid(&grid, draw), "#.\n.#\n");
+
+        let colored = Pretty::new(ColorChoice::Always);
+        assert_eq!(colored.error("oops"), "\x1b[31moops\x1b[0m");
+        assert_eq!(
+            colored.grid(&grid, draw),
+            "\x1b[31m#\x1b[0m.\n.\x1b[31m#\x1b[0m\n"
+        );
+        assert_eq!("never".parse(), Ok(ColorChoice::Never));
+    }
+}
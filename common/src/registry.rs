@@ -0,0 +1,161 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+/// A day's solver, registered by its crate with [`register_solution!`] so
+/// that runners can find every day linked into them without listing them.
+pub struct Solution {
+    pub day: u8,
+    /// Parameters that can be changed from the values in the puzzle
+    /// description, along with those values.
+    pub params: &'static [(&'static str, u64)],
+    /// Parses the raw input and reports the answer to each part in turn.
+    pub solve: fn(&str, &Params, &mut Report) -> Result<(), String>,
+}
+
+inventory::collect!(Solution);
+
+impl Solution {
+    /// Every registered solution, by day.
+    pub fn all() -> Vec<&'static Solution> {
+        let mut solutions = inventory::iter::<Solution>().collect::<Vec<_>>();
+        solutions.sort_unstable_by_key(|solution| solution.day);
+        solutions
+    }
+
+    pub fn find(day: u8) -> Option<&'static Solution> {
+        inventory::iter::<Solution>().find(|solution| solution.day == day)
+    }
+
+    /// The parameters with the values in the puzzle description.
+    pub fn default_params(&self) -> Params {
+        let mut params = Params::default();
+        for &(name, value) in self.params {
+            params.set(name, value);
+        }
+        params
+    }
+}
+
+/// Registers a day's [`Solution`], optionally with parameters:
+///
+/// ```ignore
+/// register_solution!(day: 6, params: &[("days", 80)], solve: solve);
+/// ```
+///
+/// Runners need to use the day's crate, if only with `use dayNN as _;`, for
+/// the registration to be linked into them. With the day's `wasm` feature,
+/// the macro also defines a `solve_str` function that is exported to
+/// JavaScript as `solveStr(input)`.
+#[macro_export]
+macro_rules! register_solution {
+    (day: $day:expr, params: $params:expr, solve: $solve:expr $(,)?) => {
+        $crate::inventory::submit! {
+            $crate::registry::Solution {
+                day: $day,
+                params: $params,
+                solve: $solve,
+            }
+        }
+
+        /// Solves the puzzle from its raw input, returning one entry per
+        /// part: the answer as a string, or `null` if no answer was found.
+        #[cfg(feature = "wasm")]
+        #[$crate::wasm::wasm_bindgen::prelude::wasm_bindgen(
+            js_name = solveStr,
+            wasm_bindgen = $crate::wasm::wasm_bindgen
+        )]
+        pub fn solve_str(input: &str) -> $crate::wasm::JsAnswers {
+            let solution = $crate::registry::Solution {
+                day: $day,
+                params: $params,
+                solve: $solve,
+            };
+            $crate::wasm::solve_str(&solution, input)
+        }
+    };
+    (day: $day:expr, solve: $solve:expr $(,)?) => {
+        $crate::register_solution!(day: $day, params: &[], solve: $solve);
+    };
+}
+
+/// Values of a day's parameters, by name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Params(BTreeMap<String, u64>);
+
+impl Params {
+    pub fn set(&mut self, name: &str, value: u64) {
+        self.0.insert(name.to_string(), value);
+    }
+
+    pub fn get<T: TryFrom<u64>>(&self, name: &str) -> Result<T, String> {
+        let value = self
+            .0
+            .get(name)
+            .ok_or_else(|| format!("Missing parameter '{}'", name))?;
+        T::try_from(*value)
+            .map_err(|_| format!("Parameter '{}' is too large", name))
+    }
+}
+
+/// Passes on a day's answers as soon as each part is solved, which lets
+/// runners measure every part separately.
+pub struct Report<'a> {
+    on_answer: &'a mut dyn FnMut(Option<String>),
+}
+
+impl<'a> Report<'a> {
+    pub fn new(on_answer: &'a mut dyn FnMut(Option<String>)) -> Self {
+        Self { on_answer }
+    }
+
+    pub fn found<T: Display>(&mut self, answer: T) {
+        (self.on_answer)(Some(answer.to_string()));
+    }
+
+    pub fn maybe<T: Display>(&mut self, answer: Option<T>) {
+        (self.on_answer)(answer.map(|value| value.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn solve(
+        input: &str,
+        params: &Params,
+        report: &mut Report,
+    ) -> Result<(), String> {
+        let repeat = params.get::<usize>("repeat")?;
+        report.found(input.repeat(repeat));
+        report.maybe(None::<u8>);
+        Ok(())
+    }
+
+    register_solution!(day: 99, params: &[("repeat", 2)], solve: solve);
+
+    #[test]
+    fn finds_registered_solutions() {
+        let solution = Solution::find(99).unwrap();
+        assert!(Solution::all().iter().any(|solution| solution.day == 99));
+
+        let mut answers = vec![];
+        let mut params = solution.default_params();
+        params.set("repeat", 3);
+        (solution.solve)(
+            "ab",
+            &params,
+            &mut Report::new(&mut |answer| answers.push(answer)),
+        )
+        .unwrap();
+        assert_eq!(answers, [Some("ababab".to_string()), None]);
+        assert_eq!(
+            params.get::<u8>("missing"),
+            Err("Missing parameter 'missing'".to_string())
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::SearchStats;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Finds the lowest cost of reaching a node for which `is_goal` holds,
+/// starting from `start`, using A* search.
+///
+/// `neighbors` yields each node reachable in one step with the cost of that
+/// step, and `heuristic` estimates the remaining cost from a node to the
+/// nearest goal; it must never overestimate, and `|_| 0` turns the search
+/// into plain Dijkstra.
+pub fn shortest_path<N, C, FN, I, FG, FH>(
+    start: N,
+    neighbors: FN,
+    is_goal: FG,
+    heuristic: FH,
+) -> Option<C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Default + Ord + Add<Output = C>,
+    FN: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    FG: FnMut(&N) -> bool,
+    FH: FnMut(&N) -> C,
+{
+    let mut stats = SearchStats::default();
+    shortest_path_with_stats(start, neighbors, is_goal, heuristic, &mut stats)
+}
+
+/// Same as [`shortest_path`], adding the work done to `stats`.
+pub fn shortest_path_with_stats<N, C, FN, I, FG, FH>(
+    start: N,
+    mut neighbors: FN,
+    mut is_goal: FG,
+    mut heuristic: FH,
+    stats: &mut SearchStats,
+) -> Option<C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Default + Ord + Add<Output = C>,
+    FN: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    FG: FnMut(&N) -> bool,
+    FH: FnMut(&N) -> C,
+{
+    // Nodes are kept in a vector so that the heap only holds indices and
+    // does not need to order the nodes themselves
+    let mut nodes = vec![start.clone()];
+    let mut lowest = vec![C::default()];
+    let mut indices = HashMap::from([(start.clone(), 0)]);
+    let mut heap =
+        BinaryHeap::from([Reverse((heuristic(&start), C::default(), 0))]);
+
+    while let Some(Reverse((_, cost, index))) = heap.pop() {
+        if cost > lowest[index] {
+            // Already reached this node through a cheaper path
+            stats.duplicates += 1;
+            continue;
+        }
+        if is_goal(&nodes[index]) {
+            return Some(cost);
+        }
+        stats.expanded += 1;
+
+        for (next, step_cost) in neighbors(&nodes[index]) {
+            let next_cost = cost + step_cost;
+            let next_index = match indices.entry(next) {
+                Entry::Occupied(entry) => {
+                    let next_index = *entry.get();
+                    if lowest[next_index] <= next_cost {
+                        stats.duplicates += 1;
+                        continue;
+                    }
+                    lowest[next_index] = next_cost;
+                    next_index
+                }
+                Entry::Vacant(entry) => {
+                    let next_index = nodes.len();
+                    nodes.push(entry.key().clone());
+                    lowest.push(next_cost);
+                    entry.insert(next_index);
+                    next_index
+                }
+            };
+            let estimate = next_cost + heuristic(&nodes[next_index]);
+            heap.push(Reverse((estimate, next_cost, next_index)));
+        }
+        stats.record_frontier(heap.len());
+        stats.record_visited(indices.len());
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_cheapest_route() {
+        // 0 -> 1 -> 3 costs 5, 0 -> 2 -> 3 costs 4
+        let edges = |node: &u8| match node {
+            0 => vec![(1, 1), (2, 3)],
+            1 => vec![(3, 4)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        };
+        assert_eq!(shortest_path(0, edges, |n| *n == 3, |_| 0), Some(4));
+        assert_eq!(shortest_path(0, edges, |n| *n == 4, |_| 0), None);
+        assert_eq!(shortest_path(3, edges, |n| *n == 3, |_| 0), Some(0));
+
+        let mut stats = SearchStats::default();
+        // Exhausts the graph, popping node 3 again at its costlier route
+        shortest_path_with_stats(0, edges, |n| *n == 4, |_| 0, &mut stats);
+        assert_eq!(stats.expanded, 4);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.peak_visited, 4);
+    }
+}
@@ -0,0 +1,40 @@
+use core::fmt::{Display, Formatter};
+
+/// Counters describing how much work a search did, for comparing
+/// algorithms on the same input.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SearchStats {
+    /// States whose successors were generated.
+    pub expanded: u64,
+    /// States dropped because they had already been reached at no higher
+    /// cost.
+    pub duplicates: u64,
+    /// Largest number of states waiting to be expanded at once.
+    pub peak_frontier: usize,
+    /// Largest number of states remembered at once.
+    pub peak_visited: usize,
+}
+
+impl SearchStats {
+    pub fn record_frontier(&mut self, size: usize) {
+        self.peak_frontier = self.peak_frontier.max(size);
+    }
+
+    pub fn record_visited(&mut self, size: usize) {
+        self.peak_visited = self.peak_visited.max(size);
+    }
+}
+
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} expanded, {} duplicates pruned, peak frontier {}, \
+            peak visited {}",
+            self.expanded,
+            self.duplicates,
+            self.peak_frontier,
+            self.peak_visited
+        )
+    }
+}
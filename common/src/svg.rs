@@ -0,0 +1,142 @@
+use std::fmt::{Display, Formatter, Write};
+use std::fs::write;
+use std::path::Path;
+
+/// A minimal SVG document builder for drawing puzzle states on an integer
+/// canvas; strokes keep the same width however far the image is zoomed.
+pub struct Svg {
+    view_box: (i64, i64, i64, i64),
+    size: Option<(i64, i64)>,
+    background: Option<String>,
+    elements: String,
+}
+
+impl Svg {
+    /// Creates an empty drawing showing the area from `(x, y)` spanning
+    /// `width` by `height` units.
+    pub fn new(x: i64, y: i64, width: i64, height: i64) -> Self {
+        Self {
+            view_box: (x, y, width.max(1), height.max(1)),
+            size: None,
+            background: None,
+            elements: String::new(),
+        }
+    }
+
+    /// Shows the drawing `width` by `height` pixels, rather than however
+    /// large the viewer decides.
+    pub fn size(mut self, width: i64, height: i64) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    pub fn background(mut self, color: &str) -> Self {
+        self.background = Some(color.to_string());
+        self
+    }
+
+    pub fn line(
+        &mut self,
+        (x1, y1): (i64, i64),
+        (x2, y2): (i64, i64),
+        color: &str,
+    ) -> &mut Self {
+        let _ = write!(
+            self.elements,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" {}/>"#,
+            x1, y1, x2, y2, color, THIN_STROKE
+        );
+        self.elements.push('\n');
+        self
+    }
+
+    pub fn polyline(
+        &mut self,
+        points: &[(i64, i64)],
+        color: &str,
+    ) -> &mut Self {
+        let points = points
+            .iter()
+            .map(|(x, y)| format!("{},{}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = write!(
+            self.elements,
+            r#"<polyline points="{}" fill="none" stroke="{}" {}/>"#,
+            points, color, THIN_STROKE
+        );
+        self.elements.push('\n');
+        self
+    }
+
+    pub fn rect(
+        &mut self,
+        (x, y): (i64, i64),
+        width: i64,
+        height: i64,
+        color: &str,
+    ) -> &mut Self {
+        let _ = write!(
+            self.elements,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            x, y, width, height, color
+        );
+        self.elements.push('\n');
+        self
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        write(path, self.to_string())
+    }
+}
+
+const THIN_STROKE: &str =
+    r#"stroke-width="1" vector-effect="non-scaling-stroke""#;
+
+impl Display for Svg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (x, y, width, height) = self.view_box;
+        let size = self
+            .size
+            .map(|(width, height)| {
+                format!(r#" width="{}" height="{}""#, width, height)
+            })
+            .unwrap_or_default();
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}"{} shape-rendering="crispEdges">"#,
+            x, y, width, height, size
+        )?;
+        if let Some(color) = &self.background {
+            writeln!(
+                f,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x, y, width, height, color
+            )?;
+        }
+        write!(f, "{}", self.elements)?;
+        writeln!(f, "</svg>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_elements() {
+        let mut svg = Svg::new(0, -2, 10, 5).background("white");
+        svg.line((0, 0), (3, 4), "red").rect((1, 1), 2, 2, "black");
+        svg.polyline(&[(0, 0), (1, -1)], "blue");
+        let text = svg.to_string();
+        assert!(text.starts_with("<svg") && text.ends_with("</svg>\n"));
+        assert!(text.contains(r#"viewBox="0 -2 10 5""#));
+        assert!(text.contains(r#"<line x1="0" y1="0" x2="3" y2="4""#));
+        assert!(text.contains(r#"points="0,0 1,-1""#));
+        assert_eq!(text.matches("<rect").count(), 2);
+        assert!(!text.contains("width=\"100\""));
+
+        let text = Svg::new(0, 0, 10, 5).size(100, 50).to_string();
+        assert!(text.contains(r#"width="100" height="50""#));
+    }
+}
@@ -0,0 +1,169 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::take;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Handle to a node in a [`Tree`]; only meaningful for the tree that
+/// created it.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct NodeId(usize);
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+struct Node<T> {
+    value: T,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+/// An arena of nodes linked to their parents and ordered children, which
+/// can be restructured in place without reallocating the rest of the tree.
+///
+/// Nodes are never freed: detached subtrees stay in the arena until the
+/// whole tree is dropped.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Tree<T> {
+    nodes: Vec<Node<T>>,
+}
+
+impl<T> Default for Tree<T> {
+    fn default() -> Self {
+        Self { nodes: Vec::new() }
+    }
+}
+
+impl<T> Tree<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of nodes in the arena, including detached ones.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds a node without a parent.
+    pub fn add(&mut self, value: T) -> NodeId {
+        self.add_with_children(value, Vec::new())
+    }
+
+    /// Adds a node as the parent of `children`, detaching them from any
+    /// previous parent.
+    pub fn add_with_children(
+        &mut self,
+        value: T,
+        children: Vec<NodeId>,
+    ) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            value,
+            parent: None,
+            children: Vec::new(),
+        });
+        self.set_children(id, children);
+        id
+    }
+
+    pub fn value(&self, id: NodeId) -> &T {
+        &self.nodes[id.0].value
+    }
+
+    pub fn value_mut(&mut self, id: NodeId) -> &mut T {
+        &mut self.nodes[id.0].value
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].parent
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id.0].children
+    }
+
+    /// Replaces the children of `id`; the old children become detached.
+    pub fn set_children(&mut self, id: NodeId, children: Vec<NodeId>) {
+        for old in take(&mut self.nodes[id.0].children) {
+            self.nodes[old.0].parent = None;
+        }
+        for &child in &children {
+            if let Some(parent) = self.nodes[child.0].parent {
+                self.nodes[parent.0].children.retain(|&c| c != child);
+            }
+            self.nodes[child.0].parent = Some(id);
+        }
+        self.nodes[id.0].children = children;
+    }
+
+    /// Number of ancestors of `id`.
+    pub fn depth(&self, id: NodeId) -> usize {
+        let mut depth = 0;
+        let mut node = id;
+        while let Some(parent) = self.parent(node) {
+            depth += 1;
+            node = parent;
+        }
+        depth
+    }
+
+    /// Nodes in the subtree rooted at `id`, parents before their children
+    /// and children in order.
+    pub fn preorder(&self, id: NodeId) -> Vec<NodeId> {
+        let mut order = Vec::new();
+        let mut stack = vec![id];
+        while let Some(node) = stack.pop() {
+            order.push(node);
+            stack.extend(self.children(node).iter().rev());
+        }
+        order
+    }
+
+    /// Copies the subtree rooted at `id` in `other` into this tree,
+    /// returning the id of its new, detached root.
+    pub fn graft(&mut self, other: &Tree<T>, id: NodeId) -> NodeId
+    where
+        T: Clone,
+    {
+        let children = other
+            .children(id)
+            .iter()
+            .map(|&child| self.graft(other, child))
+            .collect();
+        self.add_with_children(other.value(id).clone(), children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restructures_in_place() {
+        let mut tree = Tree::new();
+        let leaves = vec![tree.add('b'), tree.add('c')];
+        let root = tree.add_with_children('a', leaves.clone());
+        assert_eq!(tree.parent(leaves[1]), Some(root));
+        assert_eq!(tree.depth(leaves[0]), 1);
+
+        let d = tree.add('d');
+        tree.set_children(leaves[0], vec![d]);
+        let values = tree
+            .preorder(root)
+            .into_iter()
+            .map(|id| *tree.value(id))
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec!['a', 'b', 'd', 'c']);
+
+        let mut copy = Tree::new();
+        let copy_root = copy.graft(&tree, leaves[0]);
+        assert_eq!(copy.len(), 2);
+        assert_eq!(copy.parent(copy_root), None);
+        assert_eq!(*copy.value(copy.children(copy_root)[0]), 'd');
+    }
+}
@@ -0,0 +1,92 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::{replace, swap};
+
+/// Disjoint sets over the elements `0..len`, with union by size and path
+/// compression.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    sets: usize,
+}
+
+impl UnionFind {
+    /// Creates `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            sets: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Number of disjoint sets.
+    pub fn count_sets(&self) -> usize {
+        self.sets
+    }
+
+    /// Representative element of the set containing `element`.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = element;
+        while current != root {
+            current = replace(&mut self.parents[current], root);
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`, returning `false` if they
+    /// were already the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut root_a, mut root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+        if self.sizes[root_a] < self.sizes[root_b] {
+            swap(&mut root_a, &mut root_b);
+        }
+        self.parents[root_b] = root_a;
+        self.sizes[root_a] += self.sizes[root_b];
+        self.sets -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Number of elements in the set containing `element`.
+    pub fn set_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_sets() {
+        let mut sets = UnionFind::new(5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 1));
+        assert!(!sets.union(0, 3));
+        assert!(sets.connected(0, 3));
+        assert!(!sets.connected(0, 4));
+        assert_eq!(sets.set_size(1), 3);
+        assert_eq!(sets.count_sets(), 3);
+    }
+}
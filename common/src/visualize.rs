@@ -0,0 +1,24 @@
+use std::io::{stdout, Write};
+use std::thread::sleep;
+use std::time::Duration;
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// A puzzle simulation that can be drawn on the terminal, one frame per
+/// step.
+pub trait Visualize {
+    /// Frames to draw, in order, starting from the initial state.
+    fn frames(&self) -> impl Iterator<Item = String> + '_;
+
+    /// Draws every frame in place of the previous one, pausing for `delay`
+    /// after each.
+    fn visualize(&self, delay: Duration) -> std::io::Result<()> {
+        let mut out = stdout().lock();
+        for (frame, step) in self.frames().zip(0..) {
+            write!(out, "{}{}\nStep {}\n", CLEAR_SCREEN, frame, step)?;
+            out.flush()?;
+            sleep(delay);
+        }
+        Ok(())
+    }
+}
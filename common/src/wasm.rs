@@ -0,0 +1,22 @@
+use crate::registry::{Report, Solution};
+use alloc::vec::Vec;
+pub use wasm_bindgen;
+use wasm_bindgen::prelude::*;
+
+/// What the `solveStr` binding of each day returns: one entry per part, the
+/// answer as a string or `null` if no answer was found, or the error.
+pub type JsAnswers = Result<Vec<JsValue>, JsError>;
+
+/// Solves the puzzle with `solution` from its raw input, with the parameters
+/// in the puzzle description.
+pub fn solve_str(solution: &Solution, input: &str) -> JsAnswers {
+    let mut answers = Vec::new();
+    let mut on_answer = |answer| answers.push(answer);
+    (solution.solve)(
+        input,
+        &solution.default_params(),
+        &mut Report::new(&mut on_answer),
+    )
+    .map_err(|err| JsError::new(&err))?;
+    Ok(answers.into_iter().map(JsValue::from).collect())
+}
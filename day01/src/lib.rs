@@ -1,16 +1,304 @@
-pub fn part1(input: &[i32]) -> usize {
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::io::BufRead;
+
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+/// Readings can be of any type that can be compared, such as `i64` for very
+/// deep readings or `f64` for fractional ones.
+pub fn part1<T: PartialOrd>(input: &[T]) -> usize {
     input
         .windows(2)
         .filter(|window| window[0] < window[1])
         .count()
 }
 
-pub fn part2(input: &[i32]) -> usize {
+pub fn part2<T: PartialOrd>(input: &[T]) -> usize {
+    // Same shortcut as in `solve_stream`
     input
-        .windows(3)
-        .map(|window| window[0] + window[1] + window[2])
-        .collect::<Vec<i32>>()
-        .windows(2)
-        .filter(|sums| sums[0] < sums[1])
+        .windows(4)
+        .filter(|window| window[0] < window[3])
         .count()
 }
+
+/// Readings that can be averaged, by converting them to `f64`, which
+/// rounds integers beyond 2^53.
+pub trait ToF64: Copy {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_to_f64 {
+    ($($t:ty),*) => {
+        $(
+            impl ToF64 for $t {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_to_f64!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// Readings whose increases can be measured, as a type that fits any of
+/// them: unsigned for integers, since readings of a signed type can be
+/// further apart than it holds.
+pub trait Increase: Copy + PartialOrd {
+    type Amount: Copy + PartialOrd;
+
+    /// How much larger this reading is than `lower`.
+    fn increase_from(self, lower: Self) -> Self::Amount;
+}
+
+macro_rules! impl_integer_increase {
+    ($($t:ty => $amount:ty),*) => {
+        $(
+            impl Increase for $t {
+                type Amount = $amount;
+
+                fn increase_from(self, lower: Self) -> $amount {
+                    self.abs_diff(lower)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_increase!(
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize,
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, usize => usize
+);
+
+macro_rules! impl_float_increase {
+    ($($t:ty),*) => {
+        $(
+            impl Increase for $t {
+                type Amount = $t;
+
+                fn increase_from(self, lower: Self) -> $t {
+                    self - lower
+                }
+            }
+        )*
+    };
+}
+
+impl_float_increase!(f32, f64);
+
+/// Smooths out noise by replacing each `width` consecutive readings with
+/// their average, leaving `width - 1` fewer readings.
+///
+/// Panics if `width` is 0.
+pub fn moving_average<T: ToF64>(readings: &[T], width: usize) -> Vec<f64> {
+    readings
+        .windows(width)
+        .map(|window| {
+            window.iter().map(|&reading| reading.to_f64()).sum::<f64>()
+                / width as f64
+        })
+        .collect()
+}
+
+/// Smooths out noise by replacing each `width` consecutive readings with
+/// their median, or the lower of the two middle readings if `width` is even,
+/// leaving `width - 1` fewer readings.
+///
+/// Panics if `width` is 0.
+pub fn median_filter<T: Copy + PartialOrd>(
+    readings: &[T],
+    width: usize,
+) -> Vec<T> {
+    let mut sorted = Vec::with_capacity(width);
+    readings
+        .windows(width)
+        .map(|window| {
+            sorted.clear();
+            sorted.extend_from_slice(window);
+            let (_, median, _) = sorted
+                .select_nth_unstable_by((width - 1) / 2, |a, b| {
+                    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+                });
+            *median
+        })
+        .collect()
+}
+
+/// Where and by how much the readings increase, with increases of type `T`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IncreaseReport<T> {
+    /// Indices of the readings larger than the one before them.
+    pub increases: Vec<usize>,
+    /// Index of the reading with the largest increase, and the increase.
+    pub largest_increase: Option<(usize, T)>,
+    /// Indices of the longest run of readings that keep increasing.
+    pub longest_run: Range<usize>,
+}
+
+impl<T> IncreaseReport<T> {
+    /// Same as [`part1`].
+    pub fn count(&self) -> usize {
+        self.increases.len()
+    }
+}
+
+impl<T: Display> Display for IncreaseReport<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} increases", self.count())?;
+        if let Some((index, increase)) = &self.largest_increase {
+            write!(f, ", largest {} at reading {}", increase, index)?;
+        }
+        write!(
+            f,
+            ", longest run of {} readings from reading {}",
+            self.longest_run.len(),
+            self.longest_run.start
+        )
+    }
+}
+
+pub fn increase_report<T: Increase>(input: &[T]) -> IncreaseReport<T::Amount> {
+    let mut increases = Vec::new();
+    let mut largest_increase: Option<(usize, T::Amount)> = None;
+    let mut longest_run = 0..input.len().min(1);
+    let mut run_start = 0;
+    for (index, window) in (1..).zip(input.windows(2)) {
+        if window[0] < window[1] {
+            increases.push(index);
+            let increase = window[1].increase_from(window[0]);
+            if largest_increase.is_none_or(|(_, largest)| largest < increase) {
+                largest_increase = Some((index, increase));
+            }
+        } else {
+            run_start = index;
+        }
+        if index + 1 - run_start > longest_run.len() {
+            longest_run = run_start..index + 1;
+        }
+    }
+    IncreaseReport {
+        increases,
+        largest_increase,
+        longest_run,
+    }
+}
+
+/// Solves both parts in a single pass, keeping only the last three readings,
+/// so that they can come straight from [`parse_reader`].
+pub fn solve_stream<T, I, E>(readings: I) -> Result<(usize, usize), E>
+where
+    T: Copy + PartialOrd,
+    I: IntoIterator<Item = Result<T, E>>,
+{
+    let mut increases = 0;
+    let mut window_increases = 0;
+    // The last three readings, oldest first
+    let mut window = [None; 3];
+    for reading in readings {
+        let reading = reading?;
+        if window[2].is_some_and(|previous| previous < reading) {
+            increases += 1;
+        }
+        // Consecutive windows share two readings, so their sums only differ
+        // by the reading that enters and the one that leaves
+        if window[0].is_some_and(|leaving| leaving < reading) {
+            window_increases += 1;
+        }
+        window = [window[1], window[2], Some(reading)];
+    }
+    Ok((increases, window_increases))
+}
+
+pub fn parse_input<T>(input: &str) -> Result<Vec<T>, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect()
+}
+
+/// Parses one reading per line from `reader` as it's needed.
+#[cfg(feature = "std")]
+pub fn parse_reader<T, R>(reader: R) -> impl Iterator<Item = Result<T, String>>
+where
+    T: FromStr,
+    T::Err: Display,
+    R: BufRead,
+{
+    common::parse_lines(reader, |line| {
+        line.parse().map_err(|err: T::Err| err.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_other_reading_types() {
+        let deep =
+            parse_input::<i64>("4000000000\n4000000001\n3\n5000000000\n")
+                .unwrap();
+        assert_eq!((part1(&deep), part2(&deep)), (2, 1));
+
+        let fractional = [1.5, 1.25, 2.0, 2.5, 0.1, 7.0];
+        assert_eq!((part1(&fractional), part2(&fractional)), (3, 2));
+        assert_eq!(solve_stream(fractional.map(Ok::<_, ()>)), Ok((3, 2)));
+    }
+
+    #[test]
+    fn reports_increases() {
+        let readings = parse_input::<i32>(EXAMPLE).unwrap();
+        let report = increase_report(&readings);
+        assert_eq!(report.increases, [1, 2, 3, 5, 6, 7, 9]);
+        assert_eq!(report.count(), part1(&readings));
+        assert_eq!(report.largest_increase, Some((6, 33)));
+        assert_eq!(report.longest_run, 0..4);
+        assert_eq!(increase_report::<i32>(&[]).longest_run, 0..0);
+
+        let report = increase_report(&[i32::MIN, i32::MAX, i32::MIN]);
+        assert_eq!(report.largest_increase, Some((1, u32::MAX)));
+        let report = increase_report(&[0.5, 2.0]);
+        assert_eq!(report.largest_increase, Some((1, 1.5)));
+    }
+
+    #[test]
+    fn smooths_readings() {
+        let readings = [1, 9, 2, 3, 10, 4, 5];
+        assert_eq!(median_filter(&readings, 3), [2, 3, 3, 4, 5]);
+        assert_eq!(part1(&median_filter(&readings, 3)), 3);
+        assert_eq!(median_filter(&readings, 4), [2, 3, 3, 4]);
+        assert_eq!(
+            moving_average(&readings, 2),
+            [5.0, 5.5, 2.5, 6.5, 7.0, 4.5]
+        );
+        assert_eq!(moving_average(&readings, 8), []);
+        let deep: [i64; 3] = [1 << 60, 3 << 60, -(1 << 62)];
+        assert_eq!(moving_average(&deep, 2), [2f64.powi(61), -2f64.powi(59)]);
+        assert_eq!(median_filter(&readings, 1), readings);
+    }
+}
@@ -1,48 +1,157 @@
 use clap::{crate_description, App, Arg};
-use day01::{part1, part2};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::num::ParseIntError;
+use common::{open_or_example, read_or_example, Pretty, COLOR_CHOICES};
+use day01::{
+    increase_report, median_filter, moving_average, parse_input, parse_reader,
+    part1, part2, solve_stream, Increase, ToF64, EXAMPLE,
+};
+use std::fmt::Display;
 use std::process::exit;
+use std::str::FromStr;
 
 fn main() {
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("fractional")
+                .long("fractional")
+                .help("Accepts readings with a fractional part"),
+        )
+        .arg(
+            Arg::with_name("report")
+                .long("report")
+                .help("Prints where and by how much the readings increase"),
+        )
+        .arg(
+            Arg::with_name("smooth")
+                .long("smooth")
+                .takes_value(true)
+                .value_name("FILTER")
+                .possible_values(&["average", "median"])
+                .help("Smooths the readings before counting increases"),
+        )
+        .arg(
+            Arg::with_name("width")
+                .long("width")
+                .takes_value(true)
+                .default_value("3")
+                .validator(|value| match value.parse::<usize>() {
+                    Ok(width) if width > 0 => Ok(()),
+                    _ => Err("must be a positive number".to_string()),
+                })
+                .help("Number of readings each smoothed one is taken from"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
-        Ok(data) => data,
+    let filename = args.value_of("INPUT");
+    let fractional = args.is_present("fractional");
+    let smoothed = args.value_of("smooth").map(|filter| {
+        let width = args.value_of("width").unwrap().parse().unwrap();
+        if fractional {
+            smooth::<f64>(filename, filter, width)
+        } else {
+            smooth::<i64>(filename, filter, width)
+        }
+    });
+    let answers = match &smoothed {
+        Some(smoothed) => smoothed.clone().map(|(answers, _)| answers),
+        None if fractional => solve::<f64>(filename),
+        None => solve::<i64>(filename),
+    };
+    let (part1, part2) = match answers {
+        Ok(answers) => answers,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    println!("Part 1: {}", pretty.answer(part1));
+    println!("Part 2: {}", pretty.answer(part2));
+
+    if args.is_present("report") {
+        let report = match smoothed {
+            Some(smoothed) => smoothed.map(|(_, report)| report),
+            None if fractional => report::<f64>(filename),
+            None => report::<i64>(filename),
+        };
+        match report {
+            Ok(report) => println!("Report: {}", report),
+            Err(err) => println!(
+                "{}",
+                pretty.error(format!("Failed to report: {}", err))
+            ),
+        }
+    }
 }
 
-fn read_input(filename: &str) -> Result<Vec<i32>, String> {
-    let input_file = File::open(filename).map_err(|err| err.to_string())?;
-
-    BufReader::new(input_file)
-        .lines()
-        .zip(1..)
-        .map(|(line, line_num)| {
-            line.map_err(|err| (line_num, err.to_string()))
-                .and_then(|value| {
-                    value.parse().map_err(|err: ParseIntError| {
-                        (line_num, err.to_string())
-                    })
-                })
-        })
-        .collect::<Result<_, _>>()
-        .map_err(|(line_num, err)| format!("Line {}: {}", line_num, err))
+fn solve<T>(filename: Option<&str>) -> Result<(usize, usize), String>
+where
+    T: Copy + PartialOrd + FromStr,
+    T::Err: Display,
+{
+    solve_stream(parse_reader::<T, _>(open_or_example(filename, EXAMPLE)?))
+}
+
+fn report<T>(filename: Option<&str>) -> Result<String, String>
+where
+    T: Increase + FromStr,
+    T::Amount: Display,
+    T::Err: Display,
+{
+    // The report needs every reading at once, unlike solving
+    let readings = parse_input::<T>(&read_or_example(filename, EXAMPLE)?)?;
+    Ok(increase_report(&readings).to_string())
+}
+
+/// The answers for the readings smoothed by `filter`, along with their
+/// report.
+fn smooth<T>(
+    filename: Option<&str>,
+    filter: &str,
+    width: usize,
+) -> Result<((usize, usize), String), String>
+where
+    T: Increase + FromStr + ToF64,
+    T::Amount: Display,
+    T::Err: Display,
+{
+    // Smoothing needs every reading at once, unlike solving
+    let readings = parse_input::<T>(&read_or_example(filename, EXAMPLE)?)?;
+    Ok(match filter {
+        "median" => summarize(&median_filter(&readings, width)),
+        _ => summarize(&moving_average(&readings, width)),
+    })
+}
+
+fn summarize<T: Increase>(readings: &[T]) -> ((usize, usize), String)
+where
+    T::Amount: Display,
+{
+    let answers = (part1(readings), part2(readings));
+    (answers, increase_report(readings).to_string())
 }
@@ -0,0 +1,16 @@
+use crate::{parse_input, part1, part2};
+use alloc::string::String;
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let numbers = parse_input::<i64>(input)?;
+    report.found(part1(&numbers));
+    report.found(part2(&numbers));
+    Ok(())
+}
+
+register_solution!(day: 1, solve: solve);
@@ -1,20 +1,64 @@
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
 use std::str::FromStr;
 use Command::*;
 
-#[derive(Debug)]
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+/// Most `repeat` blocks that can be nested inside each other, as parsing
+/// and following them both go one level deeper for each.
+const MAX_NESTING: usize = 100;
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Command {
     Forward(i32),
     Down(i32),
     Up(i32),
 }
 
-#[derive(Default)]
-struct Position {
-    horizontal: i32,
-    depth: i32,
+/// A way of moving the submarine in response to commands.
+///
+/// Both parts of the puzzle are instances of the same simulation, differing
+/// only in how each command changes the submarine's state.
+pub trait Kinematics: Copy + Default {
+    fn update(&mut self, command: &Command);
+
+    fn horizontal(&self) -> i32;
+
+    fn depth(&self) -> i32;
+
+    /// Brings the submarine back up to depth 0.
+    fn surface(&mut self);
+}
+
+/// What to do when a command would take the submarine above the surface.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Surfacing {
+    /// Stops the course, reporting the offending command.
+    Error,
+    /// Stops the submarine at the surface.
+    Clamp,
+    /// Lets the depth go negative, as the puzzle does.
+    #[default]
+    Allow,
 }
 
-impl Position {
+/// Where the submarine is, following the rules of part 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+    pub horizontal: i32,
+    pub depth: i32,
+}
+
+impl Kinematics for Position {
     fn update(&mut self, command: &Command) {
         match command {
             Forward(units) => self.horizontal += units,
@@ -22,28 +66,30 @@ impl Position {
             Up(units) => self.depth -= units,
         }
     }
-}
 
-pub fn part1(input: &[Command]) -> i32 {
-    let final_position =
-        input
-            .iter()
-            .fold(Position::default(), |mut position, command| {
-                position.update(command);
-                position
-            });
+    fn horizontal(&self) -> i32 {
+        self.horizontal
+    }
 
-    final_position.horizontal * final_position.depth
+    fn depth(&self) -> i32 {
+        self.depth
+    }
+
+    fn surface(&mut self) {
+        self.depth = 0;
+    }
 }
 
-#[derive(Default)]
-struct AimedPosition {
-    horizontal: i32,
-    depth: i32,
-    aim: i32,
+/// Where the submarine is and where it's aiming, following the rules of
+/// part 2.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AimedPosition {
+    pub horizontal: i32,
+    pub depth: i32,
+    pub aim: i32,
 }
 
-impl AimedPosition {
+impl Kinematics for AimedPosition {
     fn update(&mut self, command: &Command) {
         match command {
             Forward(units) => {
@@ -54,18 +100,278 @@ impl AimedPosition {
             Up(units) => self.aim -= units,
         }
     }
+
+    fn horizontal(&self) -> i32 {
+        self.horizontal
+    }
+
+    fn depth(&self) -> i32 {
+        self.depth
+    }
+
+    fn surface(&mut self) {
+        self.depth = 0;
+    }
+}
+
+/// Follows every command from the starting state, returning the horizontal
+/// position multiplied by the depth at the end.
+pub fn simulate<K: Kinematics>(commands: &[Command]) -> i32 {
+    let final_state =
+        commands.iter().fold(K::default(), |mut state, command| {
+            state.update(command);
+            state
+        });
+
+    final_state.horizontal() * final_state.depth()
+}
+
+/// Same as [`simulate`], applying `surfacing` whenever a command takes the
+/// submarine above the surface.
+pub fn simulate_with<K: Kinematics>(
+    commands: &[Command],
+    surfacing: Surfacing,
+) -> Result<i32, String> {
+    let mut state = K::default();
+    for (command, command_num) in commands.iter().zip(1..) {
+        follow(&mut state, command, command_num, surfacing)?;
+    }
+
+    Ok(state.horizontal() * state.depth())
+}
+
+/// Applies `command`, the `command_num`th of the course, to `state`.
+fn follow<K: Kinematics>(
+    state: &mut K,
+    command: &Command,
+    command_num: usize,
+    surfacing: Surfacing,
+) -> Result<(), String> {
+    state.update(command);
+    if state.depth() < 0 {
+        match surfacing {
+            Surfacing::Error => {
+                return Err(format!(
+                    "Command {} ({}) rises above the surface to depth {}",
+                    command_num,
+                    command,
+                    state.depth()
+                ))
+            }
+            Surfacing::Clamp => state.surface(),
+            Surfacing::Allow => (),
+        }
+    }
+    Ok(())
+}
+
+pub fn part1(input: &[Command]) -> i32 {
+    simulate::<Position>(input)
 }
 
 pub fn part2(input: &[Command]) -> i32 {
-    let final_position =
-        input
-            .iter()
-            .fold(AimedPosition::default(), |mut position, command| {
-                position.update(command);
-                position
-            });
+    simulate::<AimedPosition>(input)
+}
+
+/// The state after each command, following the rules of `K`.
+pub fn trajectory<'a, K: Kinematics + 'a>(
+    commands: &'a [Command],
+) -> impl Iterator<Item = K> + 'a {
+    commands.iter().scan(K::default(), |state, command| {
+        state.update(command);
+        Some(*state)
+    })
+}
+
+/// Movement models that can work out how to reach a given position.
+pub trait Navigable: Kinematics {
+    /// The shortest sequence of commands, all with positive units, that takes
+    /// the submarine from the start to `horizontal` and `depth`.
+    fn plan_course(horizontal: i32, depth: i32)
+        -> Result<Vec<Command>, String>;
+}
+
+fn check_horizontal(horizontal: i32) -> Result<(), String> {
+    if horizontal < 0 {
+        return Err(format!(
+            "Can't reach horizontal position {} moving forward",
+            horizontal
+        ));
+    }
+    Ok(())
+}
+
+/// The command that changes depth, or aim, by `units`, if any.
+fn vertical(units: i32) -> Result<Option<Command>, String> {
+    match units {
+        0 => Ok(None),
+        1.. => Ok(Some(Down(units))),
+        _ => units
+            .checked_neg()
+            .map(|units| Some(Up(units)))
+            .ok_or_else(|| format!("Can't go up by {} in one command", units)),
+    }
+}
+
+impl Navigable for Position {
+    fn plan_course(
+        horizontal: i32,
+        depth: i32,
+    ) -> Result<Vec<Command>, String> {
+        check_horizontal(horizontal)?;
+        let forward = (horizontal > 0).then_some(Forward(horizontal));
+        Ok(forward.into_iter().chain(vertical(depth)?).collect())
+    }
+}
+
+impl Navigable for AimedPosition {
+    fn plan_course(
+        horizontal: i32,
+        depth: i32,
+    ) -> Result<Vec<Command>, String> {
+        check_horizontal(horizontal)?;
+        if depth == 0 {
+            return Position::plan_course(horizontal, 0);
+        }
+        if horizontal == 0 {
+            return Err(format!(
+                "Can't reach depth {} without moving forward",
+                depth
+            ));
+        }
+        if depth % horizontal == 0 {
+            // Aim once, then go all the way
+            let aim = vertical(depth / horizontal)?;
+            return Ok(aim.into_iter().chain([Forward(horizontal)]).collect());
+        }
+        // Otherwise go most of the way level, and dive on the last unit
+        Ok([Forward(horizontal - 1)]
+            .into_iter()
+            .chain(vertical(depth)?)
+            .chain([Forward(1)])
+            .collect())
+    }
+}
+
+/// A line of a course program.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Step {
+    Command(Command),
+    /// A block of steps, followed the given number of times in a row.
+    Repeat(usize, Vec<Step>),
+    /// A named mark, recording where the submarine is each time it gets
+    /// there.
+    Waypoint(String),
+}
+
+/// The state at each waypoint reached, by name.
+pub type Waypoints<'a, K> = Vec<(&'a str, K)>;
+
+struct Run<'a, K> {
+    state: K,
+    surfacing: Surfacing,
+    commands_followed: usize,
+    waypoints: Waypoints<'a, K>,
+}
+
+impl<'a, K: Kinematics> Run<'a, K> {
+    fn steps(&mut self, steps: &'a [Step]) -> Result<(), String> {
+        for step in steps {
+            match step {
+                Step::Command(command) => {
+                    self.commands_followed += 1;
+                    follow(
+                        &mut self.state,
+                        command,
+                        self.commands_followed,
+                        self.surfacing,
+                    )?;
+                }
+                Step::Repeat(count, block) => {
+                    for _ in 0..*count {
+                        self.steps(block)?;
+                    }
+                }
+                Step::Waypoint(name) => self.waypoints.push((name, self.state)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Follows every step of `program` from the starting state, returning the
+/// final state and the state at each waypoint reached, in order.
+pub fn run_program<K: Kinematics>(
+    program: &[Step],
+    surfacing: Surfacing,
+) -> Result<(K, Waypoints<'_, K>), String> {
+    let mut run = Run {
+        state: K::default(),
+        surfacing,
+        commands_followed: 0,
+        waypoints: vec![],
+    };
+    run.steps(program)?;
+    Ok((run.state, run.waypoints))
+}
 
-    final_position.horizontal * final_position.depth
+/// Solves both parts in a single pass, so that the commands can come straight
+/// from [`parse_reader`].
+pub fn solve_stream<I, E>(commands: I) -> Result<(i32, i32), E>
+where
+    I: IntoIterator<Item = Result<Command, E>>,
+{
+    let mut position = Position::default();
+    let mut aimed_position = AimedPosition::default();
+    for command in commands {
+        let command = command?;
+        position.update(&command);
+        aimed_position.update(&command);
+    }
+    Ok((
+        position.horizontal * position.depth,
+        aimed_position.horizontal * aimed_position.depth,
+    ))
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Forward(units) => write!(f, "forward {}", units),
+            Down(units) => write!(f, "down {}", units),
+            Up(units) => write!(f, "up {}", units),
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "horizontal {}, depth {}", self.horizontal, self.depth)
+    }
+}
+
+impl Display for AimedPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "horizontal {}, depth {}, aim {}",
+            self.horizontal, self.depth, self.aim
+        )
+    }
+}
+
+impl FromStr for Surfacing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "error" => Ok(Surfacing::Error),
+            "clamp" => Ok(Surfacing::Clamp),
+            "allow" => Ok(Surfacing::Allow),
+            _ => Err(format!("Invalid surfacing policy '{}'", s)),
+        }
+    }
 }
 
 impl FromStr for Command {
@@ -89,3 +395,287 @@ impl FromStr for Command {
         }
     }
 }
+
+pub fn parse_input(input: &str) -> Result<Vec<Command>, String> {
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect()
+}
+
+/// Parses a course program: one command per line, as in the puzzle input,
+/// along with `waypoint <name>` lines and `repeat <count> {` ... `}` blocks.
+/// Blank lines are ignored.
+pub fn parse_program(input: &str) -> Result<Vec<Step>, String> {
+    parse_block(&mut input.lines().zip(1..), None, 0)
+}
+
+/// Parses the steps up to the end of the block opened at line `opened_at`,
+/// if any, which is nested inside `depth` others.
+fn parse_block<'a, I>(
+    lines: &mut I,
+    opened_at: Option<usize>,
+    depth: usize,
+) -> Result<Vec<Step>, String>
+where
+    I: Iterator<Item = (&'a str, usize)>,
+{
+    let mut steps = vec![];
+    while let Some((line, line_num)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "}" {
+            return match opened_at {
+                Some(_) => Ok(steps),
+                None => Err(format!("Line {}: Unexpected '}}'", line_num)),
+            };
+        }
+        let step = if let Some(count) = line
+            .strip_prefix("repeat ")
+            .and_then(|rest| rest.strip_suffix('{'))
+        {
+            let count = count.trim().parse().map_err(|err| {
+                format!(
+                    "Line {}: Invalid repeat count '{}': {}",
+                    line_num,
+                    count.trim(),
+                    err
+                )
+            })?;
+            if depth == MAX_NESTING {
+                return Err(format!(
+                    "Line {}: repeat blocks nested too deeply",
+                    line_num
+                ));
+            }
+            Step::Repeat(count, parse_block(lines, Some(line_num), depth + 1)?)
+        } else if let Some(name) = line.strip_prefix("waypoint ") {
+            Step::Waypoint(name.trim().to_string())
+        } else {
+            Step::Command(
+                line.parse()
+                    .map_err(|err| format!("Line {}: {}", line_num, err))?,
+            )
+        };
+        steps.push(step);
+    }
+
+    match opened_at {
+        Some(line_num) => {
+            Err(format!("Line {}: Unclosed repeat block", line_num))
+        }
+        None => Ok(steps),
+    }
+}
+
+/// Parses one command per line from `reader` as it's needed.
+pub fn parse_reader<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<Command, String>> {
+    common::parse_lines(reader, str::parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn command() -> impl Strategy<Value = Command> {
+        prop_oneof![
+            any::<i32>().prop_map(Forward),
+            any::<i32>().prop_map(Down),
+            any::<i32>().prop_map(Up),
+        ]
+    }
+
+    #[test]
+    fn follows_trajectories() {
+        let commands = parse_input(EXAMPLE).unwrap();
+        let positions = trajectory::<Position>(&commands).collect::<Vec<_>>();
+        assert_eq!(positions.len(), commands.len());
+        assert_eq!(
+            positions[3],
+            Position {
+                horizontal: 13,
+                depth: 2
+            }
+        );
+        assert_eq!(
+            trajectory::<AimedPosition>(&commands).last(),
+            Some(AimedPosition {
+                horizontal: 15,
+                depth: 60,
+                aim: 10
+            })
+        );
+    }
+
+    #[test]
+    fn supports_other_kinematics() {
+        // A current that carries the submarine one unit further forward with
+        // every command
+        #[derive(Clone, Copy, Default)]
+        struct Drifting(Position);
+
+        impl Kinematics for Drifting {
+            fn update(&mut self, command: &Command) {
+                self.0.update(command);
+                self.0.horizontal += 1;
+            }
+
+            fn horizontal(&self) -> i32 {
+                self.0.horizontal
+            }
+
+            fn depth(&self) -> i32 {
+                self.0.depth
+            }
+
+            fn surface(&mut self) {
+                self.0.surface();
+            }
+        }
+
+        let commands = parse_input(EXAMPLE).unwrap();
+        assert_eq!(simulate::<Drifting>(&commands), 21 * 10);
+    }
+
+    #[test]
+    fn applies_surfacing_policy() {
+        let commands = parse_input("down 2\nup 5\nforward 3\ndown 1").unwrap();
+        assert_eq!(
+            simulate_with::<Position>(&commands, Surfacing::Error),
+            Err("Command 2 (up 5) rises above the surface to depth -3".into())
+        );
+        assert_eq!(
+            simulate_with::<Position>(&commands, Surfacing::Clamp),
+            Ok(3)
+        );
+        assert_eq!(
+            simulate_with::<Position>(&commands, Surfacing::Allow),
+            Ok(part1(&commands))
+        );
+        // Only the depth is clamped, so the submarine keeps aiming upwards
+        assert_eq!(
+            simulate_with::<AimedPosition>(&commands, Surfacing::Clamp),
+            Ok(0)
+        );
+        assert_eq!(
+            simulate_with::<AimedPosition>(&commands, Surfacing::Error),
+            Err("Command 3 (forward 3) rises above the surface to depth -9"
+                .into())
+        );
+    }
+
+    #[test]
+    fn runs_programs() {
+        let program = parse_program(
+            "waypoint start\nrepeat 2 {\n  forward 5\n  repeat 2 {\n    \
+             down 1\n  }\n  waypoint dive\n}\n\nforward 5",
+        )
+        .unwrap();
+        assert_eq!(
+            run_program::<Position>(&program, Surfacing::Allow),
+            Ok((
+                Position {
+                    horizontal: 15,
+                    depth: 4
+                },
+                vec![
+                    ("start", Position::default()),
+                    (
+                        "dive",
+                        Position {
+                            horizontal: 5,
+                            depth: 2
+                        }
+                    ),
+                    (
+                        "dive",
+                        Position {
+                            horizontal: 10,
+                            depth: 4
+                        }
+                    ),
+                ]
+            ))
+        );
+        let commands = parse_input(EXAMPLE).unwrap();
+        let (end, _) = run_program::<AimedPosition>(
+            &parse_program(EXAMPLE).unwrap(),
+            Surfacing::Allow,
+        )
+        .unwrap();
+        assert_eq!(end.horizontal * end.depth, part2(&commands));
+        assert!(parse_program("repeat 2 {\nforward 1").is_err());
+        assert!(parse_program("forward 1\n}").is_err());
+        assert!(parse_program("repeat x {\n}").is_err());
+
+        let nested = |depth| {
+            "repeat 1 {\n".repeat(depth) + "forward 1\n" + &"}\n".repeat(depth)
+        };
+        assert!(parse_program(&nested(MAX_NESTING)).is_ok());
+        assert_eq!(
+            parse_program(&nested(50_000)).err(),
+            Some(format!(
+                "Line {}: repeat blocks nested too deeply",
+                MAX_NESTING + 1
+            ))
+        );
+    }
+
+    fn final_state<K: Kinematics>(commands: &[Command]) -> (i32, i32) {
+        let state = trajectory::<K>(commands).last().unwrap_or_default();
+        (state.horizontal(), state.depth())
+    }
+
+    #[test]
+    fn plans_courses() {
+        assert_eq!(Position::plan_course(0, 0), Ok(vec![]));
+        assert_eq!(
+            Position::plan_course(15, 10),
+            Ok(vec![Forward(15), Down(10)])
+        );
+        assert!(Position::plan_course(-1, 10).is_err());
+        assert_eq!(AimedPosition::plan_course(15, 0), Ok(vec![Forward(15)]));
+        assert_eq!(
+            AimedPosition::plan_course(15, -60),
+            Ok(vec![Up(4), Forward(15)])
+        );
+        assert_eq!(
+            AimedPosition::plan_course(15, 61),
+            Ok(vec![Forward(14), Down(61), Forward(1)])
+        );
+        assert!(AimedPosition::plan_course(0, 10).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn display_round_trips(command in command()) {
+            prop_assert_eq!(command.to_string().parse(), Ok(command));
+        }
+
+        #[test]
+        fn courses_reach_target(horizontal in 0..1000, depth in -1000..1000) {
+            let course = Position::plan_course(horizontal, depth).unwrap();
+            prop_assert_eq!(
+                final_state::<Position>(&course),
+                (horizontal, depth)
+            );
+            if let Ok(course) = AimedPosition::plan_course(horizontal, depth) {
+                prop_assert_eq!(
+                    final_state::<AimedPosition>(&course),
+                    (horizontal, depth)
+                );
+            } else {
+                prop_assert!(horizontal == 0 && depth != 0);
+            }
+        }
+    }
+}
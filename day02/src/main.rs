@@ -1,7 +1,10 @@
 use clap::{crate_description, App, Arg};
-use day02::{part1, part2, Command};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use common::{open_or_example, read_or_example, Pretty, COLOR_CHOICES};
+use day02::{
+    parse_input, parse_program, parse_reader, run_program, simulate_with,
+    solve_stream, trajectory, AimedPosition, Kinematics, Position, Surfacing,
+    EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
@@ -9,35 +12,118 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("trace")
+                .long("trace")
+                .help("Prints the position after each command"),
+        )
+        .arg(
+            Arg::with_name("program")
+                .long("program")
+                .conflicts_with("trace")
+                .help(
+                    "Reads the input as a course program, with repeat blocks \
+                    and waypoints",
+                ),
+        )
+        .arg(
+            Arg::with_name("surfacing")
+                .long("surfacing")
+                .takes_value(true)
+                .value_name("POLICY")
+                .possible_values(&["error", "clamp", "allow"])
+                .default_value("allow")
+                .help("What to do when the submarine rises above the surface"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
-        Ok(data) => data,
+    let filename = args.value_of("INPUT");
+    let surfacing = args.value_of("surfacing").unwrap().parse().unwrap();
+    let answers = if args.is_present("program") {
+        run(filename, surfacing)
+    } else {
+        solve(filename, surfacing)
+    };
+    let (part1, part2) = match answers {
+        Ok(answers) => answers,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!("{}", pretty.error(format!("Failed to solve: {}", err)));
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    if args.is_present("trace") {
+        if let Err(err) = trace(filename) {
+            println!("{}", pretty.error(format!("Failed to trace: {}", err)));
+        }
+    }
+
+    println!("Part 1: {}", pretty.answer(part1));
+    println!("Part 2: {}", pretty.answer(part2));
+}
+
+fn solve(
+    filename: Option<&str>,
+    surfacing: Surfacing,
+) -> Result<(i32, i32), String> {
+    if surfacing == Surfacing::Allow {
+        return solve_stream(parse_reader(open_or_example(filename, EXAMPLE)?));
+    }
+    // Each part may stop at a different command, so they can't share a pass
+    let commands = parse_input(&read_or_example(filename, EXAMPLE)?)?;
+    Ok((
+        simulate_with::<Position>(&commands, surfacing)?,
+        simulate_with::<AimedPosition>(&commands, surfacing)?,
+    ))
+}
+
+fn trace(filename: Option<&str>) -> Result<(), String> {
+    // Tracing needs every command at once, unlike solving
+    let commands = parse_input(&read_or_example(filename, EXAMPLE)?)?;
+    let positions = trajectory::<Position>(&commands)
+        .zip(trajectory::<AimedPosition>(&commands));
+    for (command, (position, aimed_position)) in commands.iter().zip(positions)
+    {
+        println!("{}: {} / {}", command, position, aimed_position);
+    }
+    Ok(())
 }
 
-fn read_input(filename: &str) -> Result<Vec<Command>, String> {
-    let input_file = File::open(filename).map_err(|err| err.to_string())?;
-
-    BufReader::new(input_file)
-        .lines()
-        .zip(1..)
-        .map(|(line, line_num)| {
-            line.map_err(|err| (line_num, err.to_string()))
-                .and_then(|value| value.parse().map_err(|err| (line_num, err)))
-        })
-        .collect::<Result<_, _>>()
-        .map_err(|(line_num, err)| format!("Line {}: {}", line_num, err))
+fn run(
+    filename: Option<&str>,
+    surfacing: Surfacing,
+) -> Result<(i32, i32), String> {
+    let program = parse_program(&read_or_example(filename, EXAMPLE)?)?;
+    let (end, waypoints) = run_program::<Position>(&program, surfacing)?;
+    let (aimed_end, aimed_waypoints) =
+        run_program::<AimedPosition>(&program, surfacing)?;
+    for ((name, position), (_, aimed_position)) in
+        waypoints.iter().zip(aimed_waypoints)
+    {
+        println!("{}: {} / {}", name, position, aimed_position);
+    }
+    Ok((
+        end.horizontal() * end.depth(),
+        aimed_end.horizontal() * aimed_end.depth(),
+    ))
 }
@@ -1,59 +1,556 @@
-const NUM_BITS: usize = 12;
-
-pub fn part1(numbers: &[u16]) -> u32 {
-    let mut gamma = 0;
-    let mut epsilon = 0;
-    let mut mask = 1;
-    for _ in 0..NUM_BITS {
-        let on_count = numbers.iter().filter(|&num| num & mask != 0).count();
-        if on_count >= numbers.len() / 2 {
-            gamma |= mask;
-        } else {
-            epsilon |= mask;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use common::{BitReader, BitWriter};
+use core::cmp::Ordering;
+use core::fmt::{self, Display, Formatter};
+use core::iter::once;
+use core::ops::Range;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::io::BufRead;
+
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+const MAX_BITS: usize = 64;
+
+/// Widest numbers that [`solve_stream`] counts instead of keeping.
+const HISTOGRAM_BITS: usize = 16;
+
+/// Which value of a bit counts as the most common when it's on in exactly half
+/// of the numbers; the other value then counts as the least common.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TieBreak {
+    /// As in the puzzle description.
+    #[default]
+    Ones,
+    Zeros,
+}
+
+impl TieBreak {
+    /// Whether one is the most common value of a bit that's on in `on_count`
+    /// of `total` numbers.
+    fn ones_most_common(self, on_count: usize, total: usize) -> bool {
+        match (2 * on_count).cmp(&total) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => self == TieBreak::Ones,
         }
-        mask <<= 1;
     }
-    u32::from(gamma) * u32::from(epsilon)
 }
 
-fn o2_gen_rating(numbers: &[u16]) -> Option<u16> {
-    let mut filtered = numbers.to_vec();
-    let mut mask = 1 << (NUM_BITS - 1);
-    while filtered.len() > 1 {
-        if mask == 0 {
-            return None;
+impl FromStr for TieBreak {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ones" => Ok(TieBreak::Ones),
+            "zeros" => Ok(TieBreak::Zeros),
+            _ => Err(format!("Invalid tie break '{}'", s)),
         }
-        let on_count = filtered.iter().filter(|&num| num & mask != 0).count();
-        if 2 * on_count >= filtered.len() {
-            filtered.retain(|&num| num & mask != 0);
-        } else {
-            filtered.retain(|&num| num & mask == 0);
+    }
+}
+
+/// The numbers in a diagnostic report, which all have `width` bits.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Readings {
+    pub width: usize,
+    pub numbers: Vec<u64>,
+}
+
+/// The numbers that have at most `width` bits.
+fn low_bits(width: usize) -> u64 {
+    u64::MAX.checked_shr((MAX_BITS - width) as u32).unwrap_or(0)
+}
+
+/// How many numbers have each bit set, which is all that the answer to part 1
+/// depends on.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BitCounts {
+    pub total: usize,
+    /// The count for each bit, starting from the least significant.
+    pub ones: Vec<usize>,
+}
+
+impl BitCounts {
+    pub fn new(width: usize) -> Self {
+        Self {
+            total: 0,
+            ones: vec![0; width],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.ones.len()
+    }
+
+    pub fn add(&mut self, number: u64) {
+        self.total += 1;
+        for (bit, count) in self.ones.iter_mut().enumerate() {
+            if number & 1 << bit != 0 {
+                *count += 1;
+            }
+        }
+    }
+
+    pub fn gamma_epsilon(&self, tie: TieBreak) -> (u64, u64) {
+        let mut gamma = 0;
+        let mut epsilon = 0;
+        for (bit, &on_count) in self.ones.iter().enumerate() {
+            if tie.ones_most_common(on_count, self.total) {
+                gamma |= 1 << bit;
+            } else {
+                epsilon |= 1 << bit;
+            }
         }
-        mask >>= 1;
+        (gamma, epsilon)
+    }
+
+    pub fn power_consumption(&self, tie: TieBreak) -> u128 {
+        let (gamma, epsilon) = self.gamma_epsilon(tie);
+        u128::from(gamma) * u128::from(epsilon)
     }
-    filtered.pop()
 }
 
-fn co2_scrub_rating(numbers: &[u16]) -> Option<u16> {
-    let mut filtered = numbers.to_vec();
-    let mut mask = 1 << (NUM_BITS - 1);
-    while filtered.len() > 1 {
-        if mask == 0 {
-            return None;
+impl From<&Readings> for BitCounts {
+    fn from(readings: &Readings) -> Self {
+        let mut counts = Self::new(readings.width);
+        for &number in &readings.numbers {
+            counts.add(number);
         }
-        let on_count = filtered.iter().filter(|&num| num & mask != 0).count();
-        if 2 * on_count >= filtered.len() {
-            filtered.retain(|&num| num & mask == 0);
+        counts
+    }
+}
+
+/// Counts the bits of each number, each with its width, as they come, e.g.
+/// from [`parse_reader`].
+pub fn count_bits<I>(numbers: I) -> Result<BitCounts, String>
+where
+    I: IntoIterator<Item = Result<(u64, usize), String>>,
+{
+    let mut counts: Option<BitCounts> = None;
+    for (number, line_num) in numbers.into_iter().zip(1..) {
+        let (number, width) = number?;
+        let counts = counts.get_or_insert_with(|| BitCounts::new(width));
+        check_width(width, counts.width())
+            .map_err(|err| format!("Line {}: {}", line_num, err))?;
+        counts.add(number);
+    }
+    Ok(counts.unwrap_or_default())
+}
+
+/// Same as [`count_bits`], for the numbers in `reader`, one per line.
+#[cfg(feature = "std")]
+pub fn count_reader<R: BufRead>(reader: R) -> Result<BitCounts, String> {
+    count_bits(parse_reader(reader))
+}
+
+pub fn part1(readings: &Readings) -> u128 {
+    part1_with(readings, TieBreak::default())
+}
+
+pub fn part1_with(readings: &Readings, tie: TieBreak) -> u128 {
+    BitCounts::from(readings).power_consumption(tie)
+}
+
+/// Narrows down the numbers one bit at a time, starting from the most
+/// significant, keeping those with the most common value of the bit or else
+/// the least common one. The numbers are sorted, so those sharing the bits
+/// looked at so far are always a single run, split in two by the next bit.
+fn rating(
+    sorted: &[u64],
+    width: usize,
+    most_common: bool,
+    tie: TieBreak,
+) -> Option<u64> {
+    let mut run = sorted;
+    let mut bits = (0..width).rev();
+    while run.len() > 1 {
+        let mask = 1 << bits.next()?;
+        let (off, on) =
+            run.split_at(run.partition_point(|num| num & mask == 0));
+        run = if tie.ones_most_common(on.len(), run.len()) == most_common {
+            on
         } else {
-            filtered.retain(|&num| num & mask != 0);
+            off
+        };
+    }
+    run.first().copied()
+}
+
+/// The oxygen generator and CO2 scrubber ratings.
+fn ratings(readings: &Readings, tie: TieBreak) -> (Option<u64>, Option<u64>) {
+    let width = readings.width;
+    let mut sorted = readings.numbers.clone();
+    // Only the low bits count towards the ratings
+    sorted.sort_unstable_by_key(|num| num & low_bits(width));
+    (
+        rating(&sorted, width, true, tie),
+        rating(&sorted, width, false, tie),
+    )
+}
+
+pub fn part2(readings: &Readings) -> Option<u128> {
+    part2_with(readings, TieBreak::default())
+}
+
+pub fn part2_with(readings: &Readings, tie: TieBreak) -> Option<u128> {
+    match ratings(readings, tie) {
+        (Some(o2), Some(co2)) => Some(u128::from(o2) * u128::from(co2)),
+        _ => None,
+    }
+}
+
+/// Every rate and rating worked out from the diagnostic report, not just the
+/// products that answer the puzzle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DiagnosticReport {
+    pub width: usize,
+    pub gamma: u64,
+    pub epsilon: u64,
+    pub o2_generator: Option<u64>,
+    pub co2_scrubber: Option<u64>,
+}
+
+impl DiagnosticReport {
+    pub fn power_consumption(&self) -> u128 {
+        u128::from(self.gamma) * u128::from(self.epsilon)
+    }
+
+    pub fn life_support_rating(&self) -> Option<u128> {
+        Some(u128::from(self.o2_generator?) * u128::from(self.co2_scrubber?))
+    }
+
+    pub fn gamma_bits(&self) -> String {
+        self.binary(self.gamma)
+    }
+
+    pub fn epsilon_bits(&self) -> String {
+        self.binary(self.epsilon)
+    }
+
+    pub fn o2_generator_bits(&self) -> Option<String> {
+        self.o2_generator.map(|rating| self.binary(rating))
+    }
+
+    pub fn co2_scrubber_bits(&self) -> Option<String> {
+        self.co2_scrubber.map(|rating| self.binary(rating))
+    }
+
+    fn binary(&self, number: u64) -> String {
+        format!("{:0width$b}", number, width = self.width)
+    }
+}
+
+impl Display for DiagnosticReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Gamma rate: {} ({})", self.gamma_bits(), self.gamma)?;
+        writeln!(
+            f,
+            "Epsilon rate: {} ({})",
+            self.epsilon_bits(),
+            self.epsilon
+        )?;
+        for (name, rating) in [
+            ("Oxygen generator rating", self.o2_generator),
+            ("CO2 scrubber rating", self.co2_scrubber),
+        ] {
+            match rating {
+                Some(rating) => writeln!(
+                    f,
+                    "{}: {} ({})",
+                    name,
+                    self.binary(rating),
+                    rating
+                )?,
+                None => writeln!(f, "{}: not found", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn diagnostics(readings: &Readings) -> DiagnosticReport {
+    diagnostics_with(readings, TieBreak::default())
+}
+
+pub fn diagnostics_with(
+    readings: &Readings,
+    tie: TieBreak,
+) -> DiagnosticReport {
+    let (gamma, epsilon) = BitCounts::from(readings).gamma_epsilon(tie);
+    let (o2_generator, co2_scrubber) = ratings(readings, tie);
+    DiagnosticReport {
+        width: readings.width,
+        gamma,
+        epsilon,
+        o2_generator,
+        co2_scrubber,
+    }
+}
+
+/// How many times each number appears in the report, which is all that the
+/// answers depend on, in a fixed amount of memory however long the report.
+struct Histogram(Vec<usize>);
+
+impl Histogram {
+    fn new(width: usize) -> Self {
+        Self(vec![0; 1 << width])
+    }
+
+    fn width(&self) -> usize {
+        self.0.len().trailing_zeros() as usize
+    }
+
+    fn count(&self, range: Range<usize>) -> usize {
+        self.0[range].iter().sum()
+    }
+
+    fn bit_counts(&self) -> BitCounts {
+        let mut counts = BitCounts::new(self.width());
+        counts.total = self.count(0..self.0.len());
+        for (bit, on_count) in counts.ones.iter_mut().enumerate() {
+            *on_count = (0..self.0.len())
+                .filter(|num| num & 1 << bit != 0)
+                .map(|num| self.0[num])
+                .sum();
+        }
+        counts
+    }
+
+    /// Same as [`rating`], where the numbers sharing the bits looked at so
+    /// far are next to each other in the histogram.
+    fn rating(&self, most_common: bool, tie: TieBreak) -> Option<u64> {
+        let mut range = 0..self.0.len();
+        loop {
+            let total = self.count(range.clone());
+            if total == 1 {
+                let offset =
+                    self.0[range.clone()].iter().position(|&c| c > 0)?;
+                return u64::try_from(range.start + offset).ok();
+            }
+            if total == 0 || range.len() == 1 {
+                return None;
+            }
+            let middle = range.start + range.len() / 2;
+            let on_count = self.count(middle..range.end);
+            if tie.ones_most_common(on_count, total) == most_common {
+                range.start = middle;
+            } else {
+                range.end = middle;
+            }
         }
-        mask >>= 1;
     }
-    filtered.pop()
+
+    fn life_support_rating(&self, tie: TieBreak) -> Option<u128> {
+        let o2 = self.rating(true, tie)?;
+        let co2 = self.rating(false, tie)?;
+        Some(u128::from(o2) * u128::from(co2))
+    }
 }
 
-pub fn part2(numbers: &[u16]) -> Option<u32> {
-    let o2 = o2_gen_rating(numbers)?;
-    let co2 = co2_scrub_rating(numbers)?;
-    Some(u32::from(o2) * u32::from(co2))
+fn check_width(width: usize, expected: usize) -> Result<(), String> {
+    if width != expected {
+        return Err(format!("Expected {} bits, found {}", expected, width));
+    }
+    Ok(())
+}
+
+/// Solves both parts in a single pass, so that the numbers, each with its
+/// width, can come straight from [`parse_reader`]. Narrow numbers are counted
+/// instead of kept.
+pub fn solve_stream<I>(numbers: I) -> Result<(u128, Option<u128>), String>
+where
+    I: IntoIterator<Item = Result<(u64, usize), String>>,
+{
+    solve_stream_with(numbers, TieBreak::default())
+}
+
+/// Same as [`solve_stream`], breaking ties as `tie` says.
+pub fn solve_stream_with<I>(
+    numbers: I,
+    tie: TieBreak,
+) -> Result<(u128, Option<u128>), String>
+where
+    I: IntoIterator<Item = Result<(u64, usize), String>>,
+{
+    let mut numbers = numbers.into_iter();
+    let (first, width) = match numbers.next().transpose()? {
+        Some(number) => number,
+        None => return Ok((0, None)),
+    };
+    let numbers = once(Ok((first, width))).chain(numbers).zip(1..).map(
+        |(number, line_num)| {
+            let (number, number_width) = number?;
+            check_width(number_width, width)
+                .map_err(|err| format!("Line {}: {}", line_num, err))?;
+            Ok(number)
+        },
+    );
+
+    if width > HISTOGRAM_BITS {
+        // Too wide to count every possible number
+        let readings = Readings {
+            width,
+            numbers: numbers.collect::<Result<_, String>>()?,
+        };
+        return Ok((part1_with(&readings, tie), part2_with(&readings, tie)));
+    }
+    let mut histogram = Histogram::new(width);
+    for number in numbers {
+        histogram.0[number? as usize] += 1;
+    }
+    Ok((
+        histogram.bit_counts().power_consumption(tie),
+        histogram.life_support_rating(tie),
+    ))
+}
+
+/// A binary number, with how many bits it was written with.
+fn parse_number(line: &str) -> Result<(u64, usize), String> {
+    if line.len() > MAX_BITS {
+        return Err(format!("Expected at most {} bits", MAX_BITS));
+    }
+    let mut bits = BitWriter::new();
+    for ch in line.chars() {
+        match ch {
+            '0' => bits.push(0, 1),
+            '1' => bits.push(1, 1),
+            _ => return Err(format!("Invalid bit '{}'", ch)),
+        }
+    }
+    BitReader::new(bits.as_bytes())
+        .pop(bits.len())
+        .map(|number| (number, bits.len()))
+        .ok_or_else(|| format!("Invalid number '{}'", line))
+}
+
+/// Parses one binary number per line; they must all have the same number of
+/// bits, up to 64.
+pub fn parse_input(input: &str) -> Result<Readings, String> {
+    let mut readings = Readings::default();
+    for (line, line_num) in input.lines().zip(1..) {
+        let (number, width) = parse_number(line)
+            .and_then(|(number, width)| {
+                if line_num > 1 {
+                    check_width(width, readings.width)?;
+                }
+                Ok((number, width))
+            })
+            .map_err(|err| format!("Line {}: {}", line_num, err))?;
+        readings.width = width;
+        readings.numbers.push(number);
+    }
+    Ok(readings)
+}
+
+/// Parses one binary number per line from `reader` as it's needed, along with
+/// how many bits it has.
+#[cfg(feature = "std")]
+pub fn parse_reader<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<(u64, usize), String>> {
+    common::parse_lines(reader, parse_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn readings(width: usize, numbers: &[u64]) -> Readings {
+        Readings {
+            width,
+            numbers: numbers.to_vec(),
+        }
+    }
+
+    #[test]
+    fn breaks_ties() {
+        let readings = readings(2, &[0b00, 0b01, 0b10, 0b11]);
+        let ones = diagnostics_with(&readings, TieBreak::Ones);
+        assert_eq!((ones.gamma, ones.epsilon), (0b11, 0b00));
+        assert_eq!((ones.o2_generator, ones.co2_scrubber), (Some(3), Some(0)));
+        let zeros = diagnostics_with(&readings, TieBreak::Zeros);
+        assert_eq!((zeros.gamma, zeros.epsilon), (0b00, 0b11));
+        assert_eq!(
+            (zeros.o2_generator, zeros.co2_scrubber),
+            (Some(0), Some(3))
+        );
+        for tie in [TieBreak::Ones, TieBreak::Zeros] {
+            let stream = solve_stream_with(
+                readings.numbers.iter().map(|&num| Ok((num, 2))),
+                tie,
+            );
+            assert_eq!(
+                stream,
+                Ok((part1_with(&readings, tie), part2_with(&readings, tie)))
+            );
+        }
+    }
+
+    #[test]
+    fn stream_matches_slices() {
+        let example = parse_input(EXAMPLE).unwrap();
+        let wide = readings(40, &[1 << 39, (1 << 40) - 1, 5, 1 << 20]);
+        for readings in [
+            readings(1, &[1]),
+            readings(12, &[5, 5]),
+            readings(12, &[4095, 0]),
+            example,
+            wide,
+        ] {
+            let width = readings.width;
+            let stream = solve_stream(
+                readings.numbers.iter().map(|&num| Ok((num, width))),
+            );
+            assert_eq!(stream, Ok((part1(&readings), part2(&readings))));
+        }
+        assert_eq!(solve_stream([]), Ok((0, None)));
+        assert!(solve_stream([Ok((1, 2)), Ok((1, 3))]).is_err());
+    }
+
+    #[test]
+    fn counts_bits_from_reader() {
+        let counts = count_reader(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(counts.total, 12);
+        assert_eq!(counts.ones, vec![5, 7, 8, 5, 7]);
+        assert_eq!(counts.power_consumption(TieBreak::Ones), 198);
+        assert_eq!(count_bits([]), Ok(BitCounts::default()));
+        assert!(count_reader("01\n011\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn supports_wide_numbers() {
+        let numbers = [u64::MAX, u64::MAX - 1, 1];
+        let report = diagnostics(&readings(64, &numbers));
+        assert_eq!(report.gamma, u64::MAX);
+        assert_eq!(report.o2_generator, Some(u64::MAX));
+        assert_eq!(report.co2_scrubber, Some(1));
+        assert_eq!(report.life_support_rating(), Some(u128::from(u64::MAX)));
+        let input = format!("{:064b}\n{:064b}\n", u64::MAX, 1);
+        assert_eq!(parse_input(&input), Ok(readings(64, &[u64::MAX, 1])));
+        assert!(parse_input("0101\n011").is_err());
+    }
+
+    #[test]
+    fn reports_diagnostics() {
+        let readings = readings(5, &[0b10110, 0b10111, 0b11100]);
+        let report = diagnostics(&readings);
+        assert_eq!(report.gamma_bits(), "10110");
+        assert_eq!(report.epsilon, 0b01001);
+        assert_eq!(report.o2_generator_bits(), Some("10111".into()));
+        assert_eq!(report.co2_scrubber, None);
+        assert_eq!(report.power_consumption(), part1(&readings));
+        assert_eq!(report.life_support_rating(), part2(&readings));
+    }
 }
@@ -1,8 +1,9 @@
 use clap::{crate_description, App, Arg};
-use day03::{part1, part2};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::num::ParseIntError;
+use common::{open_or_example, read_or_example, Pretty, COLOR_CHOICES};
+use day03::{
+    diagnostics_with, parse_input, parse_reader, solve_stream_with, TieBreak,
+    EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
@@ -10,42 +11,81 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("report")
+                .long("report")
+                .help("Prints every rate and rating, in binary and decimal"),
+        )
+        .arg(
+            Arg::with_name("ties")
+                .long("ties")
+                .takes_value(true)
+                .value_name("VALUE")
+                .possible_values(&["ones", "zeros"])
+                .default_value("ones")
+                .help(
+                    "Which bit value is the most common when there are as \
+                    many ones as zeros",
+                ),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
-        Ok(data) => data,
+    let filename = args.value_of("INPUT");
+    let tie = args.value_of("ties").unwrap().parse().unwrap();
+    let (part1, part2) = match solve(filename, tie) {
+        Ok(answers) => answers,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
-    match part2(&input) {
-        Some(answer) => println!("Part 2: {}", &answer),
+    if args.is_present("report") {
+        if let Err(err) = report(filename, tie) {
+            println!("{}", pretty.error(format!("Failed to report: {}", err)));
+        }
+    }
+
+    println!("Part 1: {}", pretty.answer(part1));
+    match part2 {
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
         None => println!("Part 2: Not found"),
     }
 }
 
-fn read_input(filename: &str) -> Result<Vec<u16>, String> {
-    let input_file = File::open(filename).map_err(|err| err.to_string())?;
+fn solve(
+    filename: Option<&str>,
+    tie: TieBreak,
+) -> Result<(u128, Option<u128>), String> {
+    solve_stream_with(parse_reader(open_or_example(filename, EXAMPLE)?), tie)
+}
 
-    BufReader::new(input_file)
-        .lines()
-        .zip(1..)
-        .map(|(line, line_num)| {
-            line.map_err(|err| (line_num, err.to_string()))
-                .and_then(|bits| {
-                    u16::from_str_radix(&bits, 2).map_err(
-                        |err: ParseIntError| (line_num, err.to_string()),
-                    )
-                })
-        })
-        .collect::<Result<_, _>>()
-        .map_err(|(line_num, err)| format!("Line {}: {}", line_num, err))
+fn report(filename: Option<&str>, tie: TieBreak) -> Result<(), String> {
+    // The report needs every number at once, unlike solving
+    let numbers = parse_input(&read_or_example(filename, EXAMPLE)?)?;
+    print!("{}", diagnostics_with(&numbers, tie));
+    Ok(())
 }
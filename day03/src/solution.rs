@@ -0,0 +1,16 @@
+use crate::{parse_input, part1, part2};
+use alloc::string::String;
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let readings = parse_input(input)?;
+    report.found(part1(&readings));
+    report.maybe(part2(&readings));
+    Ok(())
+}
+
+register_solution!(day: 3, solve: solve);
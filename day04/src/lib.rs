@@ -1,84 +1,458 @@
-use std::collections::HashMap;
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::num::ParseIntError;
 use std::str::FromStr;
+use Backend::*;
+
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
 
 pub const BOARD_SIZE: usize = 5;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Board {
     numbers: HashMap<i32, (usize, usize)>,
+    marked: HashSet<i32>,
     row_marks: Vec<usize>,
     col_marks: Vec<usize>,
     complete: bool,
 }
 
 impl Board {
-    fn mark(&mut self, number: i32) {
-        if let Some((row, col)) = self.numbers.remove(&number) {
-            self.row_marks[row] += 1;
-            self.col_marks[col] += 1;
-            if self.row_marks[row] == BOARD_SIZE
-                || self.col_marks[col] == BOARD_SIZE
-            {
-                self.complete = true;
-            }
+    /// Marks `number`, if it's on the board and hasn't been marked yet.
+    pub fn mark(&mut self, number: i32) {
+        if let Some(&(row, col)) = self.numbers.get(&number) {
+            self.mark_at(number, row, col);
+        }
+    }
+
+    fn mark_at(&mut self, number: i32, row: usize, col: usize) {
+        if !self.marked.insert(number) {
+            return;
+        }
+        self.row_marks[row] += 1;
+        self.col_marks[col] += 1;
+        if self.row_marks[row] == BOARD_SIZE
+            || self.col_marks[col] == BOARD_SIZE
+        {
+            self.complete = true;
         }
     }
 
+    /// The win, if marking `number` on the board, the `index`th in the input,
+    /// has just completed it.
+    fn win(
+        &self,
+        draw: usize,
+        number: i32,
+        index: usize,
+        scoring: Scoring,
+    ) -> Option<WinEvent> {
+        self.is_complete().then(|| WinEvent {
+            draw,
+            number,
+            board: index,
+            score: scoring.score(self, number),
+        })
+    }
+
     fn is_complete(&self) -> bool {
         self.complete
     }
 
-    fn sum_remaining(&self) -> i32 {
-        self.numbers.keys().sum()
+    fn sum(&self, marked: bool) -> i64 {
+        self.numbers
+            .keys()
+            .filter(|number| self.marked.contains(number) == marked)
+            .map(|&number| i64::from(number))
+            .sum()
+    }
+
+    /// The numbers in the row with `number`, if it's complete, or else in its
+    /// column.
+    fn line_with(&self, number: i32) -> Vec<i32> {
+        let Some(&(row, col)) = self.numbers.get(&number) else {
+            return vec![];
+        };
+        let row_complete = self.row_marks[row] == BOARD_SIZE;
+        self.numbers
+            .iter()
+            .filter(
+                |(_, &(r, c))| if row_complete { r == row } else { c == col },
+            )
+            .map(|(&number, _)| number)
+            .collect()
+    }
+
+    /// Writes the numbers in their rows and columns, passing each marked one
+    /// through `highlight` instead of padding it with spaces.
+    pub fn render_with_marks<F>(&self, mut highlight: F) -> String
+    where
+        F: FnMut(String) -> String,
+    {
+        let mut grid = [[0; BOARD_SIZE]; BOARD_SIZE];
+        for (&number, &(row, col)) in &self.numbers {
+            grid[row][col] = number;
+        }
+        let mut rendering = String::new();
+        for row in grid {
+            for number in row {
+                let cell = format!("{:>2}", number);
+                if self.marked.contains(&number) {
+                    rendering.push_str(&highlight(cell));
+                } else {
+                    rendering.push_str(&format!(" {} ", cell));
+                }
+            }
+            rendering.push('\n');
+        }
+        rendering
     }
 }
 
-pub fn part1(numbers: &[i32], boards: &[Board]) -> Option<i32> {
-    let mut boards = boards.to_vec();
-    for &number in numbers {
-        for board in boards.iter_mut() {
-            board.mark(number);
-            if board.is_complete() {
-                return Some(number * board.sum_remaining());
+/// A board completing a row or column.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WinEvent {
+    /// Position of the winning number among the numbers drawn.
+    pub draw: usize,
+    pub number: i32,
+    /// Position of the board in the input.
+    pub board: usize,
+    pub score: i64,
+}
+
+/// How a winning board is scored.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Scoring {
+    /// The sum of the unmarked numbers times the winning number, as in the
+    /// puzzle description.
+    #[default]
+    UnmarkedSum,
+    /// The sum of the marked numbers times the winning number.
+    MarkedSum,
+    /// The product of the numbers in the completed row or column.
+    LineProduct,
+}
+
+impl Scoring {
+    fn score(&self, board: &Board, number: i32) -> i64 {
+        match self {
+            Scoring::UnmarkedSum => i64::from(number) * board.sum(false),
+            Scoring::MarkedSum => i64::from(number) * board.sum(true),
+            Scoring::LineProduct => {
+                board.line_with(number).into_iter().map(i64::from).product()
             }
         }
     }
-    None
 }
 
-pub fn part2(numbers: &[i32], boards: &[Board]) -> Option<i32> {
-    let mut boards = boards.to_vec();
-    let mut last_win = None;
-    for &number in numbers {
-        for board in boards.iter_mut() {
+/// The order in which boards that complete on the same draw are taken to
+/// win.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TieBreak {
+    /// In the order they're in the input.
+    #[default]
+    InputOrder,
+    HighestScore,
+    LowestScore,
+}
+
+impl TieBreak {
+    fn sort(&self, wins: &mut [WinEvent]) {
+        match self {
+            TieBreak::InputOrder => (),
+            TieBreak::HighestScore => {
+                wins.sort_by_key(|win| Reverse(win.score))
+            }
+            TieBreak::LowestScore => wins.sort_by_key(|win| win.score),
+        }
+    }
+}
+
+/// Ways of finding the boards that a drawn number completes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Backend {
+    /// Marks the number on every board that hasn't won yet, on multiple
+    /// threads with the `parallel` feature.
+    #[default]
+    Scanning,
+    /// Looks up where the number is on every board, so that each draw only
+    /// touches the boards that have it.
+    Indexed,
+}
+
+impl common::Backend for Backend {
+    const ALL: &'static [Self] = &[Scanning, Indexed];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Scanning => "scanning",
+            Indexed => "indexed",
+        }
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+fn scan(
+    boards: &mut [Board],
+    draw: usize,
+    number: i32,
+    scoring: Scoring,
+) -> Vec<WinEvent> {
+    boards
+        .iter_mut()
+        .enumerate()
+        .filter(|(_, board)| !board.is_complete())
+        .filter_map(|(index, board)| {
             board.mark(number);
-            if board.is_complete() {
-                last_win = Some(number * board.sum_remaining())
+            board.win(draw, number, index, scoring)
+        })
+        .collect()
+}
+
+#[cfg(feature = "parallel")]
+fn scan(
+    boards: &mut [Board],
+    draw: usize,
+    number: i32,
+    scoring: Scoring,
+) -> Vec<WinEvent> {
+    boards
+        .par_iter_mut()
+        .enumerate()
+        .filter(|(_, board)| !board.is_complete())
+        .filter_map(|(index, board)| {
+            board.mark(number);
+            board.win(draw, number, index, scoring)
+        })
+        .collect()
+}
+
+/// Draws every number in turn, marking the boards that haven't won yet, and
+/// yields the wins on each draw that has any, in input order.
+fn wins_by_draw<'a>(
+    numbers: &'a [i32],
+    boards: &[Board],
+    scoring: Scoring,
+    backend: Backend,
+) -> Box<dyn Iterator<Item = Vec<WinEvent>> + 'a> {
+    let mut boards = boards.to_vec();
+    let draws = numbers.iter().copied().enumerate();
+    match backend {
+        Scanning => Box::new(
+            draws
+                .map(move |(draw, number)| {
+                    scan(&mut boards, draw, number, scoring)
+                })
+                .filter(|wins| !wins.is_empty()),
+        ),
+        Indexed => {
+            let mut index: HashMap<i32, Vec<(usize, usize, usize)>> =
+                HashMap::new();
+            for (board_index, board) in boards.iter().enumerate() {
+                for (&number, &(row, col)) in &board.numbers {
+                    index.entry(number).or_default().push((
+                        board_index,
+                        row,
+                        col,
+                    ));
+                }
             }
+            Box::new(
+                draws
+                    .map(move |(draw, number)| {
+                        let places = index.get(&number).into_iter().flatten();
+                        places
+                            .filter_map(|&(board_index, row, col)| {
+                                let board = &mut boards[board_index];
+                                if board.is_complete() {
+                                    return None;
+                                }
+                                board.mark_at(number, row, col);
+                                board.win(draw, number, board_index, scoring)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .filter(|wins| !wins.is_empty()),
+            )
         }
-        boards.retain(|board| !board.is_complete());
     }
-    last_win
+}
+
+/// Every win in order: by draw, then by board.
+pub fn play<'a>(
+    numbers: &'a [i32],
+    boards: &[Board],
+) -> impl Iterator<Item = WinEvent> + 'a {
+    play_with(numbers, boards, Scoring::default(), Backend::default())
+}
+
+/// Same as [`play`], scoring the boards as `scoring` says and finding the ones
+/// that complete with `backend`.
+pub fn play_with<'a>(
+    numbers: &'a [i32],
+    boards: &[Board],
+    scoring: Scoring,
+    backend: Backend,
+) -> impl Iterator<Item = WinEvent> + 'a {
+    wins_by_draw(numbers, boards, scoring, backend).flatten()
+}
+
+/// Every board that completes on `draw`, the position of a number among
+/// those drawn, in input order.
+pub fn wins_on_draw(
+    numbers: &[i32],
+    boards: &[Board],
+    draw: usize,
+) -> Vec<WinEvent> {
+    wins_by_draw(numbers, boards, Scoring::default(), Backend::default())
+        .take_while(|wins| wins[0].draw <= draw)
+        .find(|wins| wins[0].draw == draw)
+        .unwrap_or_default()
+}
+
+pub fn part1(numbers: &[i32], boards: &[Board]) -> Option<i64> {
+    part1_with(
+        numbers,
+        boards,
+        TieBreak::default(),
+        Scoring::default(),
+        Backend::default(),
+    )
+}
+
+pub fn part2(numbers: &[i32], boards: &[Board]) -> Option<i64> {
+    part2_with(
+        numbers,
+        boards,
+        TieBreak::default(),
+        Scoring::default(),
+        Backend::default(),
+    )
+}
+
+/// Same as [`part1`], taking boards that complete on the same draw to win in
+/// the order given by `tie`, and scoring them as `scoring` says.
+pub fn part1_with(
+    numbers: &[i32],
+    boards: &[Board],
+    tie: TieBreak,
+    scoring: Scoring,
+    backend: Backend,
+) -> Option<i64> {
+    let mut wins = wins_by_draw(numbers, boards, scoring, backend).next()?;
+    tie.sort(&mut wins);
+    wins.first().map(|win| win.score)
+}
+
+/// Same as [`part2`], taking boards that complete on the same draw to win in
+/// the order given by `tie`, and scoring them as `scoring` says.
+pub fn part2_with(
+    numbers: &[i32],
+    boards: &[Board],
+    tie: TieBreak,
+    scoring: Scoring,
+    backend: Backend,
+) -> Option<i64> {
+    let mut wins = wins_by_draw(numbers, boards, scoring, backend).last()?;
+    tie.sort(&mut wins);
+    wins.last().map(|win| win.score)
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "unmarked-sum" => Ok(Scoring::UnmarkedSum),
+            "marked-sum" => Ok(Scoring::MarkedSum),
+            "line-product" => Ok(Scoring::LineProduct),
+            _ => Err(format!("Invalid scoring '{}'", s)),
+        }
+    }
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "input-order" => Ok(TieBreak::InputOrder),
+            "highest-score" => Ok(TieBreak::HighestScore),
+            "lowest-score" => Ok(TieBreak::LowestScore),
+            _ => Err(format!("Invalid tie break '{}'", s)),
+        }
+    }
+}
+
+impl Display for WinEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Draw {} ({}): board {} wins with score {}",
+            self.draw + 1,
+            self.number,
+            self.board + 1,
+            self.score
+        )
+    }
+}
+
+impl Display for Board {
+    /// Writes the numbers in their rows and columns, with marked ones in
+    /// brackets.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render_with_marks(|cell| format!("[{}]", cell)))
+    }
 }
 
 impl FromStr for Board {
     type Err = String;
 
+    /// Reads the numbers in their rows and columns, marking the ones in
+    /// brackets, as the board is displayed.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let numbers: HashMap<i32, (usize, usize)> = s
+        let cells = s
             .trim()
             .lines()
             .enumerate()
             .flat_map(|(row, line)| {
-                line.split_whitespace().enumerate().map(move |(col, num)| {
-                    num.parse::<i32>()
-                        .map(|number| (number, (row, col)))
-                        .map_err(|err: ParseIntError| err.to_string())
-                })
+                let line = line
+                    .replace("[ ", "[")
+                    .replace('[', " [")
+                    .replace(']', "] ");
+                let cells = line
+                    .split_whitespace()
+                    .enumerate()
+                    .map(|(col, cell)| {
+                        let (num, marked) = match cell
+                            .strip_prefix('[')
+                            .and_then(|cell| cell.strip_suffix(']'))
+                        {
+                            Some(num) => (num, true),
+                            None => (cell, false),
+                        };
+                        num.parse::<i32>()
+                            .map(|number| (number, (row, col), marked))
+                            .map_err(|err: ParseIntError| err.to_string())
+                    })
+                    .collect::<Vec<_>>();
+                cells.into_iter()
             })
-            .collect::<Result<Vec<_>, _>>()?
-            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        let numbers: HashMap<i32, (usize, usize)> = cells
+            .iter()
+            .map(|&(number, position, _)| (number, position))
             .collect();
 
         if numbers.len() != BOARD_SIZE * BOARD_SIZE
@@ -93,11 +467,218 @@ impl FromStr for Board {
         let col_marks = vec![0; BOARD_SIZE];
         let complete = false;
 
-        Ok(Board {
+        let mut board = Board {
             numbers,
+            marked: HashSet::new(),
             row_marks,
             col_marks,
             complete,
-        })
+        };
+        for (number, (row, col), marked) in cells {
+            if marked {
+                board.mark_at(number, row, col);
+            }
+        }
+        Ok(board)
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<(Vec<i32>, Vec<Board>), String> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let (first, remaining) = lines
+        .split_first()
+        .ok_or_else(|| "Empty file".to_string())?;
+
+    let numbers = first
+        .split(',')
+        .map(|num| num.parse())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err: ParseIntError| err.to_string())?;
+
+    let boards = remaining
+        .chunks(BOARD_SIZE + 1)
+        .map(|chunk| chunk.join("\n").parse::<Board>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((numbers, boards))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::hash_set;
+    use proptest::prelude::*;
+
+    fn new_board(numbers: impl IntoIterator<Item = i32>) -> Board {
+        Board {
+            numbers: numbers
+                .into_iter()
+                .zip(0..)
+                .map(|(num, pos)| (num, (pos / BOARD_SIZE, pos % BOARD_SIZE)))
+                .collect(),
+            marked: HashSet::new(),
+            row_marks: vec![0; BOARD_SIZE],
+            col_marks: vec![0; BOARD_SIZE],
+            complete: false,
+        }
+    }
+
+    /// Boards with some of their numbers marked.
+    fn board() -> impl Strategy<Value = Board> {
+        (
+            hash_set(any::<i32>(), BOARD_SIZE * BOARD_SIZE),
+            prop::collection::vec(any::<bool>(), BOARD_SIZE * BOARD_SIZE),
+        )
+            .prop_map(|(numbers, marks)| {
+                let numbers = numbers.into_iter().collect::<Vec<_>>();
+                let mut board = new_board(numbers.iter().copied());
+                for (number, marked) in numbers.into_iter().zip(marks) {
+                    if marked {
+                        board.mark(number);
+                    }
+                }
+                board
+            })
+    }
+
+    /// Boards with numbers below 40, so that they're likely to share some.
+    fn small_board() -> impl Strategy<Value = Board> {
+        Just((0..40).collect::<Vec<_>>())
+            .prop_shuffle()
+            .prop_map(|numbers| {
+                new_board(numbers[..BOARD_SIZE * BOARD_SIZE].to_vec())
+            })
+    }
+
+    #[test]
+    fn plays_every_win() {
+        let (numbers, boards) = parse_input(EXAMPLE).unwrap();
+        let wins = play(&numbers, &boards).collect::<Vec<_>>();
+        assert_eq!(wins.len(), boards.len());
+        assert_eq!(
+            wins[0],
+            WinEvent {
+                draw: 11,
+                number: 24,
+                board: 2,
+                score: 4512
+            }
+        );
+        assert_eq!(wins[2].board, 1);
+        assert_eq!(wins[2].score, 1924);
+    }
+
+    #[test]
+    fn breaks_ties_between_winners() {
+        // Both boards complete their first row on the fifth draw
+        let board = |rest: i32| {
+            let numbers = (1..=5).chain(rest..rest + 20).collect::<Vec<_>>();
+            numbers
+                .chunks(BOARD_SIZE)
+                .map(|row| {
+                    row.iter()
+                        .map(|num| num.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+                .parse::<Board>()
+                .unwrap()
+        };
+        let boards = [board(6), board(26)];
+        let numbers = [1, 2, 3, 4, 5];
+        let wins = wins_on_draw(&numbers, &boards, 4);
+        assert_eq!(
+            wins.iter().map(|win| win.score).collect::<Vec<_>>(),
+            vec![1550, 3550]
+        );
+        assert!(wins_on_draw(&numbers, &boards, 3).is_empty());
+        assert_eq!(part1(&numbers, &boards), Some(1550));
+        assert_eq!(part2(&numbers, &boards), Some(3550));
+        assert_eq!(
+            part1_with(
+                &numbers,
+                &boards,
+                TieBreak::HighestScore,
+                Scoring::default(),
+                Scanning
+            ),
+            Some(3550)
+        );
+        assert_eq!(
+            part2_with(
+                &numbers,
+                &boards,
+                TieBreak::HighestScore,
+                Scoring::default(),
+                Indexed
+            ),
+            Some(1550)
+        );
+        assert_eq!(
+            part2_with(
+                &numbers,
+                &boards,
+                TieBreak::LowestScore,
+                Scoring::default(),
+                Scanning
+            ),
+            Some(3550)
+        );
+    }
+
+    #[test]
+    fn scores_in_other_ways() {
+        let (numbers, boards) = parse_input(EXAMPLE).unwrap();
+        let first_win = |scoring| {
+            part1_with(
+                &numbers,
+                &boards,
+                TieBreak::default(),
+                scoring,
+                Backend::default(),
+            )
+        };
+        assert_eq!(first_win(Scoring::UnmarkedSum), Some(4512));
+        assert_eq!(first_win(Scoring::MarkedSum), Some(137 * 24));
+        assert_eq!(
+            first_win(Scoring::LineProduct),
+            Some(14 * 21 * 17 * 24 * 4)
+        );
+    }
+
+    #[test]
+    fn shows_marks() {
+        let (numbers, boards) = parse_input(EXAMPLE).unwrap();
+        let mut board = boards[2].clone();
+        for &number in &numbers[..12] {
+            board.mark(number);
+        }
+        assert_eq!(
+            board.to_string(),
+            "[14][21][17][24][ 4]\n 10  16  15 [ 9] 19 \n 18   8 [23] 26  20 \n \
+             22 [11] 13   6 [ 5]\n[ 2][ 0] 12   3 [ 7]\n"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn display_round_trips(board in board()) {
+            prop_assert_eq!(board.to_string().parse(), Ok(board));
+        }
+
+        #[test]
+        fn backends_agree(
+            numbers in prop::collection::vec(0..40, 0..40),
+            boards in prop::collection::vec(small_board(), 0..8)
+        ) {
+            prop_assert_eq!(
+                play_with(&numbers, &boards, Scoring::LineProduct, Scanning)
+                    .collect::<Vec<_>>(),
+                play_with(&numbers, &boards, Scoring::LineProduct, Indexed)
+                    .collect::<Vec<_>>()
+            );
+        }
     }
 }
@@ -1,60 +1,122 @@
 use clap::{crate_description, App, Arg};
-use day04::{part1, part2, Board, BOARD_SIZE};
-use std::fs::read_to_string;
-use std::num::ParseIntError;
+use common::{read_or_example, Backend as _, Color, Pretty, COLOR_CHOICES};
+use day04::{
+    parse_input, part1_with, part2_with, play_with, Backend, Board, EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
+    let backends = Backend::names();
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("wins")
+                .long("wins")
+                .help("Prints every board's win, in order, with its marks"),
+        )
+        .arg(
+            Arg::with_name("ties")
+                .long("ties")
+                .takes_value(true)
+                .value_name("ORDER")
+                .possible_values(&[
+                    "input-order",
+                    "highest-score",
+                    "lowest-score",
+                ])
+                .default_value("input-order")
+                .help("Which boards win first when they complete together"),
+        )
+        .arg(
+            Arg::with_name("scoring")
+                .long("scoring")
+                .takes_value(true)
+                .possible_values(&[
+                    "unmarked-sum",
+                    "marked-sum",
+                    "line-product",
+                ])
+                .default_value("unmarked-sum")
+                .help("How winning boards are scored"),
+        )
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .takes_value(true)
+                .possible_values(&backends)
+                .default_value(Backend::default().name())
+                .help("Algorithm used to solve the puzzle"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let (numbers, boards) = match read_input(args.value_of("INPUT").unwrap()) {
+    let (numbers, boards) = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    match part1(&numbers, &boards) {
-        Some(answer) => println!("Part 1: {}", &answer),
+    let tie = args.value_of("ties").unwrap().parse().unwrap();
+    let scoring = args.value_of("scoring").unwrap().parse().unwrap();
+    // Already checked by the argument's possible values
+    let backend =
+        Backend::from_name(args.value_of("backend").unwrap()).unwrap();
+
+    if args.is_present("wins") {
+        for win in play_with(&numbers, &boards, scoring, backend) {
+            println!("{}", win);
+            let mut board = boards[win.board].clone();
+            for &number in &numbers[..=win.draw] {
+                board.mark(number);
+            }
+            println!(
+                "{}",
+                board.render_with_marks(|cell| {
+                    pretty.paint(format!("[{}]", cell), Color::Yellow)
+                })
+            );
+        }
+    }
+
+    match part1_with(&numbers, &boards, tie, scoring, backend) {
+        Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
         None => println!("Part 1: Not found"),
     }
-    match part2(&numbers, &boards) {
-        Some(answer) => println!("Part 2: {}", &answer),
+    match part2_with(&numbers, &boards, tie, scoring, backend) {
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
         None => println!("Part 2: Not found"),
     }
 }
 
-fn read_input(filename: &str) -> Result<(Vec<i32>, Vec<Board>), String> {
-    let contents = read_to_string(filename).map_err(|err| err.to_string())?;
-    let mut lines = contents.lines().collect::<Vec<_>>();
-    if lines.is_empty() {
-        return Err("Empty file".to_string());
-    }
-
-    let remaining = lines.split_off(1);
-
-    let numbers = lines
-        .pop()
-        .unwrap()
-        .split(',')
-        .map(|num| num.parse())
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|err: ParseIntError| err.to_string())?;
-
-    let boards = remaining
-        .chunks(BOARD_SIZE + 1)
-        .map(|chunk| chunk.join("\n").parse::<Board>())
-        .collect::<Result<Vec<_>, _>>()?;
-
-    Ok((numbers, boards))
+fn read_input(
+    filename: Option<&str>,
+) -> Result<(Vec<i32>, Vec<Board>), String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
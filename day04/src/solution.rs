@@ -0,0 +1,15 @@
+use crate::{parse_input, part1, part2};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let (numbers, boards) = parse_input(input)?;
+    report.maybe(part1(&numbers, &boards));
+    report.maybe(part2(&numbers, &boards));
+    Ok(())
+}
+
+register_solution!(day: 4, solve: solve);
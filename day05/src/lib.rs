@@ -1,12 +1,23 @@
-use std::collections::HashMap;
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{save_png, Grid, Point2 as Point, Svg};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+use std::iter::from_fn;
+use std::path::Path;
 use std::str::FromStr;
+use Backend::*;
 
-#[derive(Copy, Clone, Eq, Hash, PartialEq)]
-pub struct Point {
-    x: i32,
-    y: i32,
-}
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Line {
     point1: Point,
     point2: Point,
@@ -21,11 +32,121 @@ impl Line {
         self.point1.x == self.point2.x
     }
 
-    fn coord_deltas(&self) -> (i32, i32) {
-        let delta_x = self.point2.x - self.point1.x;
-        let delta_y = self.point2.y - self.point1.y;
-        (delta_x.signum(), delta_y.signum())
+    /// Whether the line is horizontal or vertical, the only ones that count
+    /// for part 1.
+    pub fn is_straight(&self) -> bool {
+        self.is_horizontal() || self.is_vertical()
+    }
+
+    /// Splits the line into spans along the four directions that can be
+    /// intersected directly. Lines of any other slope become one span for
+    /// each point they cover.
+    fn spans(&self) -> Vec<Span> {
+        let (x1, y1) = (i64::from(self.point1.x), i64::from(self.point1.y));
+        let (x2, y2) = (i64::from(self.point2.x), i64::from(self.point2.y));
+        let direction = if y1 == y2 {
+            Direction::Horizontal
+        } else if x1 == x2 {
+            Direction::Vertical
+        } else if x2 - x1 == y2 - y1 {
+            Direction::Diagonal
+        } else if x2 - x1 == y1 - y2 {
+            Direction::AntiDiagonal
+        } else {
+            return self
+                .points()
+                .map(|point| {
+                    let x = i64::from(point.x);
+                    Span {
+                        direction: Direction::Horizontal,
+                        key: i64::from(point.y),
+                        start: x,
+                        end: x,
+                    }
+                })
+                .collect();
+        };
+        let (start, end) = match direction {
+            Direction::Vertical => (y1.min(y2), y1.max(y2)),
+            _ => (x1.min(x2), x1.max(x2)),
+        };
+        vec![Span {
+            direction,
+            key: direction.key(x1, y1),
+            start,
+            end,
+        }]
+    }
+
+    /// The points that the line covers, from one end to the other, picked
+    /// with Bresenham's algorithm so that lines of any slope are continuous.
+    fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        let step = (self.point2 - self.point1).signum();
+        let delta_x =
+            (i64::from(self.point2.x) - i64::from(self.point1.x)).abs();
+        let delta_y =
+            -(i64::from(self.point2.y) - i64::from(self.point1.y)).abs();
+        let mut error = delta_x + delta_y;
+        let mut next = Some(self.point1);
+        from_fn(move || {
+            let point = next?;
+            next = (point != self.point2).then(|| {
+                let mut next = point;
+                let doubled = 2 * error;
+                if doubled >= delta_y {
+                    error += delta_y;
+                    next.x += step.x;
+                }
+                if doubled <= delta_x {
+                    error += delta_x;
+                    next.y += step.y;
+                }
+                next
+            });
+            Some(point)
+        })
     }
+
+    /// Parses a line, which must have one of the given `slopes`.
+    fn parse(s: &str, slopes: Slopes) -> Result<Self, String> {
+        let points: Vec<Point> = s
+            .split("->")
+            .map(|point| point.trim().parse())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let [point1, point2] = *points.as_slice() else {
+            return Err(format!("Invalid line '{}'", s));
+        };
+
+        let (Some(delta_x), Some(delta_y)) = (
+            point2.x.checked_sub(point1.x),
+            point2.y.checked_sub(point1.y),
+        ) else {
+            return Err(format!("Invalid line '{}': Line too long", s));
+        };
+        if slopes == Slopes::Puzzle
+            && delta_x != 0
+            && delta_y != 0
+            && delta_x.unsigned_abs() != delta_y.unsigned_abs()
+        {
+            return Err(format!(
+                "Invalid line '{}': Lines must be horizontal, vertical, or \
+                45-degree diagonal",
+                s
+            ));
+        }
+
+        Ok(Line { point1, point2 })
+    }
+}
+
+/// Which lines the input can have.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Slopes {
+    /// Horizontal, vertical or 45-degree diagonal, as in the puzzle.
+    #[default]
+    Puzzle,
+    Any,
 }
 
 pub fn count_overlaps<'a, I>(lines: I) -> usize
@@ -34,84 +155,612 @@ where
 {
     let mut counter = HashMap::new();
     for line in lines.into_iter() {
-        let (delta_x, delta_y) = line.coord_deltas();
-        let mut point = line.point1;
-        loop {
+        for point in line.points() {
             counter
                 .entry(point)
                 .and_modify(|count| *count += 1)
                 .or_insert(1);
-            if point == line.point2 {
-                break;
+        }
+    }
+    counter.values().filter(|&count| *count > 1).count()
+}
+
+/// Same as [`count_overlaps`] for vents that are `thickness` cells wide on
+/// each side: every cell within that many steps of a line's points,
+/// diagonals included, is covered by the line.
+pub fn count_overlaps_with_thickness<'a, I>(lines: I, thickness: u32) -> usize
+where
+    I: IntoIterator<Item = &'a Line>,
+{
+    let thickness = i64::from(thickness);
+    let mut counter: HashMap<(i64, i64), u32> = HashMap::new();
+    for line in lines.into_iter() {
+        let mut covered = HashSet::new();
+        for point in line.points() {
+            let (x, y) = (i64::from(point.x), i64::from(point.y));
+            for cell_x in x - thickness..=x + thickness {
+                covered.extend(
+                    (y - thickness..=y + thickness)
+                        .map(|cell_y| (cell_x, cell_y)),
+                );
             }
-            point.x += delta_x;
-            point.y += delta_y;
+        }
+        for cell in covered {
+            *counter.entry(cell).or_default() += 1;
         }
     }
     counter.values().filter(|&count| *count > 1).count()
 }
 
-pub fn part1(lines: &[Line]) -> usize {
-    let non_diagonals = lines
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Direction {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+impl Direction {
+    const ALL: [Self; 4] = [
+        Self::Horizontal,
+        Self::Vertical,
+        Self::Diagonal,
+        Self::AntiDiagonal,
+    ];
+
+    /// The coefficients `(a, b)` of the lines `a * x + b * y = key` that go
+    /// in this direction.
+    fn coefficients(&self) -> (i64, i64) {
+        match self {
+            Self::Horizontal => (0, 1),
+            Self::Vertical => (1, 0),
+            Self::Diagonal => (1, -1),
+            Self::AntiDiagonal => (1, 1),
+        }
+    }
+
+    fn key(&self, x: i64, y: i64) -> i64 {
+        let (a, b) = self.coefficients();
+        a * x + b * y
+    }
+
+    /// Where a point falls along the lines in this direction.
+    fn position(&self, x: i64, y: i64) -> i64 {
+        match self {
+            Self::Vertical => y,
+            _ => x,
+        }
+    }
+}
+
+/// The points of a line in one of the four directions with the given `key`
+/// and with positions from `start` to `end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Span {
+    direction: Direction,
+    key: i64,
+    start: i64,
+    end: i64,
+}
+
+impl Span {
+    fn len(&self) -> usize {
+        (self.end - self.start + 1) as usize
+    }
+
+    fn contains(&self, (x, y): (i64, i64)) -> bool {
+        let position = self.direction.position(x, y);
+        self.direction.key(x, y) == self.key
+            && (self.start..=self.end).contains(&position)
+    }
+
+    /// The point where two spans in different directions cross, if any.
+    fn crossing(&self, other: &Span) -> Option<(i64, i64)> {
+        let (a1, b1) = self.direction.coefficients();
+        let (a2, b2) = other.direction.coefficients();
+        let det = a1 * b2 - a2 * b1;
+        let x = self.key * b2 - other.key * b1;
+        let y = a1 * other.key - a2 * self.key;
+        if det == 0 || x % det != 0 || y % det != 0 {
+            return None;
+        }
+        let point = (x / det, y / det);
+        (self.contains(point) && other.contains(point)).then_some(point)
+    }
+}
+
+/// The spans, all in one direction, covered by at least one of `spans` and
+/// those covered by at least two, both sorted.
+fn sweep(mut spans: Vec<Span>) -> (Vec<Span>, Vec<Span>) {
+    spans.sort_unstable();
+    let mut covered: Vec<Span> = Vec::new();
+    let mut overlaps: Vec<Span> = Vec::new();
+    for span in spans {
+        match covered.last_mut() {
+            Some(last) if last.key == span.key && last.end >= span.start => {
+                let overlap = Span {
+                    start: span.start,
+                    end: span.end.min(last.end),
+                    ..span
+                };
+                match overlaps.last_mut() {
+                    Some(prev)
+                        if prev.key == overlap.key
+                            && prev.end + 1 >= overlap.start =>
+                    {
+                        prev.end = prev.end.max(overlap.end);
+                    }
+                    _ => overlaps.push(overlap),
+                }
+                last.end = last.end.max(span.end);
+            }
+            _ => covered.push(span),
+        }
+    }
+    (covered, overlaps)
+}
+
+/// Whether any of `spans`, sorted and all in one direction, has `point`.
+fn any_contains(spans: &[Span], (x, y): (i64, i64)) -> bool {
+    let Some(direction) = spans.first().map(|span| span.direction) else {
+        return false;
+    };
+    let (key, position) = (direction.key(x, y), direction.position(x, y));
+    let index =
+        spans.partition_point(|span| (span.key, span.start) <= (key, position));
+    index > 0 && spans[index - 1].contains((x, y))
+}
+
+/// Counts the points covered by at least two lines without visiting every
+/// point. Lines going the same way are merged with a sweep along each of
+/// them, and those going different ways are intersected in pairs.
+fn count_crossings<'a, I>(lines: I) -> usize
+where
+    I: IntoIterator<Item = &'a Line>,
+{
+    let spans: Vec<Span> = lines.into_iter().flat_map(Line::spans).collect();
+    let (covered, overlaps): (Vec<_>, Vec<_>) = Direction::ALL
         .iter()
-        .filter(|line| line.is_horizontal() || line.is_vertical());
-    count_overlaps(non_diagonals)
+        .map(|&direction| {
+            sweep(
+                spans
+                    .iter()
+                    .filter(|span| span.direction == direction)
+                    .copied()
+                    .collect(),
+            )
+        })
+        .unzip();
+
+    let mut crossings = HashSet::new();
+    for (i, spans1) in covered.iter().enumerate() {
+        for spans2 in &covered[i + 1..] {
+            for span1 in spans1 {
+                crossings.extend(
+                    spans2.iter().filter_map(|span2| span1.crossing(span2)),
+                );
+            }
+        }
+    }
+
+    // Crossings can also lie where lines going the same way overlap, so
+    // they're only counted once
+    let mut count: usize = overlaps.iter().flatten().map(Span::len).sum();
+    for point in crossings {
+        let overlapping = overlaps
+            .iter()
+            .filter(|spans| any_contains(spans, point))
+            .count();
+        if overlapping == 0 {
+            count += 1;
+        } else {
+            count -= overlapping - 1;
+        }
+    }
+    count
+}
+
+/// Ways of counting the points where lines overlap.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Backend {
+    /// Counts how many lines go through every point they cover.
+    #[default]
+    Enumerating,
+    /// Works out where lines overlap and cross from their ends, so it
+    /// doesn't depend on how long they are.
+    Intersecting,
+}
+
+impl common::Backend for Backend {
+    const ALL: &'static [Self] = &[Enumerating, Intersecting];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Enumerating => "enumerating",
+            Intersecting => "intersecting",
+        }
+    }
+}
+
+fn count_overlaps_with<'a, I>(lines: I, backend: Backend) -> usize
+where
+    I: IntoIterator<Item = &'a Line>,
+{
+    match backend {
+        Enumerating => count_overlaps(lines),
+        Intersecting => count_crossings(lines),
+    }
+}
+
+pub fn part1(lines: &[Line]) -> usize {
+    part1_with(lines, Backend::default())
 }
 
 pub fn part2(lines: &[Line]) -> usize {
-    count_overlaps(lines)
+    part2_with(lines, Backend::default())
 }
 
-impl FromStr for Point {
-    type Err = String;
+pub fn part1_with(lines: &[Line], backend: Backend) -> usize {
+    let non_diagonals = lines.iter().filter(|line| line.is_straight());
+    count_overlaps_with(non_diagonals, backend)
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let numbers = s
-            .split(',')
-            .map(|num| {
-                num.trim().parse::<i32>().map_err(|err| {
-                    format!("Invalid coordinate '{}': {}", num, err)
-                })
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+pub fn part2_with(lines: &[Line], backend: Backend) -> usize {
+    count_overlaps_with(lines, backend)
+}
+
+/// Solves both parts in a single pass, so that the lines can come straight
+/// from [`parse_reader`]. Only the number of lines through each point is
+/// kept, counting those that aren't diagonal apart.
+pub fn solve_stream<I, E>(lines: I) -> Result<(usize, usize), E>
+where
+    I: IntoIterator<Item = Result<Line, E>>,
+{
+    let mut counter: HashMap<Point, (u32, u32)> = HashMap::new();
+    for line in lines {
+        let line = line?;
+        let straight = line.is_straight();
+        for point in line.points() {
+            let (straight_count, count) = counter.entry(point).or_default();
+            if straight {
+                *straight_count += 1;
+            }
+            *count += 1;
+        }
+    }
+    Ok((
+        counter.values().filter(|(count, _)| *count > 1).count(),
+        counter.values().filter(|(_, count)| *count > 1).count(),
+    ))
+}
+
+/// The points between `min` and `max`, both included.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+    }
+}
+
+/// How many lines go through each point, kept in order so that the points
+/// in an area can be looked up without going through every line again.
+#[derive(Clone, Debug, Default)]
+pub struct VentIndex {
+    counts: BTreeMap<Point, u32>,
+}
 
-        if numbers.len() != 2 {
-            return Err(format!("Invalid point '{}'", s));
+impl VentIndex {
+    /// Colours of points with no lines, one line, and two or more.
+    const PALETTE: &'static [[u8; 3]] =
+        &[[255, 255, 255], [160, 160, 160], [220, 0, 0]];
+
+    pub fn new<'a, I>(lines: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Line>,
+    {
+        let mut counts = BTreeMap::new();
+        for line in lines {
+            for point in line.points() {
+                *counts.entry(point).or_default() += 1;
+            }
         }
+        Self { counts }
+    }
+
+    /// The number of lines that go through `point`.
+    pub fn overlaps_at(&self, point: Point) -> u32 {
+        self.counts.get(&point).copied().unwrap_or(0)
+    }
+
+    /// The points in `bounding_box` where lines overlap, by column, with the
+    /// number of lines through each.
+    pub fn overlaps_in(
+        &self,
+        bounding_box: BoundingBox,
+    ) -> impl Iterator<Item = (Point, u32)> + '_ {
+        let range = bounding_box.min..=bounding_box.max;
+        // Ranges that end before they start make BTreeMap panic
+        (!range.is_empty())
+            .then(|| self.counts.range(range))
+            .into_iter()
+            .flatten()
+            .filter(move |(&point, &count)| {
+                count > 1 && bounding_box.contains(point)
+            })
+            .map(|(&point, &count)| (point, count))
+    }
 
-        Ok(Point {
-            x: numbers[0],
-            y: numbers[1],
+    /// The smallest box with every point that a line goes through.
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        let (first, _) = self.counts.first_key_value()?;
+        let (last, _) = self.counts.last_key_value()?;
+        let (min_y, max_y) = self
+            .counts
+            .keys()
+            .fold((i32::MAX, i32::MIN), |(min, max), point| {
+                (min.min(point.y), max.max(point.y))
+            });
+        Some(BoundingBox {
+            min: Point::new(first.x, min_y),
+            max: Point::new(last.x, max_y),
         })
     }
+
+    /// Longest side of an image, in pixels.
+    const MAX_IMAGE_SIDE: i64 = 4096;
+
+    /// The number of lines through each point within the bounds, capped at
+    /// the last colour of [`PALETTE`](VentIndex::PALETTE). Bounds wider or
+    /// taller than [`MAX_IMAGE_SIDE`](VentIndex::MAX_IMAGE_SIDE) are scaled
+    /// down, with each pixel showing the most lines through any of its
+    /// points.
+    fn image(&self) -> Grid<u8> {
+        let Some(BoundingBox { min, max }) = self.bounds() else {
+            return Grid::new(0, 0, 0);
+        };
+        let width = i64::from(max.x) - i64::from(min.x) + 1;
+        let height = i64::from(max.y) - i64::from(min.y) + 1;
+        let scale = (width.max(height) + Self::MAX_IMAGE_SIDE - 1)
+            / Self::MAX_IMAGE_SIDE;
+        let pixels = |length: i64| ((length + scale - 1) / scale) as usize;
+        let last_color = (Self::PALETTE.len() - 1) as u32;
+        let mut image = Grid::new(pixels(width), pixels(height), 0);
+        for (point, &count) in &self.counts {
+            let x = ((i64::from(point.x) - i64::from(min.x)) / scale) as usize;
+            let y = ((i64::from(point.y) - i64::from(min.y)) / scale) as usize;
+            let color = count.min(last_color) as u8;
+            image[(x, y)] = image[(x, y)].max(color);
+        }
+        image
+    }
+
+    /// Writes an image of the points covered by lines, with those where
+    /// they overlap in red, as a PNG file.
+    pub fn save_png(&self, path: &Path) -> Result<(), String> {
+        save_png(self.image(), Self::PALETTE, path)
+    }
+}
+
+/// Draws the number of lines through each point like the puzzle does, with
+/// `.` where there are none and `+` where there are more than nine.
+impl Display for VentIndex {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let Some(BoundingBox { min, max }) = self.bounds() else {
+            return Ok(());
+        };
+        for y in min.y..=max.y {
+            let row = (min.x..=max.x)
+                .map(|x| match self.overlaps_at(Point::new(x, y)) {
+                    0 => '.',
+                    count => char::from_digit(count, 10).unwrap_or('+'),
+                })
+                .collect::<String>();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws the vent lines, with diagonal ones in a different colour.
+pub fn render(lines: &[Line]) -> Svg {
+    let points = lines.iter().flat_map(|line| [line.point1, line.point2]);
+    let max_x = points.clone().map(|point| point.x).max().unwrap_or(0);
+    let max_y = points.map(|point| point.y).max().unwrap_or(0);
+    let mut svg = Svg::new(0, 0, i64::from(max_x) + 1, i64::from(max_y) + 1)
+        .background("white");
+    for line in lines {
+        let color = if line.is_straight() { "black" } else { "red" };
+        svg.line(line.point1.into(), line.point2.into(), color);
+    }
+    svg
 }
 
 impl FromStr for Line {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut points: Vec<Point> = s
-            .split("->")
-            .map(|point| point.trim().parse())
-            .collect::<Result<Vec<_>, _>>()?;
+        Line::parse(s, Slopes::Puzzle)
+    }
+}
 
-        if points.len() != 2 {
-            return Err(format!("Invalid line '{}'", s));
-        }
+pub fn parse_input(input: &str) -> Result<Vec<Line>, String> {
+    parse_input_with(input, Slopes::default())
+}
 
-        let point2 = points.pop().unwrap();
-        let point1 = points.pop().unwrap();
+/// Same as [`parse_input`], accepting lines with any of `slopes`.
+pub fn parse_input_with(
+    input: &str,
+    slopes: Slopes,
+) -> Result<Vec<Line>, String> {
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            Line::parse(line, slopes)
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect()
+}
 
-        let delta_x = point2.x - point1.x;
-        let delta_y = point2.y - point1.y;
-        if delta_x != 0 && delta_y != 0 && delta_x.abs() != delta_y.abs() {
-            return Err(format!(
-                "Invalid line '{}': Lines must be horizontal, vertical, or \
-                45-degree diagonal",
-                s
-            ));
-        }
+/// Parses one line of vents per line from `reader` as it's needed.
+pub fn parse_reader<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<Line, String>> {
+    parse_reader_with(reader, Slopes::default())
+}
 
-        Ok(Line { point1, point2 })
+/// Same as [`parse_reader`], accepting lines with any of `slopes`.
+pub fn parse_reader_with<R: BufRead>(
+    reader: R,
+    slopes: Slopes,
+) -> impl Iterator<Item = Result<Line, String>> {
+    common::parse_lines(reader, move |line| Line::parse(line, slopes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn rasterizes_any_slope() {
+        assert!("0,0 -> 4,2".parse::<Line>().is_err());
+        let lines =
+            parse_input_with("0,0 -> 4,2\n4,0 -> 0,2\n0,1 -> 4,1", Slopes::Any)
+                .unwrap();
+        let points = lines[0]
+            .points()
+            .map(|point| (point.x, point.y))
+            .collect::<Vec<_>>();
+        assert_eq!(points, vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]);
+        assert_eq!(lines[1].points().last(), Some(Point { x: 0, y: 2 }));
+        assert_eq!(part1(&lines), 0);
+        assert_eq!(part2(&lines), 3);
+        assert_eq!(count_overlaps_with_thickness(&lines, 0), 3);
+        let example = parse_input(EXAMPLE).unwrap();
+        assert_eq!((part1(&example), part2(&example)), (5, 12));
+    }
+
+    #[test]
+    fn queries_overlaps() {
+        let index = VentIndex::new(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(index.overlaps_at(Point::new(7, 4)), 2);
+        assert_eq!(index.overlaps_at(Point::new(5, 4)), 1);
+        assert_eq!(index.overlaps_at(Point::new(0, 0)), 1);
+        assert_eq!(index.overlaps_at(Point::new(0, 1)), 0);
+        let bounding_box = BoundingBox {
+            min: Point::new(0, 4),
+            max: Point::new(4, 9),
+        };
+        assert_eq!(
+            index.overlaps_in(bounding_box).collect::<Vec<_>>(),
+            vec![
+                (Point::new(0, 9), 2),
+                (Point::new(1, 9), 2),
+                (Point::new(2, 9), 2),
+                (Point::new(3, 4), 2),
+                (Point::new(4, 4), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn draws_overlaps() {
+        let index = VentIndex::new(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(
+            index.to_string(),
+            "1.1....11.\n\
+            .111...2..\n\
+            ..2.1.111.\n\
+            ...1.2.2..\n\
+            .112313211\n\
+            ...1.2....\n\
+            ..1...1...\n\
+            .1.....1..\n\
+            1.......1.\n\
+            222111....\n"
+        );
+        assert_eq!(VentIndex::default().to_string(), "");
+    }
+
+    #[test]
+    fn scales_down_large_images() {
+        let index = VentIndex::new(&parse_input(EXAMPLE).unwrap());
+        let image = index.image();
+        assert_eq!((image.width(), image.height()), (10, 10));
+        assert_eq!(image.get(4, 4), Some(&2));
+
+        let lines =
+            parse_input("0,0 -> 1,1\n1,1 -> 1,1\n1000000,500000 -> 1000000,0")
+                .unwrap();
+        let image = VentIndex::new(&lines).image();
+        assert_eq!((image.width(), image.height()), (4082, 2041));
+        assert_eq!(image.get(0, 0), Some(&2));
+        assert_eq!(image.get(4081, 2040), Some(&1));
+    }
+
+    #[test]
+    fn thickens_lines() {
+        let lines = parse_input("0,0 -> 4,0\n2,2 -> 2,5\n9,9 -> 9,9").unwrap();
+        assert_eq!(count_overlaps_with_thickness(&lines, 0), 0);
+        assert_eq!(count_overlaps_with_thickness(&lines, 1), 3);
+        assert_eq!(count_overlaps_with_thickness(&lines, 2), 15);
+        let example = parse_input(EXAMPLE).unwrap();
+        assert_eq!(count_overlaps_with_thickness(&example, 0), 12);
+    }
+
+    #[test]
+    fn intersects_huge_lines() {
+        let lines = parse_input(
+            "0,0 -> 0,2000000000\n0,1000000000 -> 0,-5\n\
+            -7,7 -> 7,-7\n5,-1 -> -5,-1\n-3,-3 -> 3,3",
+        )
+        .unwrap();
+        assert_eq!(part1_with(&lines, Intersecting), 1_000_000_002);
+        assert_eq!(part2_with(&lines, Intersecting), 1_000_000_004);
+    }
+
+    fn any_line() -> impl Strategy<Value = Line> {
+        (-10..10, -10..10, -10..10, -10..10).prop_map(|(x1, y1, x2, y2)| Line {
+            point1: Point { x: x1, y: y1 },
+            point2: Point { x: x2, y: y2 },
+        })
+    }
+
+    fn puzzle_line() -> impl Strategy<Value = Line> {
+        (-10..10, -10..10, -1..=1, -1..=1, 0..10).prop_map(
+            |(x, y, step_x, step_y, len)| Line {
+                point1: Point { x, y },
+                point2: Point {
+                    x: x + step_x * len,
+                    y: y + step_y * len,
+                },
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn backends_agree(
+            lines in prop::collection::vec(puzzle_line(), 0..12),
+            any_lines in prop::collection::vec(any_line(), 0..6),
+        ) {
+            for lines in [&lines, &any_lines] {
+                prop_assert_eq!(
+                    part1_with(lines, Enumerating),
+                    part1_with(lines, Intersecting)
+                );
+                prop_assert_eq!(
+                    part2_with(lines, Enumerating),
+                    part2_with(lines, Intersecting)
+                );
+            }
+        }
     }
 }
@@ -1,43 +1,171 @@
 use clap::{crate_description, App, Arg};
-use day05::{part1, part2, Line};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use common::{
+    open_or_example, read_or_example, Backend as _, Pretty, COLOR_CHOICES,
+};
+use day05::{
+    count_overlaps_with_thickness, parse_input_with, parse_reader_with,
+    part1_with, part2_with, render, solve_stream, Backend, Line, Slopes,
+    VentIndex, EXAMPLE,
+};
+use std::path::Path;
 use std::process::exit;
 
 fn main() {
+    let backends = Backend::names();
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("render")
+                .long("render")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Writes an SVG drawing of the puzzle to PATH"),
+        )
+        .arg(
+            Arg::with_name("draw")
+                .long("draw")
+                .help("Prints how many lines go through each point"),
+        )
+        .arg(
+            Arg::with_name("png")
+                .long("png")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Writes an image of where lines overlap to PATH"),
+        )
+        .arg(
+            Arg::with_name("any-slope")
+                .long("any-slope")
+                .help("Accepts lines of any slope, not just 45-degree ones"),
+        )
+        .arg(
+            Arg::with_name("thickness")
+                .long("thickness")
+                .takes_value(true)
+                .value_name("CELLS")
+                .default_value("0")
+                .validator(|value| match value.parse::<u32>() {
+                    Ok(_) => Ok(()),
+                    _ => Err("must be a non-negative number".to_string()),
+                })
+                .help("How many cells vents spread to on each side"),
+        )
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .takes_value(true)
+                .possible_values(&backends)
+                .default_value(Backend::default().name())
+                .help("Algorithm used to solve the puzzle"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
-        Ok(data) => data,
+    let filename = args.value_of("INPUT");
+    let slopes = if args.is_present("any-slope") {
+        Slopes::Any
+    } else {
+        Slopes::Puzzle
+    };
+    // Already checked by the argument's possible values
+    let backend =
+        Backend::from_name(args.value_of("backend").unwrap()).unwrap();
+    let thickness = args.value_of("thickness").unwrap().parse().unwrap();
+    let (part1, part2) = match solve(filename, slopes, backend, thickness) {
+        Ok(answers) => answers,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    if let Some(path) = args.value_of("render") {
+        // Rendering needs every line at once, unlike solving
+        let saved = read_input(filename, slopes).and_then(|lines| {
+            render(&lines).save(path).map_err(|err| err.to_string())
+        });
+        if let Err(err) = saved {
+            println!("{}", pretty.error(format!("Failed to render: {}", err)));
+        }
+    }
+
+    if args.is_present("draw") || args.is_present("png") {
+        // Drawing needs every line at once, unlike solving
+        match read_input(filename, slopes) {
+            Ok(lines) => {
+                let index = VentIndex::new(&lines);
+                if args.is_present("draw") {
+                    print!("{}", index);
+                }
+                if let Some(path) = args.value_of("png") {
+                    if let Err(err) = index.save_png(Path::new(path)) {
+                        println!(
+                            "{}",
+                            pretty.error(format!("Failed to draw: {}", err))
+                        );
+                    }
+                }
+            }
+            Err(err) => {
+                println!("{}", pretty.error(format!("Failed to draw: {}", err)))
+            }
+        }
+    }
+
+    println!("Part 1: {}", pretty.answer(part1));
+    println!("Part 2: {}", pretty.answer(part2));
+}
+
+fn solve(
+    filename: Option<&str>,
+    slopes: Slopes,
+    backend: Backend,
+    thickness: u32,
+) -> Result<(usize, usize), String> {
+    if thickness > 0 {
+        let lines = read_input(filename, slopes)?;
+        let straight = lines.iter().filter(|line| line.is_straight());
+        return Ok((
+            count_overlaps_with_thickness(straight, thickness),
+            count_overlaps_with_thickness(&lines, thickness),
+        ));
+    }
+    if backend != Backend::Enumerating {
+        let lines = read_input(filename, slopes)?;
+        return Ok((part1_with(&lines, backend), part2_with(&lines, backend)));
+    }
+    let reader = open_or_example(filename, EXAMPLE)?;
+    solve_stream(parse_reader_with(reader, slopes))
 }
 
-fn read_input(filename: &str) -> Result<Vec<Line>, String> {
-    let input_file = File::open(filename).map_err(|err| err.to_string())?;
-
-    BufReader::new(input_file)
-        .lines()
-        .zip(1..)
-        .map(|(line, line_num)| {
-            line.map_err(|err| (line_num, err.to_string()))
-                .and_then(|value| value.parse().map_err(|err| (line_num, err)))
-        })
-        .collect::<Result<_, _>>()
-        .map_err(|(line_num, err)| format!("Line {}: {}", line_num, err))
+fn read_input(
+    filename: Option<&str>,
+    slopes: Slopes,
+) -> Result<Vec<Line>, String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input_with(&contents, slopes))
 }
@@ -1,32 +1,373 @@
-use std::collections::{HashMap, VecDeque};
-use std::str::FromStr;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt::{self, Display, Formatter};
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use Backend::*;
+
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
 
 const RESTART_TIMER: usize = 6;
 const NEW_TIMER: usize = 8;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Population {
+    restart_timer: usize,
     timer_counts: VecDeque<u64>,
 }
 
 impl Population {
+    fn from_counts(counts: Vec<u64>) -> Self {
+        Self {
+            restart_timer: RESTART_TIMER,
+            timer_counts: counts.into(),
+        }
+    }
+
+    /// The same fish, reset to `restart` after spawning, with new fish
+    /// starting at `new`. The restart timer must be lower than the new one,
+    /// which can't be lower than any fish's timer.
+    pub fn with_timers(
+        mut self,
+        restart: usize,
+        new: usize,
+    ) -> Result<Self, String> {
+        if restart >= new {
+            return Err(format!(
+                "Restart timer {} must be less than new timer {}",
+                restart, new
+            ));
+        }
+        if let Some(timer) = (new + 1..self.timer_counts.len())
+            .rev()
+            .find(|&timer| self.timer_counts[timer] > 0)
+        {
+            return Err(format!(
+                "New timer {} is less than a fish's timer {}",
+                new, timer
+            ));
+        }
+        self.timer_counts.resize(new + 1, 0);
+        self.restart_timer = restart;
+        Ok(self)
+    }
+
     fn next(&mut self) {
         let spawn = self.timer_counts.pop_front().unwrap();
-        *self.timer_counts.get_mut(RESTART_TIMER).unwrap() += spawn;
+        *self.timer_counts.get_mut(self.restart_timer).unwrap() += spawn;
         self.timer_counts.push_back(spawn);
     }
 
     fn count(&self) -> u64 {
         self.timer_counts.iter().sum()
     }
+
+    /// How many fish have each of the puzzle's timers, from zero to eight.
+    /// Fish with higher timers, only possible after
+    /// [`with_timers`](Population::with_timers), aren't counted; use
+    /// [`counts_by_any_timer`](Population::counts_by_any_timer) for those.
+    pub fn counts_by_timer(&self) -> [u64; NEW_TIMER + 1] {
+        let mut counts = [0; NEW_TIMER + 1];
+        for (count, &fish) in counts.iter_mut().zip(&self.timer_counts) {
+            *count = fish;
+        }
+        counts
+    }
+
+    /// How many fish have each timer, from zero up to the new fish's timer,
+    /// whatever [`with_timers`](Population::with_timers) set it to.
+    pub fn counts_by_any_timer(&self) -> Vec<u64> {
+        self.timer_counts.iter().copied().collect()
+    }
+
+    /// The population after `days`.
+    pub fn after(&self, days: u32) -> Self {
+        let mut population = self.clone();
+        for _ in 1..=days {
+            population.next()
+        }
+        population
+    }
+
+    /// The matrix that turns today's count of fish by timer into
+    /// tomorrow's.
+    fn transition(&self) -> SquareMatrix {
+        let size = self.timer_counts.len();
+        let mut matrix = SquareMatrix::zero(size);
+        for timer in 1..size {
+            matrix.0[timer - 1][timer] = 1;
+        }
+        matrix.0[size - 1][0] = 1;
+        matrix.0[self.restart_timer][0] += 1;
+        matrix
+    }
 }
 
 pub fn simulate(start_population: &Population, days: u32) -> u64 {
-    let mut population = start_population.clone();
-    for _ in 1..=days {
-        population.next()
+    start_population.after(days).count()
+}
+
+/// Ways of working out how many fish there will be.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Backend {
+    /// Moves the fish along their timers one day at a time.
+    #[default]
+    Stepping,
+    /// Raises the matrix that moves fish along in one day to the power of
+    /// the number of days, so it takes a number of steps logarithmic in it.
+    Matrix,
+}
+
+impl common::Backend for Backend {
+    const ALL: &'static [Self] = &[Stepping, Matrix];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Stepping => "stepping",
+            Matrix => "matrix",
+        }
+    }
+}
+
+/// Same as [`simulate`], for any number of days, or `None` if the fish, or
+/// the matrix used to count them, don't fit in a `u128`.
+pub fn simulate_with(
+    population: &Population,
+    days: u64,
+    backend: Backend,
+) -> Option<u128> {
+    let counts: Vec<u128> =
+        population.timer_counts.iter().map(|&n| n.into()).collect();
+    if counts.iter().all(|&count| count == 0) {
+        return Some(0);
+    }
+    let counts = match backend {
+        Stepping => {
+            let mut counts = VecDeque::from(counts);
+            for _ in 0..days {
+                let spawn = counts.pop_front().unwrap();
+                let restart = &mut counts[population.restart_timer];
+                *restart = restart.checked_add(spawn)?;
+                counts.push_back(spawn);
+            }
+            counts.into()
+        }
+        Matrix => {
+            let transition = population.transition();
+            let power = transition
+                .pow(days, |sum, a, b| sum.checked_add(a.checked_mul(b)?))?;
+            power.apply(&counts, |sum, a, b| {
+                sum.checked_add(a.checked_mul(b)?)
+            })?
+        }
+    };
+    counts.into_iter().try_fold(0u128, u128::checked_add)
+}
+
+/// How many fish there will be after `days`, modulo `modulus`, which stays
+/// small however many days there are.
+pub fn simulate_modulo(
+    population: &Population,
+    days: u64,
+    modulus: u64,
+) -> Result<u64, String> {
+    if modulus == 0 {
+        return Err("Modulus must be positive".into());
+    }
+    let modulus = u128::from(modulus);
+    let mul_add = |sum: u128, a: u128, b: u128| Some((sum + a * b) % modulus);
+    let counts: Vec<u128> = population
+        .timer_counts
+        .iter()
+        .map(|&n| u128::from(n) % modulus)
+        .collect();
+    let power = population.transition().pow(days, mul_add).unwrap();
+    let count = power
+        .apply(&counts, mul_add)
+        .unwrap()
+        .into_iter()
+        .fold(0, |sum, count| (sum + count) % modulus);
+    Ok(count as u64)
+}
+
+/// Most counts of fish with some timer that [`find_population`] tries before
+/// giving up.
+const MAX_SEARCH: u64 = 1 << 22;
+
+/// Why [`find_population`] found no starting fish.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FindError {
+    /// No starting fish grow to that many.
+    Impossible,
+    /// The search gave up before finding any starting fish, or ruling them
+    /// out.
+    SearchLimit,
+}
+
+impl Display for FindError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            FindError::Impossible => write!(f, "No fish grow to that many"),
+            FindError::SearchLimit => {
+                write!(f, "Gave up searching for fish that grow to that many")
+            }
+        }
+    }
+}
+
+/// Finds starting fish that grow to `count` fish after `days`, or reports
+/// that there are none. Every fish grows into a fixed number of them,
+/// depending on its timer, so this searches for how many fish to give each
+/// timer, starting with as many as possible of the fastest growing ones.
+pub fn find_population(count: u64, days: u32) -> Result<Population, FindError> {
+    find_population_within(count, days, MAX_SEARCH)
+}
+
+fn find_population_within(
+    count: u64,
+    days: u32,
+    mut budget: u64,
+) -> Result<Population, FindError> {
+    // How many fish a single fish with each timer grows to, most first, and
+    // only one timer for each number
+    let mut grown: Vec<(usize, u64)> = (0..=NEW_TIMER)
+        .filter_map(|timer| {
+            let mut single = vec![0; NEW_TIMER + 1];
+            single[timer] = 1;
+            let single = Population::from_counts(single);
+            let grown = simulate_with(&single, days.into(), Matrix)?;
+            let grown = u64::try_from(grown).ok()?;
+            (grown <= count).then_some((timer, grown))
+        })
+        .collect();
+    grown.sort_by_key(|&(_, grown)| Reverse(grown));
+    grown.dedup_by_key(|(_, grown)| *grown);
+
+    let mut counts = vec![0; NEW_TIMER + 1];
+    match fill_timers(count, &grown, &mut counts, &mut budget) {
+        Some(true) => Ok(Population::from_counts(counts)),
+        Some(false) => Err(FindError::Impossible),
+        None => Err(FindError::SearchLimit),
+    }
+}
+
+/// Sets how many fish have each timer in `grown`, along with how many fish
+/// each of them grows to, so that they grow to `count`. Returns whether
+/// there are such fish, or `None` if that takes more than `budget` tries.
+fn fill_timers(
+    count: u64,
+    grown: &[(usize, u64)],
+    counts: &mut [u64],
+    budget: &mut u64,
+) -> Option<bool> {
+    let Some((&(timer, single), rest)) = grown.split_first() else {
+        return Some(count == 0);
+    };
+    // The other timers can only grow to multiples of their common divisor
+    let divisor = rest.iter().fold(0, |divisor, &(_, g)| gcd(divisor, g));
+    for fish in (0..=count / single).rev() {
+        let remaining = count - fish * single;
+        if !remaining.is_multiple_of(divisor) {
+            continue;
+        }
+        *budget = budget.checked_sub(1)?;
+        counts[timer] = fish;
+        if fill_timers(remaining, rest, counts, budget)? {
+            return Some(true);
+        }
+    }
+    counts[timer] = 0;
+    Some(false)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// A square matrix of counts, with arithmetic done by a `mul_add` function
+/// returning `sum + a * b`, or `None` if that overflows.
+#[derive(Clone)]
+struct SquareMatrix(Vec<Vec<u128>>);
+
+impl SquareMatrix {
+    fn zero(size: usize) -> Self {
+        Self(vec![vec![0; size]; size])
+    }
+
+    fn identity(size: usize) -> Self {
+        let mut matrix = Self::zero(size);
+        for i in 0..size {
+            matrix.0[i][i] = 1;
+        }
+        matrix
+    }
+
+    fn mul<F>(&self, other: &Self, mul_add: F) -> Option<Self>
+    where
+        F: Fn(u128, u128, u128) -> Option<u128>,
+    {
+        let size = self.0.len();
+        let mut product = Self::zero(size);
+        for i in 0..size {
+            for j in 0..size {
+                product.0[i][j] = (0..size).try_fold(0, |sum, k| {
+                    mul_add(sum, self.0[i][k], other.0[k][j])
+                })?;
+            }
+        }
+        Some(product)
+    }
+
+    fn pow<F>(&self, mut exponent: u64, mul_add: F) -> Option<Self>
+    where
+        F: Fn(u128, u128, u128) -> Option<u128> + Copy,
+    {
+        let mut result = Self::identity(self.0.len());
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base, mul_add)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base, mul_add)?;
+            }
+        }
+        Some(result)
+    }
+
+    fn apply<F>(&self, vector: &[u128], mul_add: F) -> Option<Vec<u128>>
+    where
+        F: Fn(u128, u128, u128) -> Option<u128>,
+    {
+        self.0
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(vector)
+                    .try_fold(0, |sum, (&a, &b)| mul_add(sum, a, b))
+            })
+            .collect()
     }
-    population.count()
 }
 
 impl FromStr for Population {
@@ -41,22 +382,124 @@ impl FromStr for Population {
                     .parse()
                     .map_err(|err| format!("Invalid timer '{}': {}", num, err))
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<Result<Vec<usize>, _>>()?;
 
         if timers.iter().any(|&timer| timer > NEW_TIMER) {
             return Err(format!("Timers must be less than {}", NEW_TIMER));
         }
 
-        let totals =
-            timers.iter().fold(HashMap::new(), |mut counters, timer| {
-                *counters.entry(timer).or_insert(0) += 1;
-                counters
-            });
+        let mut counts = vec![0; NEW_TIMER + 1];
+        for timer in timers {
+            counts[timer] += 1;
+        }
+
+        Ok(Self::from_counts(counts))
+    }
+}
+
+/// Lists the fish's timers in increasing order, like the input.
+impl Display for Population {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut timers = self
+            .timer_counts
+            .iter()
+            .zip(0..)
+            .flat_map(|(&count, timer)| (0..count).map(move |_| timer));
+        if let Some(timer) = timers.next() {
+            write!(f, "{}", timer)?;
+        }
+        timers.try_for_each(|timer| write!(f, ",{}", timer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supports_other_timers() {
+        let population: Population = EXAMPLE.parse().unwrap();
+        assert_eq!(simulate(&population, 80), 5934);
+        let same = population.clone().with_timers(RESTART_TIMER, NEW_TIMER);
+        assert_eq!(simulate(&same.unwrap(), 80), 5934);
+        assert!(population.clone().with_timers(2, 2).is_err());
+        assert!(population.clone().with_timers(1, 3).is_err());
+
+        // Fish that spawn every day, with newborns waiting a day, grow like
+        // the Fibonacci numbers
+        let fibonacci = "0".parse::<Population>().unwrap().with_timers(0, 1);
+        assert_eq!(simulate(&fibonacci.unwrap(), 10), 144);
+        let longer = population.with_timers(10, 20).unwrap();
+        assert!(simulate(&longer, 80) < 5934);
+    }
+
+    #[test]
+    fn counts_fish_by_timer() {
+        let population: Population = EXAMPLE.parse().unwrap();
+        assert_eq!(population.counts_by_timer(), [0, 1, 1, 2, 1, 0, 0, 0, 0]);
+        // 6,0,6,4,5,6,0,1,1,2,6,7,8,8,8
+        assert_eq!(
+            population.after(11).counts_by_timer(),
+            [2, 2, 1, 0, 1, 1, 4, 1, 3]
+        );
+        let longer = population.clone().with_timers(6, 10).unwrap().after(3);
+        assert_eq!(longer.counts_by_timer(), [2, 1, 0, 0, 0, 1, 1, 0, 0]);
+        assert_eq!(
+            longer.counts_by_any_timer(),
+            [2, 1, 0, 0, 0, 1, 1, 0, 0, 1, 1]
+        );
+        let shorter = population.with_timers(2, 4).unwrap();
+        assert_eq!(shorter.counts_by_timer(), [0, 1, 1, 2, 1, 0, 0, 0, 0]);
+        assert_eq!(shorter.counts_by_any_timer(), [0, 1, 1, 2, 1]);
+    }
+
+    #[test]
+    fn finds_populations() {
+        for (count, days) in [(5934, 80), (7, 0), (1000, 10), (0, 256)] {
+            let population = find_population(count, days).unwrap();
+            assert_eq!(simulate(&population, days), count);
+        }
+        assert_eq!(find_population(1, 80).err(), Some(FindError::Impossible));
+        assert_eq!(find_population(10, 30).err(), Some(FindError::Impossible));
+
+        // The puzzle's own answer, with a fish growing to billions
+        let population = find_population(26_984_457_539, 256).unwrap();
+        assert_eq!(simulate(&population, 256), 26_984_457_539);
+        assert_eq!(
+            find_population_within(26_984_457_539, 256, 1).err(),
+            Some(FindError::SearchLimit)
+        );
+
+        let population = find_population(5934, 80).unwrap();
+        let text = population.to_string();
+        let parsed: Population = text.parse().unwrap();
+        assert_eq!(parsed.to_string(), text);
+    }
 
-        let timer_counts = (0..=NEW_TIMER)
-            .map(|timer| *totals.get(&timer).unwrap_or(&0))
-            .collect();
+    #[test]
+    fn backends_agree() {
+        let population: Population = EXAMPLE.parse().unwrap();
+        for days in 0..=256 {
+            let expected = Some(u128::from(simulate(&population, days)));
+            for backend in [Stepping, Matrix] {
+                let count = simulate_with(&population, days.into(), backend);
+                assert_eq!(count, expected);
+            }
+        }
+        for backend in [Stepping, Matrix] {
+            assert_eq!(simulate_with(&population, 2000, backend), None);
+        }
+    }
 
-        Ok(Self { timer_counts })
+    #[test]
+    fn counts_modulo() {
+        let population: Population = EXAMPLE.parse().unwrap();
+        assert_eq!(
+            simulate_modulo(&population, 256, 1_000_000_007),
+            Ok(26_984_457_539 % 1_000_000_007)
+        );
+        assert_eq!(simulate_modulo(&population, 80, 1), Ok(0));
+        assert!(simulate_modulo(&population, 80, 0).is_err());
+        assert!(simulate_modulo(&population, 1_000_000_000_000, 97).is_ok());
     }
 }
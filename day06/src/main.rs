@@ -1,34 +1,185 @@
 use clap::{crate_description, App, Arg};
-use day06::{simulate, Population};
-use std::fs::read_to_string;
+use common::{read_or_example, Backend as _, Pretty, COLOR_CHOICES};
+use day06::{
+    find_population, simulate_modulo, simulate_with, Backend, Population,
+    EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
+    let backends = Backend::names();
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless_one(&["example", "find"])
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("restart-timer")
+                .long("restart-timer")
+                .takes_value(true)
+                .value_name("DAYS")
+                .default_value("6")
+                .validator(is_number)
+                .help("Timer of fish that have just spawned"),
+        )
+        .arg(
+            Arg::with_name("new-timer")
+                .long("new-timer")
+                .takes_value(true)
+                .value_name("DAYS")
+                .default_value("8")
+                .validator(is_number)
+                .help("Timer of newborn fish"),
+        )
+        .arg(
+            Arg::with_name("by-timer")
+                .long("by-timer")
+                .help("Prints how many fish have each timer after each part"),
+        )
+        .arg(
+            Arg::with_name("days")
+                .long("days")
+                .takes_value(true)
+                .value_name("DAYS")
+                .validator(is_number)
+                .help("Also counts the fish after DAYS days"),
+        )
+        .arg(
+            Arg::with_name("modulo")
+                .long("modulo")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .requires("days")
+                .validator(|value| match value.parse::<u64>() {
+                    Ok(modulus) if modulus > 0 => Ok(()),
+                    _ => Err("must be a positive number".to_string()),
+                })
+                .help("Counts the fish after DAYS days modulo NUMBER"),
+        )
+        .arg(
+            Arg::with_name("find")
+                .long("find")
+                .takes_value(true)
+                .value_name("COUNT")
+                .requires("days")
+                .conflicts_with_all(&["INPUT", "example"])
+                .validator(is_number)
+                .help("Finds fish that grow to COUNT fish after DAYS days"),
+        )
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .takes_value(true)
+                .possible_values(&backends)
+                .default_value(Backend::default().name())
+                .help("Algorithm used to solve the puzzle"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    if let Some(count) = args.value_of("find") {
+        let days = args.value_of("days").unwrap().parse().unwrap();
+        match find_population(count.parse().unwrap(), days) {
+            Ok(population) => println!("{}", population),
+            Err(err) => {
+                println!(
+                    "{}",
+                    pretty.error(format!(
+                        "Failed to find fish that grow to {} after {} days: {}",
+                        count, days, err
+                    ))
+                );
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    let restart = args.value_of("restart-timer").unwrap().parse().unwrap();
+    let new = args.value_of("new-timer").unwrap().parse().unwrap();
+    let input = match read_input(args.value_of("INPUT"))
+        .and_then(|population| population.with_timers(restart, new))
+    {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", simulate(&input, 80));
-    println!("Part 2: {}", simulate(&input, 256));
+    // Already checked by the argument's possible values
+    let backend =
+        Backend::from_name(args.value_of("backend").unwrap()).unwrap();
+
+    if let Some(days) = args.value_of("days") {
+        let days = days.parse().unwrap();
+        let count = match args.value_of("modulo") {
+            Some(modulus) => {
+                simulate_modulo(&input, days, modulus.parse().unwrap())
+                    .map(|count| count.to_string())
+            }
+            None => simulate_with(&input, days, backend)
+                .map(|count| count.to_string())
+                .ok_or_else(|| "Too many fish to count".to_string()),
+        };
+        match count {
+            Ok(count) => {
+                println!("After {} days: {}", days, pretty.answer(count))
+            }
+            Err(err) => println!("{}", pretty.error(err)),
+        }
+    }
+
+    if args.is_present("by-timer") {
+        for days in [80, 256] {
+            let counts = input
+                .after(days)
+                .counts_by_any_timer()
+                .iter()
+                .map(|count| count.to_string())
+                .collect::<Vec<_>>();
+            println!("Fish by timer after {} days: {}", days, counts.join(","));
+        }
+    }
+
+    match simulate_with(&input, 80, backend) {
+        Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
+        None => println!("Part 1: Too many fish to count"),
+    }
+    match simulate_with(&input, 256, backend) {
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
+        None => println!("Part 2: Too many fish to count"),
+    }
+}
+
+fn is_number(value: String) -> Result<(), String> {
+    match value.parse::<u64>() {
+        Ok(_) => Ok(()),
+        _ => Err("must be a non-negative number".to_string()),
+    }
 }
 
-fn read_input(filename: &str) -> Result<Population, String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())?
-        .parse()
+fn read_input(filename: Option<&str>) -> Result<Population, String> {
+    read_or_example(filename, EXAMPLE)?.parse()
 }
@@ -0,0 +1,20 @@
+use crate::{simulate, Population};
+use alloc::string::String;
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let population: Population = input.parse()?;
+    report.found(simulate(&population, params.get("part1_days")?));
+    report.found(simulate(&population, params.get("part2_days")?));
+    Ok(())
+}
+
+register_solution!(
+    day: 6,
+    params: &[("part1_days", 80), ("part2_days", 256)],
+    solve: solve,
+);
@@ -1,31 +1,319 @@
-fn total_distance_simple(positions: &[i32], target: i32) -> i32 {
-    positions.iter().map(|&pos| (pos - target).abs()).sum()
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use Backend::*;
+
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+/// How much fuel a crab burns to move some distance. It shouldn't go down
+/// as the distance grows, so that no crab needs to go past the outermost
+/// ones. Any `Fn(i32) -> i32` is one.
+pub trait CostFn {
+    fn cost(&self, distance: i32) -> i32;
+}
+
+impl<F: Fn(i32) -> i32> CostFn for F {
+    fn cost(&self, distance: i32) -> i32 {
+        self(distance)
+    }
 }
 
-fn total_distance_incremental(positions: &[i32], target: i32) -> i32 {
+/// One unit of fuel per step, as in part 1.
+pub fn constant_cost(distance: i32) -> i32 {
+    distance
+}
+
+/// One more unit of fuel for every step than for the previous one, as in
+/// part 2.
+pub fn incremental_cost(distance: i32) -> i32 {
+    (distance * distance + distance) / 2
+}
+
+fn total_cost<C: CostFn>(positions: &[i32], target: i32, cost: &C) -> i32 {
     positions
         .iter()
-        .map(|&pos| {
-            let dist = (pos - target).abs();
-            (dist * dist + dist) / 2
-        })
+        .map(|&pos| cost.cost((pos - target).abs()))
         .sum()
 }
 
-pub fn part1(positions: &[i32]) -> Option<i32> {
-    let min = *positions.iter().min().unwrap();
-    let max = *positions.iter().max().unwrap();
-
+/// The least fuel the crabs need to align, with each of them burning `cost`
+/// for the distance it moves, trying every position between the outermost
+/// crabs.
+pub fn min_total_cost<C: CostFn>(positions: &[i32], cost: C) -> Option<i32> {
+    let min = *positions.iter().min()?;
+    let max = *positions.iter().max()?;
     (min..=max)
-        .map(|num| total_distance_simple(positions, num))
+        .map(|target| total_cost(positions, target, &cost))
         .min()
 }
 
+/// Same as [`min_total_cost`], narrowing down the position by thirds, which
+/// only works when `cost` grows at least as fast with every step, like both
+/// puzzle costs do, so that the total can't go back down once it goes up.
+pub fn min_convex_total_cost<C: CostFn>(
+    positions: &[i32],
+    cost: C,
+) -> Option<i32> {
+    let mut low = *positions.iter().min()?;
+    let mut high = *positions.iter().max()?;
+    while high - low > 2 {
+        let third = (high - low) / 3;
+        let (mid1, mid2) = (low + third, high - third);
+        let cost1 = total_cost(positions, mid1, &cost);
+        let cost2 = total_cost(positions, mid2, &cost);
+        if cost1 < cost2 {
+            high = mid2 - 1;
+        } else if cost1 > cost2 {
+            low = mid1 + 1;
+        } else {
+            (low, high) = (mid1, mid2);
+        }
+    }
+    (low..=high)
+        .map(|target| total_cost(positions, target, &cost))
+        .min()
+}
+
+/// Ways of finding the position that is cheapest to align to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Backend {
+    /// Goes straight to the median, or to the positions around the mean
+    /// when every step costs more than the previous one, in linear time.
+    #[default]
+    ClosedForm,
+    /// Tries every position between the outermost crabs, which is slower
+    /// but useful to check the others.
+    BruteForce,
+    /// Narrows down the cheapest position by thirds.
+    TernarySearch,
+}
+
+impl common::Backend for Backend {
+    const ALL: &'static [Self] = &[ClosedForm, BruteForce, TernarySearch];
+
+    fn name(&self) -> &'static str {
+        match self {
+            BruteForce => "brute-force",
+            TernarySearch => "ternary-search",
+            ClosedForm => "closed-form",
+        }
+    }
+}
+
+pub fn part1(positions: &[i32]) -> Option<i32> {
+    part1_with(positions, Backend::default())
+}
+
 pub fn part2(positions: &[i32]) -> Option<i32> {
-    let min = *positions.iter().min().unwrap();
-    let max = *positions.iter().max().unwrap();
+    part2_with(positions, Backend::default())
+}
 
-    (min..=max)
-        .map(|num| total_distance_incremental(positions, num))
+pub fn part1_with(positions: &[i32], backend: Backend) -> Option<i32> {
+    match backend {
+        BruteForce => min_total_cost(positions, constant_cost),
+        TernarySearch => min_convex_total_cost(positions, constant_cost),
+        ClosedForm => {
+            if positions.is_empty() {
+                return None;
+            }
+            let mut positions = positions.to_vec();
+            let middle = positions.len() / 2;
+            let (_, &mut median, _) = positions.select_nth_unstable(middle);
+            Some(total_cost(&positions, median, &constant_cost))
+        }
+    }
+}
+
+pub fn part2_with(positions: &[i32], backend: Backend) -> Option<i32> {
+    match backend {
+        BruteForce => min_total_cost(positions, incremental_cost),
+        TernarySearch => min_convex_total_cost(positions, incremental_cost),
+        ClosedForm => {
+            // The cheapest position is at most half a step away from the
+            // mean, so only the integers next to it need checking
+            let len = i64::try_from(positions.len()).ok().filter(|&n| n > 0)?;
+            let sum = positions.iter().map(|&pos| i64::from(pos)).sum::<i64>();
+            let mean = sum.div_euclid(len) as i32;
+            (mean - 1..=mean + 1)
+                .map(|num| total_cost(positions, num, &incremental_cost))
+                .min()
+        }
+    }
+}
+
+/// The number of crabs at each position, in order of position.
+pub fn aggregate(positions: &[i32]) -> Vec<(i32, u64)> {
+    let mut counts = BTreeMap::new();
+    for &pos in positions {
+        *counts.entry(pos).or_insert(0) += 1;
+    }
+    counts.into_iter().collect()
+}
+
+fn weighted_total_cost<F>(crabs: &[(i32, u64)], target: i32, cost: F) -> u128
+where
+    F: Fn(u128) -> u128,
+{
+    crabs
+        .iter()
+        .map(|&(pos, count)| {
+            let distance = u128::from(pos.abs_diff(target));
+            cost(distance) * u128::from(count)
+        })
+        .sum()
+}
+
+/// Same as [`part1`] for crabs given as positions with how many of them are
+/// at each, in any order, taking time and memory that only grow with the
+/// number of positions.
+pub fn part1_weighted(crabs: &[(i32, u64)]) -> Option<u128> {
+    let mut crabs = crabs.to_vec();
+    crabs.sort_unstable();
+    let total = crabs.iter().map(|&(_, count)| u128::from(count)).sum();
+    let mut seen = 0;
+    let &(median, _) = crabs.iter().find(|&&(_, count)| {
+        seen += u128::from(count);
+        seen > 0 && 2 * seen >= total
+    })?;
+    Some(weighted_total_cost(&crabs, median, |distance| distance))
+}
+
+/// Same as [`part2`] for crabs given as positions with how many of them are
+/// at each, like [`part1_weighted`].
+pub fn part2_weighted(crabs: &[(i32, u64)]) -> Option<u128> {
+    let total: i128 = crabs.iter().map(|&(_, count)| i128::from(count)).sum();
+    if total == 0 {
+        return None;
+    }
+    let sum: i128 = crabs
+        .iter()
+        .map(|&(pos, count)| i128::from(pos) * i128::from(count))
+        .sum();
+    let mean = sum.div_euclid(total) as i32;
+    (mean.saturating_sub(1)..=mean.saturating_add(1))
+        .map(|target| {
+            weighted_total_cost(crabs, target, |distance| {
+                (distance * distance + distance) / 2
+            })
+        })
         .min()
 }
+
+pub fn parse_input(input: &str) -> Result<Vec<i32>, String> {
+    input
+        .split(',')
+        .map(|num| {
+            num.trim()
+                .parse()
+                .map_err(|err| format!("Invalid position '{}': {}", num, err))
+        })
+        .collect::<Result<_, _>>()
+}
+
+/// Parses crab positions, each optionally followed by `:` and how many crabs
+/// are there, adding up the crabs at the same position.
+pub fn parse_weighted(input: &str) -> Result<Vec<(i32, u64)>, String> {
+    let mut counts = BTreeMap::new();
+    for entry in input.split(',') {
+        let (pos, count) = match entry.split_once(':') {
+            Some((pos, count)) => (pos, count.trim().parse()),
+            None => (entry, Ok(1)),
+        };
+        let count: u64 = count
+            .map_err(|err| format!("Invalid count '{}': {}", entry, err))?;
+        let pos: i32 = pos
+            .trim()
+            .parse()
+            .map_err(|err| format!("Invalid position '{}': {}", pos, err))?;
+        *counts.entry(pos).or_insert(0) += count;
+    }
+    Ok(counts.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Backend as _;
+    use proptest::prelude::*;
+
+    #[test]
+    fn backends_agree() {
+        let positions = parse_input("16,1,2,0,4,2,7,1,2,14").unwrap();
+        for &backend in Backend::ALL {
+            assert_eq!(part1_with(&positions, backend), Some(37));
+            assert_eq!(part2_with(&positions, backend), Some(168));
+        }
+        assert_eq!(part1_with(&[], ClosedForm), None);
+        assert_eq!(part2_with(&[], ClosedForm), None);
+    }
+
+    #[test]
+    fn supports_other_costs() {
+        let positions = parse_input("16,1,2,0,4,2,7,1,2,14").unwrap();
+        assert_eq!(min_total_cost(&positions, constant_cost), Some(37));
+        assert_eq!(min_total_cost(&positions, |d: i32| d * d), Some(291));
+        assert_eq!(min_total_cost(&positions, |d: i32| d.min(3)), Some(15));
+        let piecewise = |d: i32| if d < 5 { d } else { 2 * d - 5 };
+        assert_eq!(min_total_cost(&positions, piecewise), Some(53));
+        assert_eq!(min_total_cost(&[], constant_cost), None);
+    }
+
+    #[test]
+    fn solves_weighted_crabs() {
+        let positions = parse_input("16,1,2,0,4,2,7,1,2,14").unwrap();
+        let crabs = aggregate(&positions);
+        assert_eq!(crabs.len(), 7);
+        assert_eq!(parse_weighted("16,1:2,2:3,0,4,7,14"), Ok(crabs.clone()));
+        assert_eq!(part1_weighted(&crabs), Some(37));
+        assert_eq!(part2_weighted(&crabs), Some(168));
+
+        let crabs = parse_weighted("0:3000000000,10:1000000000").unwrap();
+        assert_eq!(part1_weighted(&crabs), Some(10_000_000_000));
+        assert_eq!(part2_weighted(&crabs), Some(45_000_000_000));
+        assert_eq!(part1_weighted(&[(3, 0)]), None);
+        assert!(parse_weighted("1:x").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn backends_match_brute_force(
+            positions in prop::collection::vec(0..200, 1..30)
+        ) {
+            for backend in [ClosedForm, TernarySearch] {
+                prop_assert_eq!(
+                    part1_with(&positions, backend),
+                    part1_with(&positions, BruteForce)
+                );
+                prop_assert_eq!(
+                    part2_with(&positions, backend),
+                    part2_with(&positions, BruteForce)
+                );
+            }
+            let crabs = aggregate(&positions);
+            let as_u128 = |answer: Option<i32>| answer.map(|n| n as u128);
+            prop_assert_eq!(
+                part1_weighted(&crabs),
+                as_u128(part1_with(&positions, BruteForce))
+            );
+            prop_assert_eq!(
+                part2_weighted(&crabs),
+                as_u128(part2_with(&positions, BruteForce))
+            );
+            let cubic = |d: i32| d * d * d;
+            prop_assert_eq!(
+                min_convex_total_cost(&positions, cubic),
+                min_total_cost(&positions, cubic)
+            );
+        }
+    }
+}
@@ -1,46 +1,106 @@
 use clap::{crate_description, App, Arg};
-use day07::{part1, part2};
-use std::fs::read_to_string;
+use common::{read_or_example, Backend as _, Pretty, COLOR_CHOICES};
+use day07::{
+    parse_input, parse_weighted, part1_weighted, part1_with, part2_weighted,
+    part2_with, Backend, EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
+    let backends = Backend::names();
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("weighted")
+                .long("weighted")
+                .conflicts_with("backend")
+                .help(
+                    "Reads positions as POSITION:COUNT pairs, solving in \
+                    time that only grows with the number of positions",
+                ),
+        )
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .takes_value(true)
+                .possible_values(&backends)
+                .default_value(Backend::default().name())
+                .help("Algorithm used to solve the puzzle"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    if args.is_present("weighted") {
+        let crabs = match read_or_example(args.value_of("INPUT"), EXAMPLE)
+            .and_then(|contents| parse_weighted(&contents))
+        {
+            Ok(data) => data,
+            Err(err) => {
+                println!(
+                    "{}",
+                    pretty.error(format!("Failed to read input: {}", err))
+                );
+                exit(2);
+            }
+        };
+        match part1_weighted(&crabs) {
+            Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
+            None => println!("Part 1: Not found"),
+        }
+        match part2_weighted(&crabs) {
+            Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
+            None => println!("Part 2: Not found"),
+        }
+        return;
+    }
+
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    match part1(&input) {
-        Some(answer) => println!("Part 1: {}", &answer),
+    // Already checked by the argument's possible values
+    let backend =
+        Backend::from_name(args.value_of("backend").unwrap()).unwrap();
+
+    match part1_with(&input, backend) {
+        Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
         None => println!("Part 1: Not found"),
     }
-    match part2(&input) {
-        Some(answer) => println!("Part 2: {}", &answer),
+    match part2_with(&input, backend) {
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
         None => println!("Part 2: Not found"),
     }
 }
 
-fn read_input(filename: &str) -> Result<Vec<i32>, String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())?
-        .split(',')
-        .map(|num| {
-            num.trim()
-                .parse()
-                .map_err(|err| format!("Invalid position '{}': {}", num, err))
-        })
-        .collect::<Result<_, _>>()
+fn read_input(filename: Option<&str>) -> Result<Vec<i32>, String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
@@ -0,0 +1,16 @@
+use crate::{parse_input, part1, part2};
+use alloc::string::String;
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let positions = parse_input(input)?;
+    report.maybe(part1(&positions));
+    report.maybe(part2(&positions));
+    Ok(())
+}
+
+register_solution!(day: 7, solve: solve);
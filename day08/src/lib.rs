@@ -1,11 +1,23 @@
-use std::collections::{HashMap, HashSet};
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::iter::FromIterator;
 use std::str::FromStr;
+use Backend::*;
 use Segment::*;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-enum Segment {
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Segment {
     A = 1,
     B = 2,
     C = 4,
@@ -15,40 +27,329 @@ enum Segment {
     G = 64,
 }
 
-type SegmentMap = HashMap<Segment, Segment>;
-type Signal = HashSet<Segment>;
+const SEGMENTS: [Segment; 7] = [A, B, C, D, E, F, G];
 
-fn map_signal(signal: &Signal, map: &SegmentMap) -> Option<Signal> {
-    signal
-        .iter()
-        .map(|s| map.get(s).copied())
-        .collect::<Option<HashSet<_>>>()
+/// The segment that each wire is connected to.
+pub type SegmentMap = HashMap<Segment, Segment>;
+
+/// A set of wires or segments, one bit for each.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Signal(u8);
+
+impl Signal {
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, segment: Segment) -> bool {
+        self.0 & segment as u8 != 0
+    }
+
+    pub fn insert(&mut self, segment: Segment) {
+        self.0 |= segment as u8;
+    }
+
+    /// The wires that are in both this signal and `other`.
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// The wires in this signal that aren't in `other`.
+    pub fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// The wires in this signal, in alphabetical order.
+    pub fn iter(self) -> impl Iterator<Item = Segment> {
+        SEGMENTS.into_iter().filter(move |&s| self.contains(s))
+    }
+}
+
+impl From<Segment> for Signal {
+    fn from(segment: Segment) -> Self {
+        Self(segment as u8)
+    }
+}
+
+impl FromIterator<Segment> for Signal {
+    fn from_iter<I: IntoIterator<Item = Segment>>(iter: I) -> Self {
+        let mut signal = Self::default();
+        for segment in iter {
+            signal.insert(segment);
+        }
+        signal
+    }
+}
+
+fn map_signal(signal: Signal, map: &SegmentMap) -> Option<Signal> {
+    signal.iter().map(|s| map.get(&s).copied()).collect()
+}
+
+/// The digit that `signal` shows once its wires are connected as in `map`.
+pub fn decode_signal(signal: Signal, map: &SegmentMap) -> Option<u32> {
+    DigitTable::default().decode_signal(signal, map)
+}
+
+/// The digits a display can show, by the segments that are lit for each,
+/// and the base of the numbers they make up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DigitTable {
+    radix: u32,
+    digits: [Option<u32>; 128],
+}
+
+impl DigitTable {
+    /// A table for numbers in base `radix`, with the segments lit for each
+    /// digit given as letters, such as `("cf", 1)`.
+    pub fn new(radix: u32, digits: &[(&str, u32)]) -> Result<Self, String> {
+        let mut table = Self {
+            radix,
+            digits: [None; 128],
+        };
+        for &(segments, digit) in digits {
+            if digit >= radix {
+                return Err(format!(
+                    "Digit {} is too large for base {}",
+                    digit, radix
+                ));
+            }
+            let mask = parse_signal(segments)?.bits() as usize;
+            if table.digits[mask].replace(digit).is_some() {
+                return Err(format!("Segments '{}' used twice", segments));
+            }
+        }
+        Ok(table)
+    }
+
+    /// The hexadecimal digits, with A to F shown as `A`, `b`, `C`, `d`, `E`
+    /// and `F`.
+    pub fn hexadecimal() -> Self {
+        let mut digits = DECIMAL_DIGITS.to_vec();
+        digits.extend([
+            ("abcdef", 10),
+            ("bdefg", 11),
+            ("abeg", 12),
+            ("cdefg", 13),
+            ("abdeg", 14),
+            ("abde", 15),
+        ]);
+        Self::new(16, &digits).unwrap()
+    }
+
+    /// Same as [`decode_signal`], with the digits in this table.
+    pub fn decode_signal(
+        &self,
+        signal: Signal,
+        map: &SegmentMap,
+    ) -> Option<u32> {
+        map_signal(signal, map).and_then(|signal| self.digit(signal))
+    }
+
+    fn digit(&self, segments: Signal) -> Option<u32> {
+        self.digits[segments.bits() as usize]
+    }
+}
+
+const DECIMAL_DIGITS: [(&str, u32); 10] = [
+    ("abcefg", 0),
+    ("cf", 1),
+    ("acdeg", 2),
+    ("acdfg", 3),
+    ("bcdf", 4),
+    ("abdfg", 5),
+    ("abdefg", 6),
+    ("acf", 7),
+    ("abcdefg", 8),
+    ("abcdfg", 9),
+];
+
+/// The decimal digits from the puzzle.
+impl Default for DigitTable {
+    fn default() -> Self {
+        Self::new(10, &DECIMAL_DIGITS).unwrap()
+    }
 }
 
-fn signal_to_digit<'a, I>(signal: I) -> Option<u32>
-where
-    I: IntoIterator<Item = &'a Segment>,
-{
-    match signal.into_iter().map(|s| *s as u8).sum() {
-        119 => Some(0),
-        36 => Some(1),
-        93 => Some(2),
-        109 => Some(3),
-        46 => Some(4),
-        107 => Some(5),
-        123 => Some(6),
-        37 => Some(7),
-        127 => Some(8),
-        111 => Some(9),
-        _ => None,
+/// Ways of working out how the wires are connected from an entry's patterns.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Backend {
+    /// Tells the patterns for each digit apart by the wires they share.
+    #[default]
+    Deduction,
+    /// Tells the wires apart by how many of the patterns each of them is in.
+    Frequency,
+    /// Uses both, failing when they don't agree.
+    CrossCheck,
+}
+
+impl common::Backend for Backend {
+    const ALL: &'static [Self] = &[Deduction, Frequency, CrossCheck];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Deduction => "deduction",
+            Frequency => "frequency",
+            CrossCheck => "cross-check",
+        }
     }
 }
 
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Display {
     patterns: Vec<Signal>,
     output: Vec<Signal>,
 }
 
+/// Why an entry couldn't be decoded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodeError {
+    /// None, or more than one, of the patterns with `segments` segments
+    /// could be `digit`.
+    NoUniquePattern {
+        digit: u32,
+        segments: usize,
+        candidates: Vec<String>,
+    },
+    /// None, or more than one, of the wires in `candidates`, taken from
+    /// `pattern`, could go to `segment`.
+    NoUniqueWire {
+        segment: Segment,
+        pattern: String,
+        candidates: String,
+    },
+    /// None, or more than one, of the wires in `candidates`, which are in
+    /// `count` of the patterns, could go to `segment`.
+    NoUniqueFrequency {
+        segment: Segment,
+        count: usize,
+        candidates: String,
+    },
+    /// Deduction and frequency analysis found different wirings, given as
+    /// the wires going to each segment in alphabetical order.
+    Disagreement { deduced: String, counted: String },
+    /// An output signal isn't a digit with the wiring that was deduced.
+    InvalidOutput { signal: String },
+    /// The output, `digits` digits long, reads a number too large for a
+    /// `u32`.
+    OutputTooLarge { digits: usize },
+    /// No wiring turns every pattern and output into digits.
+    NoWiring,
+    /// More than one wiring turns every pattern and output into digits,
+    /// reading `outputs`.
+    Ambiguous { wirings: usize, outputs: Vec<u32> },
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::NoUniquePattern {
+                digit,
+                segments,
+                candidates,
+            } if candidates.is_empty() => {
+                write!(f, "No {}-segment pattern for {}", segments, digit)
+            }
+            Self::NoUniquePattern {
+                digit,
+                segments,
+                candidates,
+            } => write!(
+                f,
+                "No unique {}-segment pattern for {} among {}",
+                segments,
+                digit,
+                candidates.join(", ")
+            ),
+            Self::NoUniqueWire {
+                segment,
+                pattern,
+                candidates,
+            } => write!(
+                f,
+                "No unique wire for segment {} in pattern {} (candidates '{}')",
+                segment.letter(),
+                pattern,
+                candidates
+            ),
+            Self::NoUniqueFrequency {
+                segment,
+                count,
+                candidates,
+            } => write!(
+                f,
+                "No unique wire for segment {} in {} patterns (candidates '{}')",
+                segment.letter(),
+                count,
+                candidates
+            ),
+            Self::Disagreement { deduced, counted } => write!(
+                f,
+                "Deduction found wiring {} but frequency analysis found {}",
+                deduced, counted
+            ),
+            Self::InvalidOutput { signal } => {
+                write!(f, "Output {} isn't a digit", signal)
+            }
+            Self::OutputTooLarge { digits } => {
+                write!(f, "Output of {} digits is too large to read", digits)
+            }
+            Self::NoWiring => {
+                write!(f, "No wiring turns every signal into a digit")
+            }
+            Self::Ambiguous { wirings, outputs } => {
+                let outputs = outputs
+                    .iter()
+                    .map(|output| output.to_string())
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "{} wirings fit, reading {}",
+                    wirings,
+                    outputs.join(" or ")
+                )
+            }
+        }
+    }
+}
+
+/// The wires of a signal as letters, in alphabetical order.
+fn signal_string(signal: Signal) -> String {
+    signal.iter().map(|s| s.letter()).collect()
+}
+
+/// The wires going to each segment, in alphabetical order of segment.
+fn wiring_string(seg_map: &SegmentMap) -> String {
+    let mut wires = seg_map.iter().collect::<Vec<_>>();
+    wires.sort_unstable_by_key(|(_, segment)| segment.index());
+    wires.into_iter().map(|(wire, _)| wire.letter()).collect()
+}
+
+/// The only wire in `candidates`, taken from `pattern`, that can go to
+/// `segment`.
+fn unique_wire(
+    segment: Segment,
+    pattern: Signal,
+    candidates: Signal,
+) -> Result<Segment, DecodeError> {
+    match candidates.iter().collect::<Vec<_>>()[..] {
+        [wire] => Ok(wire),
+        _ => Err(DecodeError::NoUniqueWire {
+            segment,
+            pattern: signal_string(pattern),
+            candidates: signal_string(candidates),
+        }),
+    }
+}
+
 impl Display {
     fn count_easy_digits(&self) -> usize {
         self.output
@@ -57,148 +358,248 @@ impl Display {
             .count()
     }
 
-    fn decode(&self) -> Option<u32> {
-        // 1: {C, F}
-        let pat1 = match self
-            .patterns
+    /// Reads the output, deducing the wiring from the patterns, or else
+    /// searching for it.
+    pub fn decode(&self) -> Result<u32, DecodeError> {
+        self.decode_with(&DigitTable::default(), Backend::default())
+    }
+
+    /// Same as [`decode`](Display::decode), for a display that shows the
+    /// digits in `table`, working out the wiring with `backend`. Wirings for
+    /// digits other than the puzzle's are searched for, since both backends
+    /// rely on the puzzle's patterns.
+    pub fn decode_with(
+        &self,
+        table: &DigitTable,
+        backend: Backend,
+    ) -> Result<u32, DecodeError> {
+        self.wiring(backend)
+            .and_then(|seg_map| self.read_output(&seg_map, table))
+            .or_else(|err| {
+                if let DecodeError::Disagreement { .. } = err {
+                    return Err(err);
+                }
+                match self.search_output(table) {
+                    Err(DecodeError::NoWiring) => Err(err),
+                    result => result,
+                }
+            })
+    }
+
+    fn wiring(&self, backend: Backend) -> Result<SegmentMap, DecodeError> {
+        match backend {
+            Deduction => self.deduce_wiring(),
+            Frequency => self.count_wiring(),
+            CrossCheck => {
+                let deduced = self.deduce_wiring()?;
+                let counted = self.count_wiring()?;
+                if deduced != counted {
+                    return Err(DecodeError::Disagreement {
+                        deduced: wiring_string(&deduced),
+                        counted: wiring_string(&counted),
+                    });
+                }
+                Ok(deduced)
+            }
+        }
+    }
+
+    fn read_output(
+        &self,
+        seg_map: &SegmentMap,
+        table: &DigitTable,
+    ) -> Result<u32, DecodeError> {
+        self.output.iter().try_fold(0u32, |acc, signal| {
+            let digit =
+                table.decode_signal(*signal, seg_map).ok_or_else(|| {
+                    DecodeError::InvalidOutput {
+                        signal: signal_string(*signal),
+                    }
+                })?;
+            acc.checked_mul(table.radix)
+                .and_then(|acc| acc.checked_add(digit))
+                .ok_or(DecodeError::OutputTooLarge {
+                    digits: self.output.len(),
+                })
+        })
+    }
+
+    /// Works out how the wires are connected to the segments, so that more
+    /// signals from the same display can be decoded with [`decode_signal`].
+    /// Entries without the patterns needed to deduce it are searched for the
+    /// one wiring that turns all their patterns and outputs into digits.
+    pub fn solve_wiring(&self) -> Result<SegmentMap, DecodeError> {
+        self.solve_wiring_with(&DigitTable::default(), Backend::default())
+    }
+
+    /// Same as [`solve_wiring`](Display::solve_wiring), for a display that
+    /// shows the digits in `table`, working out the wiring with `backend`.
+    pub fn solve_wiring_with(
+        &self,
+        table: &DigitTable,
+        backend: Backend,
+    ) -> Result<SegmentMap, DecodeError> {
+        self.wiring(backend).or_else(|err| {
+            if let DecodeError::Disagreement { .. } = err {
+                return Err(err);
+            }
+            let mut wirings = self.matching_wirings(table).collect::<Vec<_>>();
+            match wirings.len() {
+                0 => Err(err),
+                1 => Ok(wirings.remove(0)),
+                _ => Err(self.ambiguity(&wirings, table)),
+            }
+        })
+    }
+
+    /// Reads the output with every wiring that fits, for entries where there
+    /// is more than one, as long as they all read the same.
+    fn search_output(&self, table: &DigitTable) -> Result<u32, DecodeError> {
+        let wirings = self.matching_wirings(table).collect::<Vec<_>>();
+        match self.ambiguity(&wirings, table) {
+            DecodeError::Ambiguous { outputs, .. } if outputs.len() == 1 => {
+                Ok(outputs[0])
+            }
+            err => Err(err),
+        }
+    }
+
+    fn ambiguity(
+        &self,
+        wirings: &[SegmentMap],
+        table: &DigitTable,
+    ) -> DecodeError {
+        if wirings.is_empty() {
+            return DecodeError::NoWiring;
+        }
+        // The wirings turn every output into a digit, so they can only fail
+        // to read one that's too large
+        let mut outputs = match wirings
             .iter()
-            .filter(|p| p.len() == 2)
-            .collect::<Vec<_>>()[..]
+            .map(|seg_map| self.read_output(seg_map, table))
+            .collect::<Result<Vec<_>, _>>()
         {
-            [x] => Some(x),
-            _ => None,
-        }?;
+            Ok(outputs) => outputs,
+            Err(err) => return err,
+        };
+        outputs.sort_unstable();
+        outputs.dedup();
+        DecodeError::Ambiguous {
+            wirings: wirings.len(),
+            outputs,
+        }
+    }
 
-        // 7: {A, C, F}
-        let pat7 = match self
+    /// Tries every way the wires could be connected to the segments, for
+    /// entries that don't have the patterns [`deduce_wiring`] relies on,
+    /// keeping those that turn all patterns and outputs into digits.
+    ///
+    /// [`deduce_wiring`]: Display::deduce_wiring
+    fn matching_wirings<'a>(
+        &'a self,
+        table: &'a DigitTable,
+    ) -> impl Iterator<Item = SegmentMap> + 'a {
+        permutations(SEGMENTS)
+            .into_iter()
+            .filter(move |wiring| {
+                self.patterns.iter().chain(&self.output).all(|signal| {
+                    let segments = signal.iter().map(|s| wiring[s.index()]);
+                    table.digit(segments.collect()).is_some()
+                })
+            })
+            .map(|wiring| SEGMENTS.into_iter().zip(wiring).collect())
+    }
+
+    /// The only pattern with `segments` segments that passes `filter`, which
+    /// has to be the one for `digit`.
+    fn unique_pattern<P>(
+        &self,
+        digit: u32,
+        segments: usize,
+        filter: P,
+    ) -> Result<Signal, DecodeError>
+    where
+        P: Fn(Signal) -> bool,
+    {
+        let candidates = self
             .patterns
             .iter()
-            .filter(|p| p.len() == 3)
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(x),
-            _ => None,
-        }?;
+            .copied()
+            .filter(|&p| p.len() == segments && filter(p))
+            .collect::<Vec<_>>();
+        match candidates[..] {
+            [pattern] => Ok(pattern),
+            _ => Err(DecodeError::NoUniquePattern {
+                digit,
+                segments,
+                candidates: candidates.into_iter().map(signal_string).collect(),
+            }),
+        }
+    }
+
+    fn deduce_wiring(&self) -> Result<SegmentMap, DecodeError> {
+        // 1: {C, F}
+        let pat1 = self.unique_pattern(1, 2, |_| true)?;
+
+        // 7: {A, C, F}
+        let pat7 = self.unique_pattern(7, 3, |_| true)?;
 
         // pat7 - pat1 = {A, C, F} - {C, F} => {A}
-        let seg_a = match pat7.difference(pat1).collect::<Vec<_>>()[..] {
-            [x] => Some(*x),
-            _ => None,
-        }?;
+        let seg_a = unique_wire(A, pat7, pat7.difference(pat1))?;
 
         // 4: {B, C, D, F}
-        let pat4 = match self
-            .patterns
-            .iter()
-            .filter(|p| p.len() == 4)
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(x),
-            _ => None,
-        }?;
+        let pat4 = self.unique_pattern(4, 4, |_| true)?;
 
         // 9: {A, B, C, D, F, G}
         // {A, B, C, D, F, G} - {B, C, D, F} = {A, G}
-        let pat9 = match self
-            .patterns
-            .iter()
-            .filter(|p| {
-                p.len() == 6
-                    && p.difference(pat4).collect::<HashSet<_>>().len() == 2
-            })
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(x),
-            _ => None,
-        }?;
+        let pat9 =
+            self.unique_pattern(9, 6, |p| p.difference(pat4).len() == 2)?;
 
         // pat9 - pat4 - {A} = {A, B, C, D, F, G} - {B, C, D, F} - {A} => {G}
-        let seg_g = match pat9
-            .difference(pat4)
-            .filter(|&&s| s != seg_a)
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(*x),
-            _ => None,
-        }?;
+        let seg_g = unique_wire(
+            G,
+            pat9,
+            pat9.difference(pat4).difference(seg_a.into()),
+        )?;
 
         // 3: {A, C, D, F, G}
         // {A, C, D, F, G} - {A, C, F} = {D, G}
-        let pat3 = match self
-            .patterns
-            .iter()
-            .filter(|p| {
-                p.len() == 5
-                    && p.difference(pat7).collect::<HashSet<_>>().len() == 2
-            })
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(x),
-            _ => None,
-        }?;
+        let pat3 =
+            self.unique_pattern(3, 5, |p| p.difference(pat7).len() == 2)?;
 
         // pat3 - pat7 - {G} = {A, C, D, F, G} - {A, C, F} - {G} => {D}
-        let seg_d = match pat3
-            .difference(pat7)
-            .filter(|&&s| s != seg_g)
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(*x),
-            _ => None,
-        }?;
+        let seg_d = unique_wire(
+            D,
+            pat3,
+            pat3.difference(pat7).difference(seg_g.into()),
+        )?;
 
         // 2: {A, C, D, E, G}
         // {A, C, D, E, G} - {A, B, C, D, F, G} = {E}
-        let pat2 = match self
-            .patterns
-            .iter()
-            .filter(|p| {
-                p.len() == 5
-                    && p.difference(pat9).collect::<HashSet<_>>().len() == 1
-            })
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(x),
-            _ => None,
-        }?;
+        let pat2 =
+            self.unique_pattern(2, 5, |p| p.difference(pat9).len() == 1)?;
 
         // pat2 - pat9 = {A, C, D, E, G} - {A, B, C, D, F, G} => {E}
-        let seg_e = match pat2.difference(pat9).collect::<Vec<_>>()[..] {
-            [x] => Some(*x),
-            _ => None,
-        }?;
+        let seg_e = unique_wire(E, pat2, pat2.difference(pat9))?;
 
         // pat2 - {A, D, E, G} = {A, C, D, E, G} - {A, D, E, G} = {C}
-        let seg_c = match pat2
-            .iter()
-            .filter(|&&s| s != seg_a && s != seg_d && s != seg_e && s != seg_g)
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(*x),
-            _ => None,
-        }?;
+        let seg_c = unique_wire(
+            C,
+            pat2,
+            pat2.difference(Signal::from_iter([seg_a, seg_d, seg_e, seg_g])),
+        )?;
 
         // pat1 - {C} = {C, F} - {C} = {F}
-        let seg_f = match pat1
-            .iter()
-            .filter(|&&s| s != seg_c)
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(*x),
-            _ => None,
-        }?;
+        let seg_f = unique_wire(F, pat1, pat1.difference(seg_c.into()))?;
 
         // pat4 - {C, D, F} = {B, C, D, F} - {C, D, F} = {B}
-        let seg_b = match pat4
-            .iter()
-            .filter(|&&s| s != seg_c && s != seg_d && s != seg_f)
-            .collect::<Vec<_>>()[..]
-        {
-            [x] => Some(*x),
-            _ => None,
-        }?;
+        let seg_b = unique_wire(
+            B,
+            pat4,
+            pat4.difference(Signal::from_iter([seg_c, seg_d, seg_f])),
+        )?;
 
-        let seg_map: SegmentMap = HashMap::from([
+        Ok(HashMap::from([
             (seg_a, A),
             (seg_b, B),
             (seg_c, C),
@@ -206,15 +607,68 @@ impl Display {
             (seg_e, E),
             (seg_f, F),
             (seg_g, G),
-        ]);
+        ]))
+    }
+
+    /// Works out the wiring from how many of the ten patterns each wire is
+    /// in, which is different for every segment except A and C, of which
+    /// only C is in 1, and D and G, of which only D is in 4.
+    fn count_wiring(&self) -> Result<SegmentMap, DecodeError> {
+        let in_patterns = |count| {
+            SEGMENTS
+                .into_iter()
+                .filter(|&wire| {
+                    self.patterns.iter().filter(|p| p.contains(wire)).count()
+                        == count
+                })
+                .collect::<Signal>()
+        };
+        let unique_wire = |segment, count, candidates: Signal| match candidates
+            .iter()
+            .collect::<Vec<_>>()[..]
+        {
+            [wire] => Ok((wire, segment)),
+            _ => Err(DecodeError::NoUniqueFrequency {
+                segment,
+                count,
+                candidates: signal_string(candidates),
+            }),
+        };
+
+        let pat1 = self.unique_pattern(1, 2, |_| true)?;
+        let pat4 = self.unique_pattern(4, 4, |_| true)?;
+        [
+            unique_wire(A, 8, in_patterns(8).difference(pat1)),
+            unique_wire(B, 6, in_patterns(6)),
+            unique_wire(C, 8, in_patterns(8).intersection(pat1)),
+            unique_wire(D, 7, in_patterns(7).intersection(pat4)),
+            unique_wire(E, 4, in_patterns(4)),
+            unique_wire(F, 9, in_patterns(9)),
+            unique_wire(G, 7, in_patterns(7).difference(pat4)),
+        ]
+        .into_iter()
+        .collect()
+    }
+}
 
-        let res = self.output.iter().try_fold(0, |acc, signal| {
-            map_signal(signal, &seg_map)
-                .and_then(|signal| signal_to_digit(&signal))
-                .map(|digit| acc * 10 + digit)
-        });
-        res
+/// Every ordering of `items`, generated with Heap's algorithm.
+fn permutations<const N: usize>(mut items: [Segment; N]) -> Vec<[Segment; N]> {
+    let mut counters = [0; N];
+    let mut result = vec![items];
+    let mut i = 1;
+    while i < N {
+        if counters[i] < i {
+            let swap_with = if i % 2 == 0 { 0 } else { counters[i] };
+            items.swap(swap_with, i);
+            result.push(items);
+            counters[i] += 1;
+            i = 1;
+        } else {
+            counters[i] = 0;
+            i += 1;
+        }
     }
+    result
 }
 
 pub fn part1(display_entries: &[Display]) -> usize {
@@ -225,11 +679,39 @@ pub fn part1(display_entries: &[Display]) -> usize {
 }
 
 pub fn part2(display_entries: &[Display]) -> Option<u32> {
+    part2_with(display_entries, &DigitTable::default(), Backend::default())
+}
+
+pub fn part2_with(
+    display_entries: &[Display],
+    table: &DigitTable,
+    backend: Backend,
+) -> Option<u32> {
     display_entries
         .iter()
-        .map(|display| display.decode())
-        .collect::<Option<Vec<_>>>()
-        .map(|values| values.iter().sum())
+        .map(|display| display.decode_with(table, backend).ok())
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .try_fold(0u32, u32::checked_add)
+}
+
+impl Segment {
+    /// The position of the segment in alphabetical order.
+    fn index(self) -> usize {
+        (self as u8).trailing_zeros() as usize
+    }
+
+    fn letter(self) -> char {
+        match self {
+            A => 'a',
+            B => 'b',
+            C => 'c',
+            D => 'd',
+            E => 'e',
+            F => 'f',
+            G => 'g',
+        }
+    }
 }
 
 impl TryFrom<char> for Segment {
@@ -259,24 +741,202 @@ impl FromStr for Display {
 
         let patterns = patterns_str
             .split_whitespace()
-            .map(|pat_str| {
-                pat_str
-                    .chars()
-                    .map(Segment::try_from)
-                    .collect::<Result<Signal, _>>()
-            })
+            .map(parse_signal)
             .collect::<Result<Vec<_>, _>>()?;
 
         let output = output_str
             .split_whitespace()
-            .map(|out_str| {
-                out_str
-                    .chars()
-                    .map(Segment::try_from)
-                    .collect::<Result<Signal, _>>()
-            })
-            .collect::<Result<Vec<Signal>, _>>()?;
+            .map(parse_signal)
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(Self { patterns, output })
     }
 }
+
+/// Parses the wires of a signal, such as `cdfeb`.
+pub fn parse_signal(s: &str) -> Result<Signal, String> {
+    s.chars().map(Segment::try_from).collect()
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Display>, String> {
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_more_signals() {
+        let entry = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb \
+            ab | cdfeb fcadb cdfeb cdbaf";
+        let display: Display = entry.parse().unwrap();
+        let wiring = display.solve_wiring().unwrap();
+        assert_eq!(wiring[&Segment::D], Segment::A);
+        let signal = parse_signal("cagedb").unwrap();
+        assert_eq!(decode_signal(signal, &wiring), Some(0));
+        let signal = parse_signal("ab").unwrap();
+        assert_eq!(decode_signal(signal, &wiring), Some(1));
+        let signal = parse_signal("abc").unwrap();
+        assert_eq!(decode_signal(signal, &wiring), None);
+        assert!(parse_signal("abx").is_err());
+    }
+
+    #[test]
+    fn searches_unusual_entries() {
+        assert_eq!(permutations(SEGMENTS).len(), 5040);
+        let entry = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb \
+            ab | cdfeb fcadb cdfeb cdbaf";
+        let display: Display = entry.parse().unwrap();
+        assert_eq!(display.decode(), Ok(5353));
+
+        // Without the patterns for 1 and 4, the wiring can't be deduced
+        let display: Display = entry
+            .replace(" eafb", "")
+            .replace(" ab |", " |")
+            .parse()
+            .unwrap();
+        assert!(display.deduce_wiring().is_err());
+        assert!(display.solve_wiring().is_ok());
+        assert_eq!(display.decode(), Ok(5353));
+
+        // With no patterns, a 5 could also be a 2 or a 3
+        let display: Display = "| cdfeb".parse().unwrap();
+        assert_eq!(
+            display.decode(),
+            Err(DecodeError::Ambiguous {
+                wirings: 720,
+                outputs: vec![2, 3, 5]
+            })
+        );
+        let display: Display = "| abcdefg ab".parse().unwrap();
+        assert!(display.solve_wiring().is_err());
+        assert_eq!(display.decode(), Ok(81));
+    }
+
+    #[test]
+    fn decodes_other_digits() {
+        let hex = DigitTable::hexadecimal();
+        // Wired straight through, showing 0 to F and then "bEEF"
+        let patterns = "abcefg cf acdeg acdfg bcdf abdfg abdefg acf abcdefg \
+            abcdfg abcdef bdefg abeg cdefg abdeg abde";
+        let display: Display = format!("{} | bdefg abdeg abdeg abde", patterns)
+            .parse()
+            .unwrap();
+        assert_eq!(display.decode_with(&hex, Deduction), Ok(0xBEEF));
+        assert!(display.decode().is_err());
+
+        // Swapping wires a and g
+        let swapped = |s: &str| {
+            s.chars()
+                .map(|c| match c {
+                    'a' => 'g',
+                    'g' => 'a',
+                    c => c,
+                })
+                .collect::<String>()
+        };
+        let display: Display = swapped(&format!("{} | abcdef cf", patterns))
+            .parse()
+            .unwrap();
+        assert_eq!(display.decode_with(&hex, Deduction), Ok(0xA1));
+        let wiring = display.solve_wiring_with(&hex, Frequency).unwrap();
+        assert_eq!(wiring[&Segment::G], Segment::A);
+
+        let binary = DigitTable::new(2, &[("abcefg", 0), ("cf", 1)]).unwrap();
+        let display: Display = "cf abcefg | cf abcefg cf".parse().unwrap();
+        assert_eq!(display.decode_with(&binary, Deduction), Ok(0b101));
+        assert!(DigitTable::new(2, &[("cf", 2)]).is_err());
+        assert!(DigitTable::new(2, &[("cf", 0), ("fc", 1)]).is_err());
+    }
+
+    #[test]
+    fn backends_agree() {
+        use common::Backend as _;
+
+        let entries = parse_input(EXAMPLE).unwrap();
+        for display in &entries {
+            assert_eq!(display.count_wiring(), display.deduce_wiring());
+        }
+        for &backend in Backend::ALL {
+            let table = DigitTable::default();
+            assert_eq!(part2_with(&entries, &table, backend), Some(66582));
+        }
+
+        // 6 lit up as abcdeg
+        let display: Display =
+            "abcefg cf acdeg acdfg bcdf abdfg abcdeg acf abcdefg abcdfg | cf"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            display.decode_with(&DigitTable::default(), Deduction),
+            Ok(1)
+        );
+        assert_eq!(
+            display.decode_with(&DigitTable::default(), CrossCheck),
+            Err(DecodeError::Disagreement {
+                deduced: "abcdefg".to_string(),
+                counted: "abfdecg".to_string(),
+            })
+        );
+        let display: Display = "cf bcdf | cf".parse().unwrap();
+        assert_eq!(
+            display.count_wiring().unwrap_err().to_string(),
+            "No unique wire for segment a in 8 patterns (candidates '')"
+        );
+    }
+
+    #[test]
+    fn explains_decode_errors() {
+        let error = |entry: &str| {
+            let display: Display = entry.parse().unwrap();
+            display.decode().unwrap_err().to_string()
+        };
+        assert_eq!(
+            error("ab cd | ab"),
+            "No unique 2-segment pattern for 1 among ab, cd"
+        );
+        assert_eq!(
+            error("ab abcd abcdefg bcd | ab"),
+            "No unique wire for segment a in pattern bcd (candidates 'cd')"
+        );
+        assert_eq!(
+            error("ab abc abcd abcefg | ab"),
+            "No 6-segment pattern for 9"
+        );
+        let entry = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb \
+            ab | cdfeb abc";
+        assert_eq!(error(entry), "Output abc isn't a digit");
+
+        // Nine digits fit in a u32 in decimal, and eight in hexadecimal, but
+        // not one more
+        let decimal = "abcefg cf acdeg acdfg bcdf abdfg abdefg acf abcdefg \
+            abcdfg";
+        let entry = |patterns: &str, output: &str, digits| {
+            let output = vec![output; digits].join(" ");
+            format!("{} | {}", patterns, output)
+                .parse::<Display>()
+                .unwrap()
+        };
+        assert_eq!(entry(decimal, "abcdfg", 9).decode(), Ok(999_999_999));
+        assert_eq!(
+            error(&format!("{} | {}", decimal, ["abcdfg"; 10].join(" "))),
+            "Output of 10 digits is too large to read"
+        );
+        let hex = DigitTable::hexadecimal();
+        let patterns =
+            format!("{} abcdef bdefg abeg cdefg abdeg abde", decimal);
+        let output = |digits| {
+            entry(&patterns, "abde", digits).decode_with(&hex, Deduction)
+        };
+        assert_eq!(output(8), Ok(u32::MAX));
+        assert_eq!(output(9), Err(DecodeError::OutputTooLarge { digits: 9 }));
+    }
+}
@@ -1,46 +1,89 @@
 use clap::{crate_description, App, Arg};
-use day08::{part1, part2, Display};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use common::{read_or_example, Backend as _, Pretty, COLOR_CHOICES};
+use day08::{
+    parse_input, part1, part2_with, Backend, DigitTable, Display, EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
+    let backends = Backend::names();
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
+        .arg(
+            Arg::with_name("digits")
+                .long("digits")
+                .takes_value(true)
+                .possible_values(&["decimal", "hexadecimal"])
+                .default_value("decimal")
+                .help("Digits shown by the displays"),
+        )
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .takes_value(true)
+                .possible_values(&backends)
+                .default_value(Backend::default().name())
+                .help("Algorithm used to work out the wiring"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
-    match part2(&input) {
-        Some(answer) => println!("Part 2: {}", &answer),
-        None => println!("Part 2: Not found"),
+    let table = match args.value_of("digits") {
+        Some("hexadecimal") => DigitTable::hexadecimal(),
+        _ => DigitTable::default(),
+    };
+    // Already checked by the argument's possible values
+    let backend =
+        Backend::from_name(args.value_of("backend").unwrap()).unwrap();
+
+    println!("Part 1: {}", pretty.answer(part1(&input)));
+    match part2_with(&input, &table, backend) {
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
+        None => {
+            println!("Part 2: Not found");
+            for (entry, entry_num) in input.iter().zip(1..) {
+                if let Err(err) = entry.decode_with(&table, backend) {
+                    let message = format!("Entry {}: {}", entry_num, err);
+                    println!("{}", pretty.error(message));
+                }
+            }
+        }
     }
 }
 
-fn read_input(filename: &str) -> Result<Vec<Display>, String> {
-    let input_file = File::open(filename).map_err(|err| err.to_string())?;
-
-    BufReader::new(input_file)
-        .lines()
-        .zip(1..)
-        .map(|(line, line_num)| {
-            line.map_err(|err| (line_num, err.to_string()))
-                .and_then(|value| value.parse().map_err(|err| (line_num, err)))
-        })
-        .collect::<Result<_, _>>()
-        .map_err(|(line_num, err)| format!("Line {}: {}", line_num, err))
+fn read_input(filename: Option<&str>) -> Result<Vec<Display>, String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
@@ -0,0 +1,15 @@
+use crate::{parse_input, part1, part2};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let entries = parse_input(input)?;
+    report.found(part1(&entries));
+    report.maybe(part2(&entries));
+    Ok(())
+}
+
+register_solution!(day: 8, solve: solve);
@@ -1,87 +1,496 @@
-use std::collections::{HashMap, HashSet};
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{parse_digit_grid, Connectivity, Grid, UnionFind, Visualize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
-pub struct HeightMap(HashMap<(i32, i32), u8>);
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct HeightMap(Grid<u8>);
 
 impl HeightMap {
-    fn low_points(&self) -> impl Iterator<Item = (i32, i32, u8)> + '_ {
-        self.0.iter().filter_map(|(&(row, col), &height)| {
-            if self.is_low_point(row, col, height) {
-                Some((row, col, height))
+    fn low_points(
+        &self,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (usize, usize, u8)> + '_ {
+        self.0.iter().filter_map(move |((x, y), &height)| {
+            if self.is_low_point(x, y, height, connectivity) {
+                Some((x, y, height))
             } else {
                 None
             }
         })
     }
 
-    fn is_low_point(&self, row: i32, col: i32, height: u8) -> bool {
-        [
-            (row - 1, col),
-            (row, col - 1),
-            (row, col + 1),
-            (row + 1, col),
-        ]
-        .iter()
-        .all(|&(r, c)| self.0.get(&(r, c)).map(|&h| h > height).unwrap_or(true))
+    fn is_low_point(
+        &self,
+        x: usize,
+        y: usize,
+        height: u8,
+        connectivity: Connectivity,
+    ) -> bool {
+        self.0
+            .neighbors(x, y, connectivity)
+            .all(|pos| self.0[pos] > height)
     }
 
-    fn total_risk_level(&self) -> u32 {
-        self.low_points()
+    fn total_risk_level(&self, connectivity: Connectivity) -> u32 {
+        self.low_points(connectivity)
             .map(|(_, _, height)| height as u32 + 1)
             .sum()
     }
 
-    fn basin_size(&self, start_row: i32, start_col: i32) -> usize {
-        let mut points = HashSet::from([(start_row, start_col)]);
-        let mut queue = vec![(start_row, start_col)];
-
-        while let Some((row, col)) = queue.pop() {
-            queue.extend(
-                [
-                    (row - 1, col),
-                    (row, col - 1),
-                    (row, col + 1),
-                    (row + 1, col),
-                ]
-                .into_iter()
-                .filter(|&(r, c)| {
-                    self.0.get(&(r, c)).map(|&h| h < 9).unwrap_or(false)
-                        && points.insert((r, c))
-                }),
-            );
+    /// Joins every location that isn't a ridge (height 9) with the
+    /// neighbours it flows to or from, so each set is a basin.
+    fn basins(&self, connectivity: Connectivity) -> UnionFind {
+        let mut basins = UnionFind::new(self.0.len());
+        for ((x, y), &height) in self.0.iter() {
+            if height == 9 {
+                continue;
+            }
+            let index = self.0.index_of(x, y).unwrap();
+            for (nx, ny) in self.0.neighbors(x, y, connectivity) {
+                if self.0[(nx, ny)] < 9 {
+                    basins.union(index, self.0.index_of(nx, ny).unwrap());
+                }
+            }
         }
-
-        points.len()
+        basins
     }
 }
 
 pub fn part1(height_map: &HeightMap) -> u32 {
-    height_map.total_risk_level()
+    part1_with(height_map, Connectivity::Four)
+}
+
+/// Same as [`part1`], where locations are adjacent as `connectivity` says.
+pub fn part1_with(height_map: &HeightMap, connectivity: Connectivity) -> u32 {
+    height_map.total_risk_level(connectivity)
 }
 
 pub fn part2(height_map: &HeightMap) -> usize {
-    let mut sizes = height_map
-        .low_points()
-        .map(|(row, col, _)| height_map.basin_size(row, col))
+    part2_with(height_map, Connectivity::Four)
+}
+
+/// Same as [`part2`], where locations are adjacent as `connectivity` says.
+pub fn part2_with(height_map: &HeightMap, connectivity: Connectivity) -> usize {
+    let mut basins = height_map.basins(connectivity);
+    // Diagonals can join the basins of more than one low point
+    let mut roots = height_map
+        .low_points(connectivity)
+        .map(|(x, y, _)| basins.find(height_map.0.index_of(x, y).unwrap()))
+        .collect::<Vec<_>>();
+    roots.sort_unstable();
+    roots.dedup();
+    let mut sizes = roots
+        .into_iter()
+        .map(|root| basins.set_size(root))
         .collect::<Vec<_>>();
     sizes.sort_unstable();
     sizes.iter().rev().take(3).product()
 }
 
+/// The height of every location along with the basin it belongs to, unless
+/// it's a ridge. Basins are numbered from 0 in the order they're reached,
+/// row by row.
+pub fn basin_map(height_map: &HeightMap) -> Grid<(u8, Option<usize>)> {
+    basin_map_with(height_map, Connectivity::Four)
+}
+
+/// Same as [`basin_map`], where locations are adjacent as `connectivity`
+/// says.
+pub fn basin_map_with(
+    height_map: &HeightMap,
+    connectivity: Connectivity,
+) -> Grid<(u8, Option<usize>)> {
+    let map = &height_map.0;
+    let mut basins = height_map.basins(connectivity);
+    // The number given to each basin, by the location at its root
+    let mut numbers = vec![None; map.len()];
+    let mut count = 0;
+    Grid::from_fn(map.width(), map.height(), |x, y| {
+        let height = map[(x, y)];
+        if height == 9 {
+            return (height, None);
+        }
+        let root = basins.find(map.index_of(x, y).unwrap());
+        let number = *numbers[root].get_or_insert_with(|| {
+            count += 1;
+            count - 1
+        });
+        (height, Some(number))
+    })
+}
+
+/// What there is to know about a basin without flooding it again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Basin {
+    /// Same number as in [`basin_map`].
+    pub id: usize,
+    pub size: usize,
+    /// The lowest location, the first one row by row if there's a tie.
+    pub low_point: (usize, usize),
+    /// How many locations are next to a ridge or the edge of the map.
+    pub boundary_cells: usize,
+}
+
+/// Every basin, in the order they're numbered.
+pub fn basins(height_map: &HeightMap) -> impl Iterator<Item = Basin> {
+    basins_with(height_map, Connectivity::Four)
+}
+
+/// Same as [`basins`], where locations are adjacent as `connectivity` says.
+pub fn basins_with(
+    height_map: &HeightMap,
+    connectivity: Connectivity,
+) -> impl Iterator<Item = Basin> {
+    let map = basin_map_with(height_map, connectivity);
+    let mut basins: Vec<Basin> = Vec::new();
+    for ((x, y), &(height, id)) in map.iter() {
+        let id = match id {
+            Some(id) => id,
+            None => continue,
+        };
+        if id == basins.len() {
+            basins.push(Basin {
+                id,
+                size: 0,
+                low_point: (x, y),
+                boundary_cells: 0,
+            });
+        }
+        let basin = &mut basins[id];
+        basin.size += 1;
+        if height < map[basin.low_point].0 {
+            basin.low_point = (x, y);
+        }
+        let neighbors = map.neighbors(x, y, connectivity).collect::<Vec<_>>();
+        if neighbors.len() < connectivity.offsets().len()
+            || neighbors.into_iter().any(|pos| map[pos].1.is_none())
+        {
+            basin.boundary_cells += 1;
+        }
+    }
+    basins.into_iter()
+}
+
+/// Where water goes from a location that has no lower neighbour but has some
+/// at the same height.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Plateau {
+    /// Stays there, so the location is a sink of its own.
+    #[default]
+    Stop,
+    /// Flows across the plateau to the nearest location that has a lower
+    /// neighbour, or else to a single sink for the whole plateau.
+    Drain,
+}
+
+impl FromStr for Plateau {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "stop" => Ok(Plateau::Stop),
+            "drain" => Ok(Plateau::Drain),
+            _ => Err(format!("Invalid plateau policy '{}'", s)),
+        }
+    }
+}
+
+/// Where water goes from a location.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Flow {
+    /// Nowhere, as ridges aren't part of any basin.
+    Ridge,
+    /// Nowhere, as there's nowhere lower to go.
+    Sink,
+    /// To the neighbour at these coordinates.
+    To((usize, usize)),
+}
+
+/// Which neighbour water flows to from each location, following the steepest
+/// descent, so that every location drains into a sink.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Drainage(Grid<Flow>);
+
+impl Drainage {
+    pub fn flow(&self, x: usize, y: usize) -> Option<Flow> {
+        self.0.get(x, y).copied()
+    }
+
+    /// Every sink, row by row.
+    pub fn sinks(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.0
+            .iter()
+            .filter(|(_, &flow)| flow == Flow::Sink)
+            .map(|(pos, _)| pos)
+    }
+
+    /// The sink that each location drains into, unless it's a ridge.
+    pub fn sink_map(&self) -> Grid<Option<(usize, usize)>> {
+        let mut sinks = Grid::new(self.0.width(), self.0.height(), None);
+        for (start, _) in self.0.iter() {
+            let mut path = Vec::new();
+            let mut pos = start;
+            let sink = loop {
+                if let Some(sink) = sinks[pos] {
+                    break Some(sink);
+                }
+                match self.0[pos] {
+                    Flow::Ridge => break None,
+                    Flow::Sink => break Some(pos),
+                    Flow::To(next) => {
+                        path.push(pos);
+                        pos = next;
+                    }
+                }
+            };
+            sinks[pos] = sink;
+            for pos in path {
+                sinks[pos] = sink;
+            }
+        }
+        sinks
+    }
+
+    /// How many locations drain into each sink, in the same order as
+    /// [`sinks`](Drainage::sinks).
+    pub fn basin_sizes(&self) -> Vec<((usize, usize), usize)> {
+        let mut sizes = Grid::new(self.0.width(), self.0.height(), 0);
+        for &sink in self.sink_map().values().flatten() {
+            sizes[sink] += 1;
+        }
+        self.sinks().map(|sink| (sink, sizes[sink])).collect()
+    }
+}
+
+/// Works out where water flows from each location, going to its lowest
+/// neighbour, and across plateaus as `plateau` says.
+pub fn drainage(
+    height_map: &HeightMap,
+    connectivity: Connectivity,
+    plateau: Plateau,
+) -> Drainage {
+    let map = &height_map.0;
+    let mut flows = Grid::new(map.width(), map.height(), Flow::Sink);
+    let mut queue = VecDeque::new();
+    for ((x, y), &height) in map.iter() {
+        if height == 9 {
+            flows[(x, y)] = Flow::Ridge;
+            continue;
+        }
+        let lowest = map
+            .neighbors(x, y, connectivity)
+            .filter(|&pos| map[pos] < height)
+            .min_by_key(|&pos| map[pos]);
+        if let Some(pos) = lowest {
+            flows[(x, y)] = Flow::To(pos);
+            queue.push_back((x, y));
+        }
+    }
+    if plateau == Plateau::Stop {
+        return Drainage(flows);
+    }
+
+    // Flat locations drain towards the nearest ones that can flow lower,
+    // and plateaus that can't into the first of their locations
+    let mut reached = flows.map(|&flow| flow != Flow::Sink);
+    let mut starts = map.coords();
+    loop {
+        while let Some((x, y)) = queue.pop_front() {
+            for pos in map.neighbors(x, y, connectivity) {
+                if !reached[pos] && map[pos] == map[(x, y)] {
+                    reached[pos] = true;
+                    flows[pos] = Flow::To((x, y));
+                    queue.push_back(pos);
+                }
+            }
+        }
+        match starts.find(|&pos| !reached[pos]) {
+            Some(start) => {
+                reached[start] = true;
+                queue.push_back(start);
+            }
+            None => return Drainage(flows),
+        }
+    }
+}
+
+/// Letters for each basin, which are reused when there are more basins.
+const BASIN_LABELS: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// The basins filling up from their lowest locations, each drawn with its own
+/// letter, and the ridges between them drawn as `#`.
+pub struct Flooding<'a> {
+    height_map: &'a HeightMap,
+    connectivity: Connectivity,
+}
+
+impl<'a> Flooding<'a> {
+    pub fn new(height_map: &'a HeightMap, connectivity: Connectivity) -> Self {
+        Self {
+            height_map,
+            connectivity,
+        }
+    }
+
+    /// The basin that each location belongs to, and how many steps it takes
+    /// to reach it from the basin's lowest location.
+    fn reached(&self) -> Grid<Option<(usize, usize)>> {
+        let map = &self.height_map.0;
+        let mut reached = Grid::new(map.width(), map.height(), None);
+        let mut queue = VecDeque::new();
+        for basin in basins_with(self.height_map, self.connectivity) {
+            reached[basin.low_point] = Some((basin.id, 0));
+            queue.push_back(basin.low_point);
+        }
+        while let Some((x, y)) = queue.pop_front() {
+            let (id, steps) = reached[(x, y)].unwrap();
+            for pos in map.neighbors(x, y, self.connectivity) {
+                if map[pos] < 9 && reached[pos].is_none() {
+                    reached[pos] = Some((id, steps + 1));
+                    queue.push_back(pos);
+                }
+            }
+        }
+        reached
+    }
+
+    fn draw(
+        &self,
+        reached: &Grid<Option<(usize, usize)>>,
+        step: usize,
+    ) -> String {
+        let map = &self.height_map.0;
+        Grid::from_fn(map.width(), map.height(), |x, y| match reached[(x, y)] {
+            _ if map[(x, y)] == 9 => '#',
+            Some((id, steps)) if steps <= step => {
+                char::from(BASIN_LABELS[id % BASIN_LABELS.len()])
+            }
+            _ => '.',
+        })
+        .to_string()
+    }
+}
+
+impl Visualize for Flooding<'_> {
+    fn frames(&self) -> impl Iterator<Item = String> + '_ {
+        let reached = self.reached();
+        let last = reached.values().flatten().map(|&(_, steps)| steps).max();
+        (0..=last.unwrap_or(0)).map(move |step| self.draw(&reached, step))
+    }
+}
+
+/// The basins once they're full.
+impl Display for Flooding<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.draw(&self.reached(), usize::MAX))
+    }
+}
+
 impl FromStr for HeightMap {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.lines()
-            .zip(0..)
-            .flat_map(|(line, row)| {
-                line.chars().zip(0..).map(move |(ch, col)| {
-                    ch.to_digit(10)
-                        .ok_or(format!("Invalid height value '{}'", ch))
-                        .map(|val| ((row, col), u8::try_from(val).unwrap()))
-                })
-            })
-            .collect::<Result<HashMap<_, _>, _>>()
-            .map(HeightMap)
+        parse_digit_grid(s).map(HeightMap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_basins() {
+        let height_map: HeightMap = EXAMPLE.parse().unwrap();
+        let basins = basins(&height_map).collect::<Vec<_>>();
+        let sizes = basins.iter().map(|b| b.size).collect::<Vec<_>>();
+        assert_eq!(sizes, vec![3, 9, 14, 9]);
+        assert_eq!(
+            basins[1],
+            Basin {
+                id: 1,
+                size: 9,
+                low_point: (9, 0),
+                boundary_cells: 9,
+            }
+        );
+        assert_eq!(basins[2].boundary_cells, 10);
+        let joined = basins_with(&height_map, Connectivity::Eight)
+            .map(|b| (b.size, b.low_point))
+            .collect::<Vec<_>>();
+        assert_eq!(joined, vec![(35, (9, 0))]);
+    }
+
+    #[test]
+    fn draws_basins() {
+        let height_map: HeightMap = EXAMPLE.parse().unwrap();
+        let flooding = Flooding::new(&height_map, Connectivity::Four);
+        assert_eq!(
+            flooding.to_string(),
+            "aa###bbbbb\n\
+             a#ccc#b#bb\n\
+             #ccccc#d#b\n\
+             ccccc#ddd#\n\
+             #c###ddddd\n"
+        );
+        let frames = flooding.frames().collect::<Vec<_>>();
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].matches(char::is_alphabetic).count(), 4);
+        assert_eq!(frames.last(), Some(&flooding.to_string()));
+    }
+
+    #[test]
+    fn drains_basins() {
+        let height_map: HeightMap = EXAMPLE.parse().unwrap();
+        for plateau in [Plateau::Stop, Plateau::Drain] {
+            let drainage = drainage(&height_map, Connectivity::Four, plateau);
+            assert_eq!(
+                drainage.basin_sizes(),
+                vec![((1, 0), 3), ((9, 0), 9), ((2, 2), 14), ((6, 4), 9)]
+            );
+            assert_eq!(drainage.flow(0, 0), Some(Flow::To((1, 0))));
+            assert_eq!(drainage.flow(2, 0), Some(Flow::Ridge));
+        }
+
+        let flipped: HeightMap = "1119\n9999\n5539\n5559\n".parse().unwrap();
+        let drain = drainage(&flipped, Connectivity::Four, Plateau::Drain);
+        assert_eq!(drain.basin_sizes(), vec![((0, 0), 3), ((2, 2), 6)]);
+
+        let height_map: HeightMap = "5559\n5539\n9999\n1119\n".parse().unwrap();
+        let stop = drainage(&height_map, Connectivity::Four, Plateau::Stop);
+        assert_eq!(stop.sinks().count(), 7);
+        assert_eq!(stop.flow(1, 1), Some(Flow::To((2, 1))));
+        assert_eq!(stop.flow(1, 0), Some(Flow::Sink));
+        let drain = drainage(&height_map, Connectivity::Four, Plateau::Drain);
+        assert_eq!(drain.basin_sizes(), vec![((2, 1), 6), ((0, 3), 3)]);
+        assert_eq!(drain.flow(0, 0), Some(Flow::To((1, 0))));
+        assert_eq!(drain.flow(2, 3), Some(Flow::To((1, 3))));
+        assert_eq!(drain.sink_map()[(0, 1)], Some((2, 1)));
+        assert_eq!(drain.sink_map()[(3, 3)], None);
+    }
+
+    #[test]
+    fn supports_diagonals() {
+        let example: HeightMap = EXAMPLE.parse().unwrap();
+        assert_eq!(part1_with(&example, Connectivity::Eight), 15);
+        assert_eq!(part2_with(&example, Connectivity::Eight), 35);
+
+        let height_map: HeightMap = "191\n929\n191\n".parse().unwrap();
+        assert_eq!(part1(&height_map), 8 + 3);
+        assert_eq!(part2(&height_map), 1);
+        assert_eq!(part1_with(&height_map, Connectivity::Eight), 8);
+        assert_eq!(part2_with(&height_map, Connectivity::Eight), 5);
+        let basins = basin_map_with(&height_map, Connectivity::Eight);
+        assert_eq!(basins[(1, 1)], (2, Some(0)));
     }
 }
@@ -1,34 +1,154 @@
 use clap::{crate_description, App, Arg};
-use day09::{part1, part2, HeightMap};
-use std::fs::read_to_string;
+use common::{
+    read_or_example, Color, Connectivity, Pretty, Visualize, COLOR_CHOICES,
+};
+use day09::{
+    basin_map_with, basins_with, drainage, part1_with, part2_with, Flooding,
+    HeightMap, Plateau, EXAMPLE,
+};
 use std::process::exit;
+use std::time::Duration;
 
 fn main() {
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("diagonals")
+                .long("diagonals")
+                .help("Treats diagonal locations as adjacent too"),
+        )
+        .arg(
+            Arg::with_name("basins")
+                .long("basins")
+                .help("Prints the size, low point and boundary of each basin"),
+        )
+        .arg(
+            Arg::with_name("drainage")
+                .long("drainage")
+                .takes_value(true)
+                .value_name("PLATEAUS")
+                .possible_values(&["stop", "drain"])
+                .help(
+                    "Prints how many locations drain into each sink, with \
+                    water stopping on plateaus or draining across them",
+                ),
+        )
+        .arg(
+            Arg::with_name("draw")
+                .long("draw")
+                .help("Prints the map with basins in colors and ridges in red"),
+        )
+        .arg(
+            Arg::with_name("visualize")
+                .long("visualize")
+                .help("Animates the basins filling up before solving"),
+        )
+        .arg(
+            Arg::with_name("delay")
+                .long("delay")
+                .takes_value(true)
+                .default_value("100")
+                .validator(|value| {
+                    value
+                        .parse::<u64>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .help("Milliseconds between animation frames"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    let connectivity = if args.is_present("diagonals") {
+        Connectivity::Eight
+    } else {
+        Connectivity::Four
+    };
+
+    if args.is_present("draw") {
+        let basins = basin_map_with(&input, connectivity);
+        let drawing = pretty.grid(&basins, |&(height, basin)| {
+            let color = match basin {
+                Some(num) => Color::DISTINCT[num % Color::DISTINCT.len()],
+                None => Color::Red,
+            };
+            (char::from(b'0' + height), Some(color))
+        });
+        print!("{}", drawing);
+    }
+
+    if args.is_present("visualize") {
+        let delay = args.value_of("delay").unwrap().parse().unwrap();
+        if let Err(err) = Flooding::new(&input, connectivity)
+            .visualize(Duration::from_millis(delay))
+        {
+            println!(
+                "{}",
+                pretty.error(format!("Failed to visualize: {}", err))
+            );
+        }
+    }
+
+    if args.is_present("basins") {
+        for basin in basins_with(&input, connectivity) {
+            println!(
+                "Basin {}: {} locations, low point at {:?}, {} on its boundary",
+                basin.id, basin.size, basin.low_point, basin.boundary_cells
+            );
+        }
+    }
+
+    if let Some(policy) = args.value_of("drainage") {
+        // Already checked by the argument's possible values
+        let plateau: Plateau = policy.parse().unwrap();
+        let drainage = drainage(&input, connectivity, plateau);
+        for ((x, y), size) in drainage.basin_sizes() {
+            println!("Sink at ({}, {}): {} locations", x, y, size);
+        }
+    }
+
+    println!(
+        "Part 1: {}",
+        pretty.answer(part1_with(&input, connectivity))
+    );
+    println!(
+        "Part 2: {}",
+        pretty.answer(part2_with(&input, connectivity))
+    );
 }
 
-fn read_input(filename: &str) -> Result<HeightMap, String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())?
-        .parse()
+fn read_input(filename: Option<&str>) -> Result<HeightMap, String> {
+    read_or_example(filename, EXAMPLE)?.parse()
 }
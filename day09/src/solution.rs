@@ -0,0 +1,15 @@
+use crate::{part1, part2, HeightMap};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let height_map: HeightMap = input.parse()?;
+    report.found(part1(&height_map));
+    report.found(part2(&height_map));
+    Ok(())
+}
+
+register_solution!(day: 9, solve: solve);
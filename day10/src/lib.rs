@@ -1,117 +1,787 @@
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::io::BufRead;
 use std::str::FromStr;
-use Bracket::*;
 
-#[derive(Clone, Copy)]
-pub enum Bracket {
-    RoundOpen,
-    RoundClose,
-    SquareOpen,
-    SquareClose,
-    CurlyOpen,
-    CurlyClose,
-    AngleOpen,
-    AngleClose,
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+/// The pairs of brackets from the puzzle description.
+const STANDARD_PAIRS: [(char, char); 4] =
+    [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+/// An opening or closing bracket of one of the pairs of an [`Alphabet`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Bracket {
+    /// Where its pair is in the alphabet.
+    pair: u16,
+    closes: bool,
+    open: char,
+    close: char,
 }
 
 impl Bracket {
     fn is_close(&self) -> bool {
-        matches!(self, RoundClose | SquareClose | CurlyClose | AngleClose)
+        self.closes
     }
 
-    fn matches(&self, other: &Self) -> bool {
-        matches!(
-            (self, other),
-            (RoundOpen, RoundClose)
-                | (SquareOpen, SquareClose)
-                | (CurlyOpen, CurlyClose)
-                | (AngleOpen, AngleClose)
-        )
+    /// Whether the same character opens and closes its pair, like quotes.
+    fn is_symmetric(&self) -> bool {
+        self.open == self.close
+    }
+
+    /// The bracket that closes this one, unless it's a closing bracket. A
+    /// bracket that opens and closes its pair is closed by itself.
+    pub fn closing(&self) -> Option<Self> {
+        if self.closes {
+            return None;
+        }
+        Some(Self {
+            closes: !self.is_symmetric(),
+            ..*self
+        })
     }
 }
 
-pub struct Line(Vec<Bracket>);
+/// The pairs of opening and closing brackets that lines are made of, `()`,
+/// `[]`, `{}` and `<>` by default. A pair can open and close with the same
+/// character, like quotes, which closes the last chunk opened if it's of
+/// that pair, or else opens another.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alphabet {
+    pairs: Vec<(char, char)>,
+}
 
-impl Line {
-    fn score_corrupt(&self) -> Option<u64> {
-        let mut stack: Vec<Bracket> = Vec::new();
-
-        for &bracket in self.0.iter() {
-            if bracket.is_close() {
-                if !stack.pop().map(|b| b.matches(&bracket)).unwrap_or(false) {
-                    return match bracket {
-                        RoundClose => Some(3),
-                        SquareClose => Some(57),
-                        CurlyClose => Some(1197),
-                        AngleClose => Some(25137),
-                        _ => unreachable!(),
-                    };
+impl Alphabet {
+    pub fn new(pairs: &[(char, char)]) -> Result<Self, String> {
+        if pairs.is_empty() {
+            return Err("No pairs of brackets".to_string());
+        }
+        if pairs.len() > usize::from(u16::MAX) {
+            return Err(format!("Too many pairs of brackets: {}", pairs.len()));
+        }
+        let mut seen = Vec::new();
+        for &(open, close) in pairs {
+            for ch in [open, close] {
+                if ch.is_whitespace() || seen.contains(&ch) {
+                    return Err(format!("Invalid bracket '{}'", ch));
                 }
-            } else {
-                stack.push(bracket);
             }
+            seen.push(open);
+            seen.push(close);
         }
+        Ok(Self {
+            pairs: pairs.to_vec(),
+        })
+    }
 
-        None
+    /// How many pairs of brackets there are.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
     }
 
-    fn score_incomplete(&self) -> Option<u64> {
-        let mut stack: Vec<Bracket> = Vec::new();
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
 
-        for &bracket in self.0.iter() {
-            if bracket.is_close() {
-                if !stack.pop().map(|b| b.matches(&bracket)).unwrap_or(false) {
-                    // This line is corrupt, not incomplete
-                    return None;
-                }
-            } else {
-                stack.push(bracket);
+    pub fn bracket(&self, ch: char) -> Result<Bracket, String> {
+        find_bracket(&self.pairs, ch)
+    }
+
+    /// Every bracket, the opening one of each pair before its closing one.
+    pub fn brackets(&self) -> impl Iterator<Item = Bracket> + '_ {
+        (0..).zip(&self.pairs).flat_map(|(pair, &(open, close))| {
+            let opening = Bracket {
+                pair,
+                closes: false,
+                open,
+                close,
+            };
+            let closing = opening.closing().filter(|_| open != close);
+            std::iter::once(opening).chain(closing)
+        })
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self {
+            pairs: STANDARD_PAIRS.to_vec(),
+        }
+    }
+}
+
+/// Parses pairs of brackets written one after the other, as in `()[]{}<>`.
+impl FromStr for Alphabet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars = s.trim().chars().collect::<Vec<_>>();
+        if chars.len() % 2 != 0 {
+            return Err(format!("Invalid pairs of brackets '{}'", s));
+        }
+        let pairs = chars
+            .chunks(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect::<Vec<_>>();
+        Self::new(&pairs)
+    }
+}
+
+fn find_bracket(pairs: &[(char, char)], ch: char) -> Result<Bracket, String> {
+    (0..)
+        .zip(pairs)
+        .find(|(_, &(open, close))| ch == open || ch == close)
+        .map(|(pair, &(open, close))| Bracket {
+            pair,
+            closes: ch == close && open != close,
+            open,
+            close,
+        })
+        .ok_or_else(|| format!("Invalid bracket '{}'", ch))
+}
+
+/// How many points lines are worth, for each pair of brackets in the order of
+/// the [`Alphabet`]. Pairs without points are worth none.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scoring {
+    /// Points for the bracket where a line is corrupt.
+    pub corrupt: Vec<u64>,
+    /// Points for each bracket that completes a line.
+    pub completion: Vec<u64>,
+    /// What the score of a completion is multiplied by before adding the
+    /// points for each bracket.
+    pub multiplier: u64,
+}
+
+impl Scoring {
+    /// Checks that there are points for each pair of brackets of `alphabet`,
+    /// and no more.
+    pub fn check(&self, alphabet: &Alphabet) -> Result<(), String> {
+        for (points, what) in
+            [(&self.corrupt, "corrupt"), (&self.completion, "completion")]
+        {
+            if points.len() != alphabet.len() {
+                return Err(format!(
+                    "Expected {} {} points, one for each pair of brackets, \
+                    found {}",
+                    alphabet.len(),
+                    what,
+                    points.len()
+                ));
             }
         }
+        Ok(())
+    }
+}
+
+fn points(points: &[u64], bracket: &Bracket) -> u64 {
+    points.get(usize::from(bracket.pair)).copied().unwrap_or(0)
+}
+
+/// The scores from the puzzle description.
+impl Default for Scoring {
+    fn default() -> Self {
+        Self {
+            corrupt: vec![3, 57, 1197, 25137],
+            completion: vec![1, 2, 3, 4],
+            multiplier: 5,
+        }
+    }
+}
+
+/// Parses comma-separated points, one for each pair of brackets.
+pub fn parse_points(s: &str) -> Result<Vec<u64>, String> {
+    s.split(',')
+        .map(|num| {
+            num.trim()
+                .parse()
+                .map_err(|_| format!("Invalid points '{}'", num))
+        })
+        .collect()
+}
+
+/// Where a line is corrupt: the first closing bracket that doesn't close the
+/// last chunk that was opened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Corruption {
+    /// Counting from 1.
+    pub column: usize,
+    pub found: Bracket,
+    /// The bracket that closes the last chunk, unless there's none open.
+    pub expected: Option<Bracket>,
+}
+
+impl Display for Corruption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.expected {
+            Some(expected) => write!(
+                f,
+                "Expected {} at column {}, but found {} instead",
+                char::from(expected),
+                self.column,
+                char::from(self.found)
+            ),
+            None => write!(
+                f,
+                "Found {} at column {} with no chunk open",
+                char::from(self.found),
+                self.column
+            ),
+        }
+    }
+}
+
+/// Whether a line is valid, incomplete or corrupt, with what's needed to
+/// complete it or where it goes wrong.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LineStatus {
+    /// Every chunk is closed.
+    Valid,
+    /// Closing the chunks left open takes `completion`, in order.
+    Incomplete { completion: Vec<Bracket> },
+    /// Same as a [`Corruption`], where `position` is its column.
+    Corrupt {
+        position: usize,
+        found: Bracket,
+        expected: Option<Bracket>,
+    },
+}
+
+impl LineStatus {
+    fn is_corrupt(&self) -> bool {
+        matches!(self, Self::Corrupt { .. })
+    }
 
-        let mut score = 0;
-        while let Some(bracket) = stack.pop() {
-            let value = match bracket {
-                RoundOpen => 1,
-                SquareOpen => 2,
-                CurlyOpen => 3,
-                AngleOpen => 4,
-                _ => unreachable!(),
+    fn corrupt_score(&self, scoring: &Scoring) -> Option<u64> {
+        match self {
+            Self::Corrupt { found, .. } => {
+                Some(points(&scoring.corrupt, found))
+            }
+            _ => None,
+        }
+    }
+
+    /// The score of what completes the line, unless it's corrupt or the
+    /// score doesn't fit in a `u64`.
+    fn completion_score(&self, scoring: &Scoring) -> Option<u64> {
+        let completion = match self {
+            Self::Valid => &[][..],
+            Self::Incomplete { completion } => completion,
+            Self::Corrupt { .. } => return None,
+        };
+        completion.iter().try_fold(0u64, |score, bracket| {
+            scoring
+                .multiplier
+                .checked_mul(score)?
+                .checked_add(points(&scoring.completion, bracket))
+        })
+    }
+}
+
+/// Checks a line one bracket at a time, keeping only the chunks still open,
+/// so that the line itself never has to be kept.
+#[derive(Clone, Debug, Default)]
+pub struct Checker {
+    /// The pair of each chunk still open, which is all that's needed to
+    /// tell the bracket that opened it, even for very deeply nested lines.
+    open: Vec<u16>,
+    /// The characters of each pair seen so far, by its place in the
+    /// alphabet.
+    pairs: Vec<(char, char)>,
+    column: usize,
+    corruption: Option<Corruption>,
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bracket that opens `pair`, which has already been seen.
+    fn opening(&self, pair: u16) -> Bracket {
+        let (open, close) = self.pairs[usize::from(pair)];
+        Bracket {
+            pair,
+            closes: false,
+            open,
+            close,
+        }
+    }
+
+    /// Whether the brackets so far already make the line corrupt, so the
+    /// rest don't matter.
+    pub fn is_corrupt(&self) -> bool {
+        self.corruption.is_some()
+    }
+
+    /// Takes the next bracket of the line.
+    pub fn push(&mut self, bracket: Bracket) {
+        if self.is_corrupt() {
+            return;
+        }
+        self.column += 1;
+        let pair = usize::from(bracket.pair);
+        if self.pairs.len() <= pair {
+            self.pairs.resize(pair + 1, (bracket.open, bracket.close));
+        }
+        self.pairs[pair] = (bracket.open, bracket.close);
+
+        let closes = bracket.is_close()
+            || bracket.is_symmetric()
+                && self.open.last() == Some(&bracket.pair);
+        if closes {
+            let open = self.open.pop();
+            if open != Some(bracket.pair) {
+                self.corruption = Some(Corruption {
+                    column: self.column,
+                    found: bracket,
+                    expected: open
+                        .and_then(|pair| self.opening(pair).closing()),
+                });
+            }
+        } else {
+            self.open.push(bracket.pair);
+        }
+    }
+
+    /// Takes the next character of the line, skipping whitespace.
+    pub fn push_char(&mut self, ch: char) -> Result<(), String> {
+        self.push_char_of(&STANDARD_PAIRS, ch)
+    }
+
+    /// Same as [`push_char`](Checker::push_char), with the brackets of
+    /// `alphabet`.
+    pub fn push_char_with(
+        &mut self,
+        ch: char,
+        alphabet: &Alphabet,
+    ) -> Result<(), String> {
+        self.push_char_of(&alphabet.pairs, ch)
+    }
+
+    fn push_char_of(
+        &mut self,
+        pairs: &[(char, char)],
+        ch: char,
+    ) -> Result<(), String> {
+        if !ch.is_whitespace() {
+            self.push(find_bracket(pairs, ch)?);
+        }
+        Ok(())
+    }
+
+    /// Whether the line is valid, incomplete or corrupt.
+    pub fn finish(self) -> LineStatus {
+        if let Some(corruption) = self.corruption {
+            return LineStatus::Corrupt {
+                position: corruption.column,
+                found: corruption.found,
+                expected: corruption.expected,
             };
-            score = 5 * score + value;
         }
+        if self.open.is_empty() {
+            return LineStatus::Valid;
+        }
+        LineStatus::Incomplete {
+            completion: self
+                .open
+                .iter()
+                .rev()
+                .filter_map(|&pair| self.opening(pair).closing())
+                .collect(),
+        }
+    }
+}
+
+fn classify(brackets: &[Bracket]) -> LineStatus {
+    let mut checker = Checker::new();
+    for &bracket in brackets {
+        checker.push(bracket);
+        if checker.is_corrupt() {
+            break;
+        }
+    }
+    checker.finish()
+}
 
-        Some(score)
+/// Checks a line from its characters as they come, without collecting its
+/// brackets first.
+pub fn check_chars<I>(chars: I) -> Result<LineStatus, String>
+where
+    I: IntoIterator<Item = char>,
+{
+    check_chars_with(chars, &Alphabet::default())
+}
+
+/// Same as [`check_chars`], with the brackets of `alphabet`.
+pub fn check_chars_with<I>(
+    chars: I,
+    alphabet: &Alphabet,
+) -> Result<LineStatus, String>
+where
+    I: IntoIterator<Item = char>,
+{
+    let mut checker = Checker::new();
+    for ch in chars {
+        checker.push_char_with(ch, alphabet)?;
+    }
+    Ok(checker.finish())
+}
+
+/// A change to a single bracket of a line, at a column counting from 1.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Edit {
+    /// Adds a bracket before the one at `column`.
+    Insert {
+        column: usize,
+        bracket: Bracket,
+    },
+    Replace {
+        column: usize,
+        bracket: Bracket,
+    },
+    Delete {
+        column: usize,
+    },
+}
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::Insert { column, bracket } => write!(
+                f,
+                "insert {} before column {}",
+                char::from(*bracket),
+                column
+            ),
+            Edit::Replace { column, bracket } => write!(
+                f,
+                "replace column {} with {}",
+                column,
+                char::from(*bracket)
+            ),
+            Edit::Delete { column } => write!(f, "delete column {}", column),
+        }
+    }
+}
+
+/// Edits that stop a line from being corrupt, and the line they make.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Repair {
+    pub edits: Vec<Edit>,
+    pub line: String,
+}
+
+/// Searches for the lines that up to `budget` more edits to `brackets`,
+/// made from index `from` onwards, keep from being corrupt, along with the
+/// fewest edits for each. Edits after where a line is corrupt can't help, so
+/// only earlier ones are tried, from left to right. `shift` is how many
+/// brackets the edits so far have added, less those they removed, to tell
+/// the columns they make them at in the original line.
+fn find_repairs(
+    candidates: &[Bracket],
+    brackets: &mut Vec<Bracket>,
+    from: usize,
+    shift: isize,
+    edits: &mut Vec<Edit>,
+    budget: usize,
+    repairs: &mut HashMap<Vec<Bracket>, Vec<Edit>>,
+) {
+    let corrupt = match classify(brackets) {
+        LineStatus::Corrupt { position, .. } => position - 1,
+        _ => {
+            match repairs.get(brackets) {
+                Some(best) if best.len() <= edits.len() => {}
+                _ => {
+                    repairs.insert(brackets.clone(), edits.clone());
+                }
+            }
+            return;
+        }
+    };
+    if budget == 0 {
+        return;
+    }
+    let column = |index: usize| (index as isize - shift) as usize + 1;
+    for index in from..=corrupt {
+        let removed = brackets.remove(index);
+        edits.push(Edit::Delete {
+            column: column(index),
+        });
+        find_repairs(
+            candidates,
+            brackets,
+            index,
+            shift - 1,
+            edits,
+            budget - 1,
+            repairs,
+        );
+        edits.pop();
+        brackets.insert(index, removed);
+
+        for &bracket in candidates {
+            let replaced = brackets[index];
+            if bracket != replaced {
+                brackets[index] = bracket;
+                edits.push(Edit::Replace {
+                    column: column(index),
+                    bracket,
+                });
+                let next = index + 1;
+                find_repairs(
+                    candidates,
+                    brackets,
+                    next,
+                    shift,
+                    edits,
+                    budget - 1,
+                    repairs,
+                );
+                edits.pop();
+                brackets[index] = replaced;
+            }
+
+            brackets.insert(index, bracket);
+            edits.push(Edit::Insert {
+                column: column(index),
+                bracket,
+            });
+            let next = index + 1;
+            find_repairs(
+                candidates,
+                brackets,
+                next,
+                shift + 1,
+                edits,
+                budget - 1,
+                repairs,
+            );
+            edits.pop();
+            brackets.remove(index);
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Line(Vec<Bracket>);
+
+impl Line {
+    /// Parses a line made of the brackets of `alphabet`.
+    pub fn parse_with(s: &str, alphabet: &Alphabet) -> Result<Self, String> {
+        s.trim()
+            .chars()
+            .map(|ch| alphabet.bracket(ch))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Line)
+    }
+
+    /// Goes through the brackets once to tell whether this line is valid,
+    /// incomplete or corrupt.
+    pub fn classify(&self) -> LineStatus {
+        classify(&self.0)
+    }
+
+    /// The closing brackets, in order, that complete this line, or else
+    /// where it's corrupt.
+    pub fn check(&self) -> Result<Vec<Bracket>, Corruption> {
+        match self.classify() {
+            LineStatus::Valid => Ok(Vec::new()),
+            LineStatus::Incomplete { completion } => Ok(completion),
+            LineStatus::Corrupt {
+                position,
+                found,
+                expected,
+            } => Err(Corruption {
+                column: position,
+                found,
+                expected,
+            }),
+        }
+    }
+
+    fn score_corrupt(&self, scoring: &Scoring) -> Option<u64> {
+        self.classify().corrupt_score(scoring)
+    }
+
+    /// The closing brackets, in order, that complete this line, unless it's
+    /// corrupt.
+    pub fn completion(&self) -> Option<Vec<Bracket>> {
+        self.check().ok()
+    }
+
+    /// Same as [`completion`](Line::completion), as the characters to
+    /// append to the line.
+    pub fn completion_string(&self) -> Option<String> {
+        self.completion()
+            .map(|brackets| brackets.into_iter().map(char::from).collect())
+    }
+
+    /// The ways of making at most `max_edits` edits so that this line isn't
+    /// corrupt any more, fewest edits first. Repairs that make more edits
+    /// than needed for the same line, or that include all the edits of
+    /// another repair, are left out.
+    pub fn repairs(&self, max_edits: usize) -> Vec<Repair> {
+        self.repairs_with(max_edits, &Alphabet::default())
+    }
+
+    /// Same as [`repairs`](Line::repairs), inserting and replacing with the
+    /// brackets of `alphabet`, which should be the one the line is made of.
+    pub fn repairs_with(
+        &self,
+        max_edits: usize,
+        alphabet: &Alphabet,
+    ) -> Vec<Repair> {
+        let candidates = alphabet.brackets().collect::<Vec<_>>();
+        let mut found = HashMap::new();
+        let mut brackets = self.0.clone();
+        let mut edits = Vec::new();
+        find_repairs(
+            &candidates,
+            &mut brackets,
+            0,
+            0,
+            &mut edits,
+            max_edits,
+            &mut found,
+        );
+
+        let mut repairs = found
+            .into_iter()
+            .filter(|(_, edits)| !edits.is_empty())
+            .map(|(brackets, edits)| Repair {
+                edits,
+                line: brackets.into_iter().map(char::from).collect(),
+            })
+            .collect::<Vec<_>>();
+        repairs.sort_unstable_by(|r1, r2| {
+            (r1.edits.len(), &r1.edits).cmp(&(r2.edits.len(), &r2.edits))
+        });
+        let mut minimal: Vec<Repair> = Vec::new();
+        for repair in repairs {
+            let includes = |other: &Repair| {
+                other.edits.iter().all(|edit| repair.edits.contains(edit))
+            };
+            if !minimal.iter().any(includes) {
+                minimal.push(repair);
+            }
+        }
+        minimal
     }
 }
 
 pub fn part1(lines: &[Line]) -> u64 {
-    lines.iter().filter_map(|line| line.score_corrupt()).sum()
+    part1_with(lines, &Scoring::default())
+}
+
+pub fn part1_with(lines: &[Line], scoring: &Scoring) -> u64 {
+    lines
+        .iter()
+        .filter_map(|line| line.score_corrupt(scoring))
+        .sum()
 }
 
 pub fn part2(lines: &[Line]) -> Option<u64> {
+    part2_with(lines, &Scoring::default())
+}
+
+/// Same as [`part2`], or `None` if a line's completion score doesn't fit in a
+/// `u64`.
+pub fn part2_with(lines: &[Line], scoring: &Scoring) -> Option<u64> {
     let mut incomplete = lines
         .iter()
-        .filter_map(|line| line.score_incomplete())
-        .collect::<Vec<_>>();
+        .map(Line::classify)
+        .filter(|status| !status.is_corrupt())
+        .map(|status| status.completion_score(scoring))
+        .collect::<Option<Vec<_>>>()?;
     incomplete.sort_unstable();
     incomplete.get(incomplete.len() / 2).copied()
 }
 
+/// Solves both parts in a single pass, so that the lines can come straight
+/// from [`parse_reader`]. Only the score of each incomplete line is kept.
+pub fn solve_stream<I, E>(lines: I) -> Result<(u64, Option<u64>), E>
+where
+    I: IntoIterator<Item = Result<Line, E>>,
+{
+    solve_stream_with(lines, &Scoring::default())
+}
+
+/// Same as [`solve_stream`], scoring lines as `scoring` says.
+pub fn solve_stream_with<I, E>(
+    lines: I,
+    scoring: &Scoring,
+) -> Result<(u64, Option<u64>), E>
+where
+    I: IntoIterator<Item = Result<Line, E>>,
+{
+    let statuses = lines.into_iter().map(|line| line.map(|l| l.classify()));
+    score_statuses(statuses, scoring)
+}
+
+fn score_statuses<I, E>(
+    statuses: I,
+    scoring: &Scoring,
+) -> Result<(u64, Option<u64>), E>
+where
+    I: IntoIterator<Item = Result<LineStatus, E>>,
+{
+    let mut corrupt = 0;
+    let mut incomplete = Some(Vec::new());
+    for status in statuses {
+        let status = status?;
+        if let Some(score) = status.corrupt_score(scoring) {
+            corrupt += score;
+        } else if let Some(scores) = &mut incomplete {
+            // No middle score once one of them is too large to know
+            match status.completion_score(scoring) {
+                Some(score) => scores.push(score),
+                None => incomplete = None,
+            }
+        }
+    }
+    let middle = incomplete.and_then(|mut scores| {
+        scores.sort_unstable();
+        scores.get(scores.len() / 2).copied()
+    });
+    Ok((corrupt, middle))
+}
+
+/// Same as [`solve_stream_with`], checking the lines, made of the brackets of
+/// `alphabet`, straight from the bytes of `reader`, so that not even a whole
+/// line is kept.
+pub fn solve_reader_with<R: BufRead>(
+    reader: R,
+    alphabet: &Alphabet,
+    scoring: &Scoring,
+) -> Result<(u64, Option<u64>), String> {
+    score_statuses(check_reader_with(reader, alphabet), scoring)
+}
+
+/// One of the brackets from the puzzle description.
 impl TryFrom<char> for Bracket {
     type Error = String;
 
     fn try_from(ch: char) -> Result<Self, Self::Error> {
-        match ch {
-            '(' => Ok(RoundOpen),
-            ')' => Ok(RoundClose),
-            '[' => Ok(SquareOpen),
-            ']' => Ok(SquareClose),
-            '{' => Ok(CurlyOpen),
-            '}' => Ok(CurlyClose),
-            '<' => Ok(AngleOpen),
-            '>' => Ok(AngleClose),
-            _ => Err(format!("Invalid bracket '{}'", ch)),
+        find_bracket(&STANDARD_PAIRS, ch)
+    }
+}
+
+impl From<Bracket> for char {
+    fn from(bracket: Bracket) -> Self {
+        if bracket.closes {
+            bracket.close
+        } else {
+            bracket.open
         }
     }
 }
@@ -127,3 +797,380 @@ impl FromStr for Line {
             .map(Line)
     }
 }
+
+pub fn parse_input(input: &str) -> Result<Vec<Line>, String> {
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect()
+}
+
+/// Same as [`parse_input`], with lines made of the brackets of `alphabet`.
+pub fn parse_input_with(
+    input: &str,
+    alphabet: &Alphabet,
+) -> Result<Vec<Line>, String> {
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            Line::parse_with(line, alphabet)
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect()
+}
+
+/// Parses one line of brackets per line from `reader` as it's needed.
+pub fn parse_reader<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<Line, String>> {
+    common::parse_lines(reader, str::parse)
+}
+
+/// Same as [`parse_reader`], with lines made of the brackets of `alphabet`.
+pub fn parse_reader_with<R: BufRead>(
+    reader: R,
+    alphabet: &Alphabet,
+) -> impl Iterator<Item = Result<Line, String>> {
+    let alphabet = alphabet.clone();
+    common::parse_lines(reader, move |line| Line::parse_with(line, &alphabet))
+}
+
+/// Checks each line from `reader` as it's read, a buffer at a time, so that
+/// however long lines are, only the chunks still open are kept.
+pub fn check_reader<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<LineStatus, String>> {
+    check_reader_with(reader, &Alphabet::default())
+}
+
+/// Same as [`check_reader`], with lines made of the brackets of `alphabet`.
+pub fn check_reader_with<R: BufRead>(
+    mut reader: R,
+    alphabet: &Alphabet,
+) -> impl Iterator<Item = Result<LineStatus, String>> {
+    let alphabet = alphabet.clone();
+    let mut line_num = 0;
+    std::iter::from_fn(move || {
+        line_num += 1;
+        check_line(&mut reader, &alphabet)
+            .map_err(|err| format!("Line {}: {}", line_num, err))
+            .transpose()
+    })
+}
+
+/// Checks the next line from `reader`, unless there are no more. The line is
+/// read to the end even if it's invalid, so that the next one can be checked.
+fn check_line<R: BufRead>(
+    reader: &mut R,
+    alphabet: &Alphabet,
+) -> Result<Option<LineStatus>, String> {
+    let mut checker = Checker::new();
+    // The bytes so far of a character that takes more than one
+    let mut partial = Vec::new();
+    let mut invalid = None;
+    let mut empty = true;
+    loop {
+        let buffer = reader.fill_buf().map_err(|err| err.to_string())?;
+        if buffer.is_empty() {
+            if empty {
+                return Ok(None);
+            }
+            break;
+        }
+        empty = false;
+        let newline = buffer.iter().position(|&byte| byte == b'\n');
+        for &byte in &buffer[..newline.unwrap_or(buffer.len())] {
+            if invalid.is_some() {
+                break;
+            }
+            let pushed = match decode(&mut partial, byte) {
+                Ok(Some(ch)) => checker.push_char_with(ch, alphabet),
+                Ok(None) => Ok(()),
+                Err(err) => Err(err),
+            };
+            invalid = pushed.err();
+        }
+        let used = newline.map_or(buffer.len(), |newline| newline + 1);
+        reader.consume(used);
+        if newline.is_some() {
+            break;
+        }
+    }
+    if invalid.is_none() && !partial.is_empty() {
+        invalid = Some("Invalid UTF-8 at the end of the line".to_string());
+    }
+    match invalid {
+        Some(err) => Err(err),
+        None => Ok(Some(checker.finish())),
+    }
+}
+
+/// Adds `byte` to those of the character being read, and returns the
+/// character once they're all there.
+fn decode(partial: &mut Vec<u8>, byte: u8) -> Result<Option<char>, String> {
+    if partial.is_empty() && byte.is_ascii() {
+        return Ok(Some(char::from(byte)));
+    }
+    partial.push(byte);
+    match std::str::from_utf8(partial) {
+        Ok(decoded) => {
+            let ch = decoded.chars().next();
+            partial.clear();
+            Ok(ch)
+        }
+        // Not all of its bytes yet
+        Err(err) if err.error_len().is_none() => Ok(None),
+        Err(_) => Err(format!("Invalid UTF-8 byte {:#04x}", byte)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bracket(ch: char) -> Bracket {
+        Bracket::try_from(ch).unwrap()
+    }
+
+    #[test]
+    fn completes_lines() {
+        let line: Line = "[({(<(())[]>[[{[]{<()<>>".parse().unwrap();
+        assert_eq!(line.completion_string(), Some("}}]])})]".to_string()));
+        assert_eq!(
+            line.classify().completion_score(&Scoring::default()),
+            Some(288957)
+        );
+        let line: Line = "<{([".parse().unwrap();
+        assert_eq!(
+            line.completion(),
+            Some(vec![bracket(']'), bracket(')'), bracket('}'), bracket('>')])
+        );
+        let line: Line = "{([(<{}[<>[]}>{[]{[(<()>".parse().unwrap();
+        assert_eq!(line.completion(), None);
+        let line: Line = "[]".parse().unwrap();
+        assert_eq!(line.completion_string(), Some(String::new()));
+    }
+
+    #[test]
+    fn finds_corruption() {
+        let line: Line = "{([(<{}[<>[]}>{[]{[(<()>".parse().unwrap();
+        let corruption = line.check().unwrap_err();
+        assert_eq!(
+            corruption,
+            Corruption {
+                column: 13,
+                found: bracket('}'),
+                expected: Some(bracket(']')),
+            }
+        );
+        assert_eq!(
+            corruption.to_string(),
+            "Expected ] at column 13, but found } instead"
+        );
+        let line: Line = "()>".parse().unwrap();
+        assert_eq!(line.check().unwrap_err().expected, None);
+        assert_eq!(line.score_corrupt(&Scoring::default()), Some(25137));
+    }
+
+    #[test]
+    fn classifies_lines() {
+        let status = |line: &str| line.parse::<Line>().unwrap().classify();
+        assert_eq!(status("[<>({}){}[([])<>]]"), LineStatus::Valid);
+        assert_eq!(
+            status("[({"),
+            LineStatus::Incomplete {
+                completion: vec![bracket('}'), bracket(')'), bracket(']')]
+            }
+        );
+        assert_eq!(
+            status("[(])"),
+            LineStatus::Corrupt {
+                position: 3,
+                found: bracket(']'),
+                expected: Some(bracket(')')),
+            }
+        );
+        assert_eq!(status("[(])").completion_score(&Scoring::default()), None);
+        assert_eq!(status("").completion_score(&Scoring::default()), Some(0));
+
+        let deep = "<".repeat(40);
+        assert_eq!(status(&deep).completion_score(&Scoring::default()), None);
+        let lines = parse_input(&format!("{}\n<", deep)).unwrap();
+        assert_eq!(part2(&lines), None);
+        let stream = solve_reader_with(
+            deep.as_bytes(),
+            &Alphabet::default(),
+            &Scoring::default(),
+        );
+        assert_eq!(stream, Ok((0, None)));
+    }
+
+    #[test]
+    fn checks_streams() {
+        let lines = parse_input(EXAMPLE).unwrap();
+        let expected = lines.iter().map(Line::classify).map(Ok);
+        // A small buffer so that lines don't fit in it
+        let reader = std::io::BufReader::with_capacity(4, EXAMPLE.as_bytes());
+        let statuses = check_reader(reader).collect::<Vec<_>>();
+        assert_eq!(statuses, expected.collect::<Vec<_>>());
+        assert_eq!(
+            solve_reader_with(
+                EXAMPLE.as_bytes(),
+                &Alphabet::default(),
+                &Scoring::default()
+            ),
+            Ok((26397, Some(288957)))
+        );
+
+        let statuses = check_reader("()\r\n(x]\n\n[>".as_bytes());
+        assert_eq!(
+            statuses.collect::<Vec<_>>(),
+            vec![
+                Ok(LineStatus::Valid),
+                Err("Line 2: Invalid bracket 'x'".to_string()),
+                Ok(LineStatus::Valid),
+                Ok(LineStatus::Corrupt {
+                    position: 2,
+                    found: bracket('>'),
+                    expected: Some(bracket(']')),
+                }),
+            ]
+        );
+        let deep = "(".repeat(1000) + ">";
+        let status = check_chars(deep.chars()).unwrap();
+        assert_eq!(status.corrupt_score(&Scoring::default()), Some(25137));
+        assert!(check_chars("(é)".chars()).is_err());
+    }
+
+    #[test]
+    fn repairs_corrupt_lines() {
+        let line: Line = "[(])".parse().unwrap();
+        let repairs = line.repairs(1);
+        let edits = repairs.iter().map(|r| r.edits[0]).collect::<Vec<_>>();
+        assert_eq!(
+            edits,
+            vec![
+                Edit::Insert {
+                    column: 3,
+                    bracket: bracket('[')
+                },
+                Edit::Replace {
+                    column: 3,
+                    bracket: bracket('(')
+                },
+                Edit::Delete { column: 3 },
+            ]
+        );
+        let lines = repairs.iter().map(|r| r.line.as_str()).collect::<Vec<_>>();
+        assert_eq!(lines, vec!["[([])", "[(()", "[()"]);
+        assert_eq!(edits[1].to_string(), "replace column 3 with (");
+
+        let repairs = line.repairs(2);
+        assert_eq!(repairs.len(), 53);
+        assert_eq!(&repairs[..3], &line.repairs(1)[..]);
+        assert_eq!(
+            repairs[3].edits,
+            vec![
+                Edit::Insert {
+                    column: 1,
+                    bracket: bracket('(')
+                },
+                Edit::Insert {
+                    column: 3,
+                    bracket: bracket(')')
+                },
+            ]
+        );
+        assert_eq!(repairs[3].line, "([()])");
+
+        let line: Line = "))".parse().unwrap();
+        assert_eq!(line.repairs(0), vec![]);
+        let lines = line.repairs(1).into_iter().map(|r| r.line);
+        assert_eq!(lines.collect::<Vec<_>>(), vec!["()"]);
+        let line: Line = "[()]".parse().unwrap();
+        assert_eq!(line.repairs(2), vec![]);
+    }
+
+    #[test]
+    fn scores_other_ways() {
+        let lines = parse_input(EXAMPLE).unwrap();
+        let scoring = Scoring {
+            corrupt: vec![1, 2, 3, 4],
+            completion: vec![1, 1, 1, 1],
+            multiplier: 1,
+        };
+        // Corrupt at a }, two ), a ] and a >, and completed with 8, 6, 9, 9
+        // and 4 brackets
+        assert_eq!(part1_with(&lines, &scoring), 3 + 1 + 1 + 2 + 4);
+        assert_eq!(part2_with(&lines, &scoring), Some(8));
+        let stream =
+            solve_stream_with(lines.into_iter().map(Ok::<_, ()>), &scoring);
+        assert_eq!(stream, Ok((11, Some(8))));
+        assert_eq!(
+            parse_points("3, 57,1197,25137"),
+            Ok(vec![3, 57, 1197, 25137])
+        );
+        assert!(parse_points("1,2,x,4").is_err());
+        let scoring = Scoring {
+            corrupt: vec![1, 2, 3],
+            ..Scoring::default()
+        };
+        assert!(scoring.check(&Alphabet::default()).is_err());
+    }
+
+    #[test]
+    fn checks_other_alphabets() {
+        let alphabet: Alphabet = "()«»\"\"".parse().unwrap();
+        assert_eq!(alphabet.brackets().count(), 5);
+        let status =
+            |line| Line::parse_with(line, &alphabet).unwrap().classify();
+        assert_eq!(status("«\"(\"\")\"»"), LineStatus::Valid);
+        let quote = alphabet.bracket('"').unwrap();
+        assert_eq!(
+            status("(«\"»"),
+            LineStatus::Corrupt {
+                position: 4,
+                found: alphabet.bracket('»').unwrap(),
+                expected: Some(quote),
+            }
+        );
+        let line = Line::parse_with("\"«", &alphabet).unwrap();
+        assert_eq!(line.completion_string(), Some("»\"".to_string()));
+        assert!(Line::parse_with("([)", &alphabet).is_err());
+        let line = Line::parse_with("(»", &alphabet).unwrap();
+        assert_eq!(line.repairs_with(1, &alphabet)[0].line, "(«»");
+
+        // A buffer too small for the bytes of « and »
+        let input = "«»\n\"\"«\n";
+        let reader = std::io::BufReader::with_capacity(1, input.as_bytes());
+        let statuses = check_reader_with(reader, &alphabet);
+        assert_eq!(
+            statuses.collect::<Vec<_>>(),
+            vec![
+                Ok(LineStatus::Valid),
+                Ok(LineStatus::Incomplete {
+                    completion: vec![alphabet.bracket('»').unwrap()]
+                }),
+            ]
+        );
+        let scoring = Scoring {
+            corrupt: vec![1, 2, 3],
+            completion: vec![1, 2, 3],
+            multiplier: 4,
+        };
+        assert_eq!(scoring.check(&alphabet), Ok(()));
+        assert!(Scoring::default().check(&alphabet).is_err());
+        let solved = solve_reader_with(input.as_bytes(), &alphabet, &scoring);
+        assert_eq!(solved, Ok((0, Some(2))));
+
+        assert!("()(".parse::<Alphabet>().is_err());
+        assert!("()(]".parse::<Alphabet>().is_err());
+        assert!("( )".parse::<Alphabet>().is_err());
+        assert!("".parse::<Alphabet>().is_err());
+    }
+}
@@ -1,7 +1,9 @@
 use clap::{crate_description, App, Arg};
-use day10::{part1, part2, Line};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use common::{open_or_example, Pretty, COLOR_CHOICES};
+use day10::{
+    parse_points, parse_reader_with, solve_reader_with, Alphabet, Scoring,
+    EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
@@ -9,38 +11,217 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("complete")
+                .long("complete")
+                .help("Prints the brackets that complete each incomplete line"),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .help("Prints where each corrupt line goes wrong"),
+        )
+        .arg(
+            Arg::with_name("repair")
+                .long("repair")
+                .takes_value(true)
+                .value_name("EDITS")
+                .validator(|value| match value.parse::<usize>() {
+                    Ok(edits) if edits > 0 => Ok(()),
+                    _ => Err("must be a positive number".to_string()),
+                })
+                .help(
+                    "Prints the fewest edits, up to EDITS, that stop each \
+                    corrupt line from being corrupt",
+                ),
+        )
+        .arg(
+            Arg::with_name("brackets")
+                .long("brackets")
+                .takes_value(true)
+                .value_name("PAIRS")
+                .validator(|value| value.parse::<Alphabet>().map(|_| ()))
+                .help(
+                    "Pairs of opening and closing brackets lines are made \
+                    of, as in ()[]{}<>",
+                ),
+        )
+        .arg(
+            Arg::with_name("corrupt-points")
+                .long("corrupt-points")
+                .takes_value(true)
+                .value_name("POINTS")
+                .validator(|value| parse_points(&value).map(|_| ()))
+                .help(
+                    "Points for each pair of brackets where lines are \
+                    corrupt, separated by commas",
+                ),
+        )
+        .arg(
+            Arg::with_name("completion-points")
+                .long("completion-points")
+                .takes_value(true)
+                .value_name("POINTS")
+                .validator(|value| parse_points(&value).map(|_| ()))
+                .help(
+                    "Points for each pair's brackets that complete lines, \
+                    separated by commas",
+                ),
+        )
+        .arg(
+            Arg::with_name("multiplier")
+                .long("multiplier")
+                .takes_value(true)
+                .validator(|value| {
+                    value
+                        .parse::<u64>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .help(
+                    "Multiplies completion scores before adding each bracket",
+                ),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
-        Ok(data) => data,
+    // Already checked by the arguments' validators
+    let alphabet = match args.value_of("brackets") {
+        Some(pairs) => pairs.parse().unwrap(),
+        None => Alphabet::default(),
+    };
+    let mut scoring = Scoring::default();
+    if let Some(points) = args.value_of("corrupt-points") {
+        scoring.corrupt = parse_points(points).unwrap();
+    }
+    if let Some(points) = args.value_of("completion-points") {
+        scoring.completion = parse_points(points).unwrap();
+    }
+    if let Some(multiplier) = args.value_of("multiplier") {
+        scoring.multiplier = multiplier.parse().unwrap();
+    }
+    if let Err(err) = scoring.check(&alphabet) {
+        println!("{}", pretty.error(err));
+        exit(1);
+    }
+
+    let reports = Reports {
+        completions: args.is_present("complete"),
+        corruptions: args.is_present("check"),
+        // Already checked by the argument's validator
+        repair_edits: args.value_of("repair").map(|n| n.parse().unwrap()),
+    };
+    if reports.any() {
+        if let Err(err) = reports.print(args.value_of("INPUT"), &alphabet) {
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
+            exit(2);
+        }
+    }
+
+    let input = args.value_of("INPUT");
+    let (part1, part2) = match solve(input, &alphabet, &scoring) {
+        Ok(answers) => answers,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
-    match part2(&input) {
-        Some(answer) => println!("Part 2: {}", &answer),
+    println!("Part 1: {}", pretty.answer(part1));
+    match part2 {
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
         None => println!("Part 2: Not found"),
     }
 }
 
-fn read_input(filename: &str) -> Result<Vec<Line>, String> {
-    let input_file = File::open(filename).map_err(|err| err.to_string())?;
-
-    BufReader::new(input_file)
-        .lines()
-        .zip(1..)
-        .map(|(line, line_num)| {
-            line.map_err(|err| (line_num, err.to_string()))
-                .and_then(|ln| ln.parse().map_err(|err| (line_num, err)))
-        })
-        .collect::<Result<_, _>>()
-        .map_err(|(line_num, err)| format!("Line {}: {}", line_num, err))
+fn solve(
+    filename: Option<&str>,
+    alphabet: &Alphabet,
+    scoring: &Scoring,
+) -> Result<(u64, Option<u64>), String> {
+    solve_reader_with(open_or_example(filename, EXAMPLE)?, alphabet, scoring)
+}
+
+/// What to print about each line.
+struct Reports {
+    completions: bool,
+    corruptions: bool,
+    repair_edits: Option<usize>,
+}
+
+impl Reports {
+    fn any(&self) -> bool {
+        self.completions || self.corruptions || self.repair_edits.is_some()
+    }
+
+    fn print(
+        &self,
+        filename: Option<&str>,
+        alphabet: &Alphabet,
+    ) -> Result<(), String> {
+        let reader = open_or_example(filename, EXAMPLE)?;
+        for (line, line_num) in parse_reader_with(reader, alphabet).zip(1..) {
+            let line = line?;
+            match line.check() {
+                Ok(completion)
+                    if self.completions && !completion.is_empty() =>
+                {
+                    let completion: String =
+                        completion.into_iter().map(char::from).collect();
+                    println!("Line {}: {}", line_num, completion);
+                }
+                Err(corruption) if self.corruptions => {
+                    println!("Line {}: {}", line_num, corruption);
+                }
+                _ => {}
+            }
+            let repairs = match self.repair_edits {
+                Some(max_edits) => line.repairs_with(max_edits, alphabet),
+                None => continue,
+            };
+            let fewest = repairs.first().map(|repair| repair.edits.len());
+            for repair in repairs {
+                if Some(repair.edits.len()) != fewest {
+                    break;
+                }
+                let edits = repair
+                    .edits
+                    .iter()
+                    .map(|edit| edit.to_string())
+                    .collect::<Vec<_>>();
+                println!(
+                    "Line {}: {} to get {}",
+                    line_num,
+                    edits.join(", "),
+                    repair.line
+                );
+            }
+        }
+        Ok(())
+    }
 }
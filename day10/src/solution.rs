@@ -0,0 +1,15 @@
+use crate::{parse_input, part1, part2};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let lines = parse_input(input)?;
+    report.found(part1(&lines));
+    report.maybe(part2(&lines));
+    Ok(())
+}
+
+register_solution!(day: 10, solve: solve);
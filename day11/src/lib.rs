@@ -1,112 +1,655 @@
-use std::collections::HashMap;
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{parse_digit_grid, Animate, Connectivity, Grid, Visualize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::iter::{from_fn, once};
 use std::str::FromStr;
 
-#[derive(Clone, Copy, Eq, Hash, PartialEq)]
-pub struct Coord {
-    x: i8,
-    y: i8,
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct EnergyMap {
+    map: Grid<u8>,
 }
 
-impl Coord {
-    fn new(x: i8, y: i8) -> Self {
-        Self { x, y }
+impl EnergyMap {
+    /// A `width` by `height` map with the energy level of each octopus, row
+    /// by row from the top left.
+    pub fn new(
+        width: usize,
+        height: usize,
+        energies: &[u8],
+    ) -> Result<Self, String> {
+        if energies.len() != width * height {
+            return Err(format!(
+                "Expected {} energy levels for a {}x{} map, found {}",
+                width * height,
+                width,
+                height,
+                energies.len()
+            ));
+        }
+        Self::from_grid(Grid::from_fn(width, height, |x, y| {
+            energies[y * width + x]
+        }))
+    }
+
+    fn from_grid(map: Grid<u8>) -> Result<Self, String> {
+        if let Some(energy) = map.values().find(|&&energy| energy > 9) {
+            return Err(format!("Invalid energy level {}", energy));
+        }
+        Ok(EnergyMap { map })
+    }
+
+    pub fn width(&self) -> usize {
+        self.map.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.map.height()
+    }
+
+    /// The energy level of the octopus at column `x` and row `y`.
+    pub fn energy(&self, x: usize, y: usize) -> Option<u8> {
+        self.map.get(x, y).copied()
+    }
+
+    /// How many octopuses there are.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Steps through the simulation from this map, with how many octopuses
+    /// flash in each step.
+    pub fn simulation(&self) -> Simulation {
+        self.simulation_with(Topology::default())
+    }
+
+    /// Same as [`simulation`](EnergyMap::simulation), with octopuses
+    /// arranged as `topology` says.
+    pub fn simulation_with(&self, topology: Topology) -> Simulation {
+        Simulation {
+            adjacency: Adjacency::new(&self.map, topology),
+            map: self.clone(),
+            flashed: Vec::new(),
+        }
+    }
+
+    fn all_flashed(&self) -> bool {
+        self.map.values().all(|energy| *energy == 0)
+    }
+
+    /// This map followed by the one after every step until every octopus
+    /// flashes at once.
+    fn steps(&self) -> impl Iterator<Item = Self> {
+        let mut simulation = self.simulation();
+        let mut done = self.all_flashed();
+        once(self.clone()).chain(from_fn(move || {
+            if done {
+                return None;
+            }
+            simulation.step();
+            done = simulation.map.all_flashed();
+            Some(simulation.map.clone())
+        }))
     }
+}
 
-    fn adjacent(&self) -> impl Iterator<Item = Self> + '_ {
-        (-1..=1).flat_map(move |dx| {
-            (-1..=1).filter_map(move |dy| {
-                if dx != 0 || dy != 0 {
-                    Some(Coord::new(self.x + dx, self.y + dy))
+/// How octopuses are arranged, which tells which ones are adjacent.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Topology {
+    /// On a grid, adjacent to the eight around them, diagonals included.
+    #[default]
+    Square,
+    /// Same as on a grid, but with the edges wrapping around, so that the
+    /// octopuses on opposite edges are adjacent.
+    Toroidal,
+    /// On a grid of hexagons, adjacent to the six around them, where odd rows
+    /// are shifted right by half an octopus.
+    Hexagonal,
+}
+
+impl Topology {
+    /// The coordinates of the octopuses adjacent to the one at `(x, y)` on
+    /// `map`, excluding itself.
+    fn neighbors(
+        &self,
+        map: &Grid<u8>,
+        x: usize,
+        y: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut neighbors = match self {
+            Topology::Square => {
+                map.neighbors(x, y, Connectivity::Eight).collect::<Vec<_>>()
+            }
+            Topology::Toroidal => {
+                map.wrapping_neighbors(x, y, Connectivity::Eight).collect()
+            }
+            Topology::Hexagonal => {
+                let offsets = if y % 2 == 1 {
+                    HEX_ODD_ROW_OFFSETS
                 } else {
-                    None
-                }
-            })
-        })
+                    HEX_EVEN_ROW_OFFSETS
+                };
+                offsets
+                    .iter()
+                    .map(|(dx, dy)| (x as i64 + dx, y as i64 + dy))
+                    .filter(|&(nx, ny)| map.contains(nx, ny))
+                    .map(|(nx, ny)| (nx as usize, ny as usize))
+                    .collect()
+            }
+        };
+        // Wrapping around small maps can reach the same octopus more than
+        // once, or itself
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors.retain(|&neighbor| neighbor != (x, y));
+        neighbors
+    }
+}
+
+const HEX_EVEN_ROW_OFFSETS: [(i64, i64); 6] =
+    [(-1, 0), (1, 0), (-1, -1), (0, -1), (-1, 1), (0, 1)];
+const HEX_ODD_ROW_OFFSETS: [(i64, i64); 6] =
+    [(-1, 0), (1, 0), (0, -1), (1, -1), (0, 1), (1, 1)];
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "square" => Ok(Topology::Square),
+            "toroidal" => Ok(Topology::Toroidal),
+            "hexagonal" => Ok(Topology::Hexagonal),
+            _ => Err(format!("Invalid topology '{}'", s)),
+        }
     }
 }
 
+/// The indices of the octopuses adjacent to each one, by its index in the
+/// map, worked out once rather than on every flash.
 #[derive(Clone)]
-pub struct EnergyMap {
-    map: HashMap<Coord, u8>,
-    total_flashes: u32,
+struct Adjacency {
+    /// Where the neighbours of each octopus start in `neighbors`, and then
+    /// where the last ones end.
+    starts: Vec<usize>,
+    neighbors: Vec<usize>,
 }
 
-impl EnergyMap {
-    fn update(&mut self) {
-        let mut flashed = Vec::new();
+impl Adjacency {
+    fn new(map: &Grid<u8>, topology: Topology) -> Self {
+        let mut starts = vec![0];
+        let mut neighbors = Vec::new();
+        for (x, y) in map.coords() {
+            neighbors.extend(
+                topology
+                    .neighbors(map, x, y)
+                    .into_iter()
+                    .filter_map(|(x, y)| map.index_of(x, y)),
+            );
+            starts.push(neighbors.len());
+        }
+        Self { starts, neighbors }
+    }
+
+    fn of(&self, index: usize) -> &[usize] {
+        &self.neighbors[self.starts[index]..self.starts[index + 1]]
+    }
+}
 
-        for (coord, energy) in self.map.iter_mut() {
+/// The steps of the simulation, one after the other, yielding how many
+/// octopuses flash in each. It never ends.
+#[derive(Clone)]
+pub struct Simulation {
+    map: EnergyMap,
+    adjacency: Adjacency,
+    /// The octopuses that flashed but haven't raised their neighbours' energy
+    /// yet, kept between steps to reuse its memory.
+    flashed: Vec<usize>,
+}
+
+impl Simulation {
+    /// Makes a step, returning how many octopuses flashed.
+    fn step(&mut self) -> u32 {
+        self.step_with(|_| {})
+    }
+
+    /// Same as [`step`](Simulation::step), calling `on_flash` with the index
+    /// of each octopus as it flashes.
+    fn step_with<F: FnMut(usize)>(&mut self, mut on_flash: F) -> u32 {
+        let energies = self.map.map.as_mut_slice();
+        for (index, energy) in energies.iter_mut().enumerate() {
             *energy += 1;
             if *energy == 10 {
                 *energy = 0;
-                flashed.push(*coord);
+                self.flashed.push(index);
             }
         }
 
-        while let Some(coord) = flashed.pop() {
-            self.total_flashes += 1;
-            for adjacent in coord.adjacent() {
-                if let Some(energy) = self.map.get_mut(&adjacent) {
-                    if *energy > 0 {
-                        *energy += 1;
-                        if *energy == 10 {
-                            *energy = 0;
-                            flashed.push(adjacent);
-                        }
+        let mut flashes = 0;
+        while let Some(index) = self.flashed.pop() {
+            flashes += 1;
+            on_flash(index);
+            for &adjacent in self.adjacency.of(index) {
+                let energy = &mut energies[adjacent];
+                if *energy > 0 {
+                    *energy += 1;
+                    if *energy == 10 {
+                        *energy = 0;
+                        self.flashed.push(adjacent);
                     }
                 }
             }
         }
+        flashes
     }
 
-    fn total_flashes(&self) -> u32 {
-        self.total_flashes
+    /// The map after the steps so far.
+    pub fn energy_map(&self) -> &EnergyMap {
+        &self.map
     }
 
-    fn all_flashed(&self) -> bool {
-        self.map.values().all(|energy| *energy == 0)
+    /// Makes the next `steps` steps, recording which octopuses flash in each.
+    pub fn record(&mut self, steps: u32) -> FlashHistory {
+        let mut history = FlashHistory {
+            width: self.map.width(),
+            height: self.map.height(),
+            starts: vec![0],
+            flashes: Vec::new(),
+        };
+        let width = history.width;
+        for _ in 0..steps {
+            let flashes = &mut history.flashes;
+            self.step_with(|index| {
+                flashes.push((index % width, index / width))
+            });
+            history.starts.push(flashes.len());
+        }
+        history
+    }
+
+    /// Same as the simulation, along with the map after each step.
+    pub fn with_snapshots(mut self) -> impl Iterator<Item = (u32, EnergyMap)> {
+        std::iter::from_fn(move || {
+            let flashes = self.next()?;
+            Some((flashes, self.map.clone()))
+        })
+    }
+}
+
+/// Which octopuses flashed in each of a number of steps.
+#[derive(Clone, Debug)]
+pub struct FlashHistory {
+    width: usize,
+    height: usize,
+    /// Where the flashes of each step start in `flashes`, and then where
+    /// those of the last step end.
+    starts: Vec<usize>,
+    flashes: Vec<(usize, usize)>,
+}
+
+impl FlashHistory {
+    /// How many steps were recorded.
+    pub fn steps(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    /// The coordinates of the octopuses that flashed during `step`, counting
+    /// from 1, in the order they flashed. None did in steps that weren't
+    /// recorded.
+    pub fn flashes_at(&self, step: usize) -> &[(usize, usize)] {
+        if step == 0 || step > self.steps() {
+            return &[];
+        }
+        &self.flashes[self.starts[step - 1]..self.starts[step]]
+    }
+
+    /// How many times each octopus flashed.
+    pub fn heatmap(&self) -> Grid<u32> {
+        let mut heatmap = Grid::new(self.width, self.height, 0);
+        for &coords in &self.flashes {
+            heatmap[coords] += 1;
+        }
+        heatmap
+    }
+}
+
+impl Iterator for Simulation {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.step())
+    }
+}
+
+/// The energy level of each octopus, highlighting the ones that just flashed
+/// as `*`.
+impl Display for EnergyMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let drawing = self.map.map(|&energy| match energy {
+            0 => '*',
+            _ => char::from(b'0' + energy),
+        });
+        write!(f, "{}", drawing)
+    }
+}
+
+impl Visualize for EnergyMap {
+    /// Steps until every octopus flashes at once.
+    fn frames(&self) -> impl Iterator<Item = String> + '_ {
+        self.steps().map(|map| map.to_string())
+    }
+}
+
+impl Animate for EnergyMap {
+    /// From dark blue at no energy to light blue just before flashing, and
+    /// yellow for the ones that just flashed.
+    const PALETTE: &'static [[u8; 3]] = &[
+        [255, 230, 80],
+        [10, 20, 60],
+        [20, 35, 85],
+        [30, 50, 110],
+        [40, 65, 135],
+        [50, 80, 160],
+        [60, 95, 180],
+        [70, 110, 200],
+        [85, 130, 215],
+        [100, 150, 230],
+    ];
+
+    fn images(&self) -> impl Iterator<Item = Grid<u8>> + '_ {
+        self.steps().map(|map| map.map)
     }
 }
 
+/// How many steps part 1 counts the flashes of.
+pub const PART1_STEPS: u32 = 100;
+
 pub fn part1(start_map: &EnergyMap) -> u32 {
-    let mut map = start_map.clone();
-    for _step in 1..=100 {
-        map.update();
+    simulate(start_map, PART1_STEPS)
+}
+
+/// Total number of flashes during the given number of steps.
+pub fn simulate(start_map: &EnergyMap, steps: u32) -> u32 {
+    simulate_with(start_map, steps, Topology::default())
+}
+
+/// Same as [`simulate`], with octopuses arranged as `topology` says.
+pub fn simulate_with(
+    start_map: &EnergyMap,
+    steps: u32,
+    topology: Topology,
+) -> u32 {
+    start_map
+        .simulation_with(topology)
+        .take(steps as usize)
+        .sum()
+}
+
+pub fn part2(start_map: &EnergyMap) -> Option<u32> {
+    part2_with(start_map, Topology::default())
+}
+
+/// The first step when every octopus flashes, with octopuses arranged as
+/// `topology` says, unless they never all flash at once. That's known once
+/// their energy levels are back to what they were before an earlier step.
+pub fn part2_with(start_map: &EnergyMap, topology: Topology) -> Option<u32> {
+    let octopuses = start_map.len() as u32;
+    let mut seen = HashSet::new();
+    let mut simulation = start_map.simulation_with(topology);
+    let mut step = 0;
+    while seen.insert(simulation.map.map.clone()) {
+        step += 1;
+        if simulation.step() == octopuses {
+            return Some(step);
+        }
     }
-    map.total_flashes()
+    None
+}
+
+/// Where the energy levels of a simulation start repeating.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cycle {
+    /// How many steps there are before the levels are first the same as
+    /// after some later step.
+    pub start: u64,
+    /// How many steps it takes for the levels to come back.
+    pub length: u64,
 }
 
-pub fn part2(start_map: &EnergyMap) -> u32 {
-    let mut map = start_map.clone();
-    for step in 1.. {
-        map.update();
-        if map.all_flashed() {
-            return step;
+/// Runs up to `max_steps` steps, stopping early if the energy levels repeat.
+/// Returns the total flashes after each step so far, starting with none
+/// before the first, and the cycle if one was found.
+fn run_until_cycle(
+    start_map: &EnergyMap,
+    topology: Topology,
+    max_steps: u64,
+) -> (Vec<u128>, Option<Cycle>) {
+    let mut seen = HashMap::new();
+    let mut totals = vec![0];
+    let mut simulation = start_map.simulation_with(topology);
+    for step in 0..max_steps {
+        if let Some(start) = seen.insert(simulation.map.map.clone(), step) {
+            let length = step - start;
+            return (totals, Some(Cycle { start, length }));
         }
+        let total = totals[totals.len() - 1] + simulation.step() as u128;
+        totals.push(total);
     }
-    unreachable!()
+    (totals, None)
+}
+
+/// When the energy levels of the octopuses, arranged as `topology` says,
+/// start repeating, which they eventually do.
+pub fn find_cycle(start_map: &EnergyMap, topology: Topology) -> Cycle {
+    // There are only so many energy levels the octopuses can have
+    run_until_cycle(start_map, topology, u64::MAX).1.unwrap()
+}
+
+/// Total number of flashes during any number of steps, with octopuses
+/// arranged as `topology` says. Only the steps until the energy levels start
+/// repeating are simulated, as the flashes repeat along with them.
+pub fn fast_forward(
+    start_map: &EnergyMap,
+    steps: u64,
+    topology: Topology,
+) -> u128 {
+    let (totals, cycle) = run_until_cycle(start_map, topology, steps);
+    let Cycle { start, length } = match cycle {
+        Some(cycle) => cycle,
+        None => return totals[totals.len() - 1],
+    };
+    let before = totals[start as usize];
+    let per_cycle = totals[(start + length) as usize] - before;
+    let cycles = (steps - start) / length;
+    let rest = (steps - start) % length;
+    before
+        + cycles as u128 * per_cycle
+        + (totals[(start + rest) as usize] - before)
 }
 
 impl FromStr for EnergyMap {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.trim()
-            .lines()
-            .zip(0..)
-            .flat_map(|(line, y)| {
-                line.trim().chars().zip(0..).map(move |(ch, x)| {
-                    ch.to_digit(10)
-                        .ok_or_else(|| format!("Invalid energy level '{}'", ch))
-                        .map(|num| {
-                            (Coord::new(x, y), u8::try_from(num).unwrap())
-                        })
-                })
-            })
-            .collect::<Result<HashMap<_, _>, _>>()
-            .map(|map| EnergyMap {
-                map,
-                total_flashes: 0,
-            })
+        parse_digit_grid(s).and_then(EnergyMap::from_grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulates_any_size() {
+        let map: EnergyMap = EXAMPLE.parse().unwrap();
+        assert_eq!((map.width(), map.height()), (10, 10));
+        assert_eq!(simulate(&map, 10), 204);
+        assert_eq!(simulate(&map, PART1_STEPS), 1656);
+
+        // The smaller example from the puzzle description
+        let map = EnergyMap::new(
+            5,
+            5,
+            &[
+                1, 1, 1, 1, 1, 1, 9, 9, 9, 1, 1, 9, 1, 9, 1, 1, 9, 9, 9, 1, 1,
+                1, 1, 1, 1,
+            ],
+        )
+        .unwrap();
+        assert_eq!(map.energy(1, 1), Some(9));
+        assert_eq!(simulate(&map, 1), 9);
+        assert_eq!(simulate(&map, 2), 9);
+        let map: EnergyMap = "1234\n5678".parse().unwrap();
+        assert_eq!((map.width(), map.height()), (4, 2));
+        assert_eq!(map.energy(3, 1), Some(8));
+        assert_eq!(map.energy(4, 1), None);
+
+        assert!(EnergyMap::new(2, 2, &[1, 2, 3]).is_err());
+        assert!(EnergyMap::new(1, 1, &[10]).is_err());
+    }
+
+    #[test]
+    fn records_flashes() {
+        let map: EnergyMap =
+            "11111\n19991\n19191\n19991\n11111".parse().unwrap();
+        let history = map.simulation().record(2);
+        assert_eq!(history.steps(), 2);
+        let mut flashed = history.flashes_at(1).to_vec();
+        flashed.sort_unstable_by_key(|&(x, y)| (y, x));
+        assert_eq!(
+            flashed,
+            vec![
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (1, 2),
+                (2, 2),
+                (3, 2),
+                (1, 3),
+                (2, 3),
+                (3, 3),
+            ]
+        );
+        assert_eq!(history.flashes_at(2), &[]);
+        assert_eq!(history.flashes_at(3), &[]);
+        assert_eq!(history.heatmap()[(2, 2)], 1);
+
+        let map: EnergyMap = EXAMPLE.parse().unwrap();
+        let mut simulation = map.simulation();
+        let history = simulation.record(PART1_STEPS);
+        assert_eq!(history.flashes_at(2).len(), 35);
+        assert_eq!(history.heatmap().values().sum::<u32>(), 1656);
+        // Carries on from where the recording stopped
+        assert_eq!(simulation.record(95).flashes_at(95).len(), 100);
+    }
+
+    #[test]
+    fn supports_other_topologies() {
+        let map: EnergyMap = EXAMPLE.parse().unwrap();
+        assert_eq!(simulate_with(&map, 100, Topology::Square), 1656);
+        assert_eq!(simulate_with(&map, 100, Topology::Toroidal), 1234);
+        assert_eq!(simulate_with(&map, 100, Topology::Hexagonal), 1447);
+        assert_eq!(part2_with(&map, Topology::Toroidal), Some(29));
+        assert_eq!(part2_with(&map, Topology::Hexagonal), None);
+
+        let map: EnergyMap = "000\n000\n000".parse().unwrap();
+        let adjacency = |topology| {
+            let adjacency = Adjacency::new(&map.map, topology);
+            (0..map.len())
+                .map(|i| adjacency.of(i).len())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(adjacency(Topology::Square), [3, 5, 3, 5, 8, 5, 3, 5, 3]);
+        assert_eq!(adjacency(Topology::Toroidal), [8; 9]);
+        assert_eq!(adjacency(Topology::Hexagonal), [2, 4, 3, 5, 6, 3, 2, 4, 3]);
+        // Wrapping around a single row of two reaches the other one only
+        let map: EnergyMap = "00".parse().unwrap();
+        let adjacency = Adjacency::new(&map.map, Topology::Toroidal);
+        assert_eq!(adjacency.of(0), [1]);
+        assert_eq!("Hexagonal".parse(), Ok(Topology::Hexagonal));
+        assert!("round".parse::<Topology>().is_err());
+    }
+
+    #[test]
+    fn fast_forwards() {
+        let map: EnergyMap = EXAMPLE.parse().unwrap();
+        // Every step after they first all flash is the same
+        let cycle = find_cycle(&map, Topology::Square);
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 195,
+                length: 10
+            }
+        );
+        for steps in [0, 10, 100, 194, 195, 196, 205, 213, 400] {
+            assert_eq!(
+                fast_forward(&map, steps, Topology::Square),
+                simulate(&map, steps as u32) as u128,
+                "{} steps",
+                steps
+            );
+        }
+        let hexagonal = find_cycle(&map, Topology::Hexagonal);
+        assert_eq!(
+            hexagonal,
+            Cycle {
+                start: 341,
+                length: 8
+            }
+        );
+        assert_eq!(
+            fast_forward(&map, 1000, Topology::Hexagonal),
+            simulate_with(&map, 1000, Topology::Hexagonal) as u128,
+        );
+        let after = fast_forward(&map, u64::MAX, Topology::Square);
+        // Then they all flash again every ten steps
+        let synchronized = simulate(&map, 195) as u128;
+        let cycles = (u64::MAX - 195) / 10;
+        assert_eq!(after, synchronized + 100 * cycles as u128);
+    }
+
+    #[test]
+    fn steps_through_simulation() {
+        let map: EnergyMap = EXAMPLE.parse().unwrap();
+        let flashes = map.simulation().take(3).collect::<Vec<_>>();
+        assert_eq!(flashes, vec![0, 35, 45]);
+        let mut simulation = map.simulation();
+        assert_eq!(simulation.nth(194), Some(100));
+        assert_eq!(simulation.energy_map().energy(0, 0), Some(0));
+
+        let (flashes, after) =
+            map.simulation().with_snapshots().nth(1).unwrap();
+        assert_eq!(flashes, 35);
+        let expected: EnergyMap = "8807476555
+5089087054
+8597889608
+8485769600
+8700908800
+6600088989
+6800005943
+0000007456
+9000000876
+8700006848"
+            .parse()
+            .unwrap();
+        assert_eq!(after.map, expected.map);
+        assert_eq!(after.to_string().lines().next(), Some("88*7476555"));
+        let frames = map.frames().collect::<Vec<_>>();
+        assert_eq!(frames.len(), 196);
+        assert_eq!(frames[2], after.to_string());
+        assert!(frames[195].chars().all(|ch| ch == '*' || ch == '\n'));
     }
 }
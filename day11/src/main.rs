@@ -1,34 +1,180 @@
 use clap::{crate_description, App, Arg};
-use day11::{part1, part2, EnergyMap};
-use std::fs::read_to_string;
+use common::{
+    read_or_example, Animate, Grid, Pretty, Visualize, COLOR_CHOICES,
+};
+use day11::{
+    fast_forward, find_cycle, part2_with, simulate_with, EnergyMap, Topology,
+    EXAMPLE, PART1_STEPS,
+};
+use std::path::Path;
 use std::process::exit;
+use std::time::Duration;
 
 fn main() {
+    let default_steps = PART1_STEPS.to_string();
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("steps")
+                .long("steps")
+                .takes_value(true)
+                .default_value(&default_steps)
+                .validator(|value| {
+                    value
+                        .parse::<u32>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .help("Steps to count the flashes of in part 1"),
+        )
+        .arg(
+            Arg::with_name("topology")
+                .long("topology")
+                .takes_value(true)
+                .possible_values(&["square", "toroidal", "hexagonal"])
+                .default_value("square")
+                .help("How octopuses are arranged, for the answers"),
+        )
+        .arg(
+            Arg::with_name("fast-forward")
+                .long("fast-forward")
+                .takes_value(true)
+                .value_name("STEPS")
+                .validator(|value| {
+                    value
+                        .parse::<u64>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .help("Also counts the flashes in up to 2^64 - 1 STEPS"),
+        )
+        .arg(
+            Arg::with_name("heatmap")
+                .long("heatmap")
+                .help("Prints how often each octopus flashes in part 1"),
+        )
+        .arg(
+            Arg::with_name("visualize")
+                .long("visualize")
+                .help("Animates the puzzle on the terminal before solving it"),
+        )
+        .arg(
+            Arg::with_name("delay")
+                .long("delay")
+                .takes_value(true)
+                .default_value("100")
+                .validator(|value| {
+                    value
+                        .parse::<u64>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .help("Milliseconds between animation frames"),
+        )
+        .arg(
+            Arg::with_name("export")
+                .long("export")
+                .takes_value(true)
+                .value_name("PATH")
+                .help(
+                    "Writes every step to PATH as an animated GIF if it ends \
+                    in .gif, or else as PNG frames in directory PATH",
+                ),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    if args.is_present("visualize") {
+        let delay = args.value_of("delay").unwrap().parse().unwrap();
+        if let Err(err) = input.visualize(Duration::from_millis(delay)) {
+            println!(
+                "{}",
+                pretty.error(format!("Failed to visualize: {}", err))
+            );
+        }
+    }
+
+    if let Some(path) = args.value_of("export") {
+        let delay = args.value_of("delay").unwrap().parse().unwrap();
+        if let Err(err) =
+            input.export(Path::new(path), Duration::from_millis(delay))
+        {
+            println!("{}", pretty.error(format!("Failed to export: {}", err)));
+        }
+    }
+
+    // Already checked by the arguments' validators and possible values
+    let steps = args.value_of("steps").unwrap().parse().unwrap();
+    let topology: Topology =
+        args.value_of("topology").unwrap().parse().unwrap();
+    if args.is_present("heatmap") {
+        let mut simulation = input.simulation_with(topology);
+        print_heatmap(&simulation.record(steps).heatmap());
+    }
+
+    if let Some(horizon) = args.value_of("fast-forward") {
+        let cycle = find_cycle(&input, topology);
+        println!(
+            "Energy levels repeat every {} steps after step {}",
+            cycle.length, cycle.start
+        );
+        let horizon = horizon.parse().unwrap();
+        let flashes = fast_forward(&input, horizon, topology);
+        println!("Flashes in {} steps: {}", horizon, pretty.answer(flashes));
+    }
+
+    let part1 = simulate_with(&input, steps, topology);
+    println!("Part 1: {}", pretty.answer(part1));
+    match part2_with(&input, topology) {
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
+        None => println!("Part 2: Not found"),
+    }
+}
+
+fn print_heatmap(heatmap: &Grid<u32>) {
+    let max = heatmap.values().max().copied().unwrap_or(0);
+    let width = max.to_string().len();
+    for row in heatmap.rows() {
+        let counts = row
+            .iter()
+            .map(|count| format!("{:>1$}", count, width))
+            .collect::<Vec<_>>();
+        println!("{}", counts.join(" "));
+    }
 }
 
-fn read_input(filename: &str) -> Result<EnergyMap, String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())?
-        .parse()
+fn read_input(filename: Option<&str>) -> Result<EnergyMap, String> {
+    read_or_example(filename, EXAMPLE)?.parse()
 }
@@ -0,0 +1,19 @@
+use crate::{part2, simulate, EnergyMap};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let energy_map: EnergyMap = input.parse()?;
+    report.found(simulate(&energy_map, params.get("part1_steps")?));
+    report.maybe(part2(&energy_map));
+    Ok(())
+}
+
+register_solution!(
+    day: 11,
+    params: &[("part1_steps", 100)],
+    solve: solve,
+);
@@ -1,6 +1,17 @@
-use std::collections::{HashMap, HashSet};
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{SearchStats, UnionFind};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample1");
+
 type CaveName = String;
 
 const START_CAVE: &str = "start";
@@ -10,121 +21,663 @@ fn is_small(cave: &str) -> bool {
     cave.chars().all(|ch| ch.is_lowercase())
 }
 
-pub struct CaveSystem(HashMap<CaveName, Vec<CaveName>>);
+/// The most caves there can be, so that a set of them fits in a `u64`.
+const MAX_CAVES: usize = 64;
+
+/// Caves by their index in the order they first appear in the input, which
+/// is what paths refer to them by.
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct CaveSystem {
+    names: Vec<CaveName>,
+    /// The caves connected to each one, with how long the connection is,
+    /// except the start cave, which paths never go back to.
+    connections: Vec<Vec<(usize, u64)>>,
+    /// A bit for each small cave.
+    small: u64,
+    start: usize,
+    end: usize,
+}
 
 impl CaveSystem {
-    fn get_connections(&self, cave: &str) -> Option<&Vec<CaveName>> {
-        self.0.get(cave)
+    /// Looks for problems with the caves, which are fine if there are none.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut components = self.components();
+        let mut diagnostics = Vec::new();
+        if !components.connected(self.start, self.end) {
+            diagnostics.push(Diagnostic::UnreachableEnd);
+        }
+        for (cave, connections) in self.connections.iter().enumerate() {
+            let name = || self.names[cave].clone();
+            if !components.connected(self.start, cave) {
+                diagnostics.push(Diagnostic::Disconnected(name()));
+            }
+            if connections.iter().any(|&(other, _)| other == cave) {
+                diagnostics.push(Diagnostic::SelfLoop(name()));
+            }
+            if self.is_small(cave) {
+                continue;
+            }
+            let mut big: Vec<_> = connections
+                .iter()
+                .map(|&(other, _)| other)
+                .filter(|&other| other > cave && !self.is_small(other))
+                .collect();
+            big.sort_unstable();
+            big.dedup();
+            for other in big {
+                diagnostics.push(Diagnostic::AdjacentBigCaves(
+                    name(),
+                    self.names[other].clone(),
+                ));
+            }
+        }
+        diagnostics
     }
 
-    fn count_all_paths(&self, allow_small_reentrance: bool) -> Option<i32> {
-        let start = Path::new(START_CAVE, self.get_connections(START_CAVE)?);
-        let mut stack = vec![start];
-        let mut count = 0;
+    fn components(&self) -> UnionFind {
+        let mut components = UnionFind::new(self.names.len());
+        for (cave, connections) in self.connections.iter().enumerate() {
+            for &(other, _) in connections {
+                components.union(cave, other);
+            }
+        }
+        components
+    }
 
-        while let Some(mut path) = stack.pop() {
-            if let Some(current) = path.next_cave(allow_small_reentrance) {
-                stack.push(path);
+    /// Whether paths can go back and forth forever between connected big
+    /// caves that they can go through any number of times.
+    fn has_endless_paths(&self, limits: &[usize]) -> bool {
+        let mut components = self.components();
+        let is_endless =
+            |cave: usize| !self.is_small(cave) && limits[cave] == usize::MAX;
+        self.connections
+            .iter()
+            .enumerate()
+            .any(|(cave, connections)| {
+                is_endless(cave)
+                    && components.connected(self.start, cave)
+                    && connections.iter().any(|&(other, _)| is_endless(other))
+            })
+    }
 
-                if current == END_CAVE {
-                    count += 1;
-                    continue;
-                }
+    fn is_small(&self, cave: usize) -> bool {
+        self.small & (1 << cave) != 0
+    }
 
-                if let Some(connections) = self.get_connections(&current) {
-                    let next_path =
-                        stack.last().unwrap().next_path(current, connections);
-                    stack.push(next_path);
-                }
-            }
+    /// Goes through every path from the start cave to the end cave that
+    /// `policy` allows, calling `on_path` with its caves and length, unless
+    /// there are endless ones.
+    fn explore_paths<F>(
+        &self,
+        policy: &RevisitPolicy,
+        stats: &mut SearchStats,
+        mut on_path: F,
+    ) -> Option<()>
+    where
+        F: FnMut(&[usize], u64),
+    {
+        let limits = policy.limits(self);
+        if self.has_endless_paths(&limits) {
+            return None;
         }
+        let mut search = Search {
+            caves: self,
+            limits,
+            small_revisits: policy.small_revisits,
+            visits: vec![0; self.names.len()],
+            path: vec![self.start],
+            stats,
+        };
+        search.visits[self.start] = 1;
+        search.explore(self.start, 1 << self.start, 0, 0, &mut on_path);
+        Some(())
+    }
 
+    fn count_all_paths(
+        &self,
+        policy: &RevisitPolicy,
+        stats: &mut SearchStats,
+    ) -> Option<i32> {
+        let mut count = 0;
+        self.explore_paths(policy, stats, |_, _| count += 1)?;
         Some(count)
     }
+
+    /// The path that `is_better` than any other, unless there's none.
+    fn best_path<F>(
+        &self,
+        policy: &RevisitPolicy,
+        stats: &mut SearchStats,
+        is_better: F,
+    ) -> Option<WeightedPath>
+    where
+        F: Fn(u64, u64) -> bool,
+    {
+        let mut best: Option<(Vec<usize>, u64)> = None;
+        self.explore_paths(policy, stats, |path, length| {
+            if best
+                .as_ref()
+                .is_none_or(|&(_, best)| is_better(length, best))
+            {
+                best = Some((path.to_vec(), length));
+            }
+        })?;
+        best.map(|(path, length)| WeightedPath {
+            caves: path.iter().map(|&cave| self.names[cave].clone()).collect(),
+            length,
+        })
+    }
 }
 
-#[derive(Debug)]
-struct Path {
-    visited: HashSet<CaveName>,
-    current: CaveName,
-    connections: Vec<CaveName>,
-    small_reentered: bool,
+/// A problem with a cave system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// There's no way from the start cave to the end cave.
+    UnreachableEnd,
+    /// A cave there's no way to from the start cave.
+    Disconnected(CaveName),
+    /// A cave connected to itself.
+    SelfLoop(CaveName),
+    /// Two connected big caves, which paths can go back and forth between
+    /// forever, unless they can only go through big caves so many times.
+    AdjacentBigCaves(CaveName, CaveName),
 }
 
-impl Path {
-    fn new(start: &str, connections: &[CaveName]) -> Self {
-        Self {
-            visited: HashSet::new(),
-            current: start.to_string(),
-            connections: connections.iter().map(|s| s.to_string()).collect(),
-            small_reentered: false,
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnreachableEnd => {
+                write!(f, "No connection between start and end caves")
+            }
+            Self::Disconnected(cave) => {
+                write!(f, "No connection between start and {} cave", cave)
+            }
+            Self::SelfLoop(cave) => {
+                write!(f, "Cave {} connects to itself", cave)
+            }
+            Self::AdjacentBigCaves(cave1, cave2) => write!(
+                f,
+                "Big caves {} and {} connect to each other",
+                cave1, cave2
+            ),
         }
     }
+}
+
+/// A path from the start cave to the end cave, with the sum of the lengths
+/// of the connections it goes through.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedPath {
+    pub caves: Vec<CaveName>,
+    pub length: u64,
+}
+
+/// A search for paths that keeps the one it's on, going back a cave at a
+/// time rather than copying it for every cave it goes to.
+struct Search<'a> {
+    caves: &'a CaveSystem,
+    /// The most times the path can go through each cave.
+    limits: Vec<usize>,
+    small_revisits: usize,
+    /// How many times the path went through each cave.
+    visits: Vec<usize>,
+    /// The caves the path went through, in order.
+    path: Vec<usize>,
+    stats: &'a mut SearchStats,
+}
 
-    fn next_cave(&mut self, allow_small_reentrance: bool) -> Option<CaveName> {
-        while let Some(cave) = self.connections.pop() {
-            if self.visited.contains(&cave)
-                && (!allow_small_reentrance || self.small_reentered)
+impl Search<'_> {
+    /// Goes on from `cave` to the end cave in every way there is, having
+    /// gone through the small caves in `visited`, back into some of them
+    /// `revisits` times, and along connections `length` long in all.
+    fn explore<F>(
+        &mut self,
+        cave: usize,
+        visited: u64,
+        revisits: usize,
+        length: u64,
+        on_path: &mut F,
+    ) where
+        F: FnMut(&[usize], u64),
+    {
+        let caves = self.caves;
+        for &(next, weight) in &caves.connections[cave] {
+            let revisit = visited & (1 << next) != 0;
+            if self.visits[next] >= self.limits[next]
+                || revisit && revisits >= self.small_revisits
             {
+                self.stats.duplicates += 1;
+                continue;
+            }
+            self.path.push(next);
+            if next == caves.end {
+                on_path(&self.path, length + weight);
+                self.path.pop();
                 continue;
             }
-            return Some(cave);
-        }
-        None
-    }
 
-    fn next_path(&self, current: CaveName, connections: &[CaveName]) -> Self {
-        let mut visited = self.visited.clone();
-        if is_small(&self.current) {
-            // Only keep track of visited caves when they're small
-            visited.insert(self.current.clone());
+            self.stats.expanded += 1;
+            self.stats.record_frontier(self.path.len());
+            let visited = if caves.is_small(next) {
+                visited | 1 << next
+            } else {
+                visited
+            };
+            self.stats.record_visited(visited.count_ones() as usize);
+            self.visits[next] += 1;
+            self.explore(
+                next,
+                visited,
+                revisits + usize::from(revisit),
+                length + weight,
+                on_path,
+            );
+            self.visits[next] -= 1;
+            self.path.pop();
         }
+    }
+}
 
-        let connections = connections
-            .iter()
-            .filter(|c| c.as_str() != START_CAVE)
-            .map(|c| c.to_string())
-            .collect();
-
-        let small_reentered =
-            self.small_reentered || visited.contains(&current);
+/// How many times paths can go through caves. Without any revisits, as in
+/// part 1, small caves can be visited once and big caves any number of times.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RevisitPolicy {
+    /// How many times in total a path can go back into small caves it's
+    /// already been through, 1 in part 2. The start cave can't be revisited.
+    pub small_revisits: usize,
+    /// The most times a path can go through each of these caves, on top of
+    /// the other limits.
+    pub cave_limits: HashMap<CaveName, usize>,
+    /// The most times a path can go through each big cave, unless there's no
+    /// limit.
+    pub big_visits: Option<usize>,
+}
 
+impl RevisitPolicy {
+    /// The rules of part 2, where a single small cave can be visited twice.
+    pub fn part2() -> Self {
         Self {
-            visited,
-            current,
-            connections,
-            small_reentered,
+            small_revisits: 1,
+            ..Self::default()
         }
     }
+
+    /// The most times a path can go through each cave of `caves`. Going
+    /// back into small caves is limited by the revisits instead.
+    fn limits(&self, caves: &CaveSystem) -> Vec<usize> {
+        caves
+            .names
+            .iter()
+            .enumerate()
+            .map(|(cave, name)| {
+                let limit = self.cave_limits.get(name).copied();
+                let big_limit =
+                    self.big_visits.filter(|_| !caves.is_small(cave));
+                limit
+                    .into_iter()
+                    .chain(big_limit)
+                    .min()
+                    .unwrap_or(usize::MAX)
+            })
+            .collect()
+    }
+}
+
+/// Parses the limit of a cave, as in `b=2`.
+pub fn parse_cave_limit(s: &str) -> Result<(CaveName, usize), String> {
+    s.split_once('=')
+        .and_then(|(cave, limit)| {
+            let limit = limit.trim().parse().ok()?;
+            Some((cave.trim().to_string(), limit))
+        })
+        .ok_or_else(|| format!("Invalid cave limit '{}'", s))
 }
 
 pub fn part1(caves: &CaveSystem) -> Option<i32> {
-    caves.count_all_paths(false)
+    part1_with_stats(caves, &mut SearchStats::default())
 }
 
 pub fn part2(caves: &CaveSystem) -> Option<i32> {
-    caves.count_all_paths(true)
+    part2_with_stats(caves, &mut SearchStats::default())
+}
+
+/// Same as [`part1`], counting partial paths as expanded states and
+/// revisits of small caves as pruned duplicates.
+pub fn part1_with_stats(
+    caves: &CaveSystem,
+    stats: &mut SearchStats,
+) -> Option<i32> {
+    count_paths_with_stats(caves, &RevisitPolicy::default(), stats)
+}
+
+/// Same as [`part2`], counting partial paths as expanded states and
+/// revisits of small caves as pruned duplicates.
+pub fn part2_with_stats(
+    caves: &CaveSystem,
+    stats: &mut SearchStats,
+) -> Option<i32> {
+    count_paths_with_stats(caves, &RevisitPolicy::part2(), stats)
+}
+
+/// How many paths go from the start cave to the end cave, going through
+/// caves as many times as `policy` allows, unless there are endless ones.
+pub fn count_paths(caves: &CaveSystem, policy: &RevisitPolicy) -> Option<i32> {
+    count_paths_with_stats(caves, policy, &mut SearchStats::default())
+}
+
+/// Same as [`count_paths`], counting partial paths as expanded states and
+/// caves that can't be visited again as pruned duplicates.
+pub fn count_paths_with_stats(
+    caves: &CaveSystem,
+    policy: &RevisitPolicy,
+    stats: &mut SearchStats,
+) -> Option<i32> {
+    caves.count_all_paths(policy, stats)
+}
+
+/// The shortest path from the start cave to the end cave that `policy`
+/// allows, counting the lengths of the connections it goes through, unless
+/// there's none.
+pub fn shortest_path(
+    caves: &CaveSystem,
+    policy: &RevisitPolicy,
+) -> Option<WeightedPath> {
+    caves.best_path(policy, &mut SearchStats::default(), |l1, l2| l1 < l2)
+}
+
+/// Same as [`shortest_path`], for the longest path.
+pub fn longest_path(
+    caves: &CaveSystem,
+    policy: &RevisitPolicy,
+) -> Option<WeightedPath> {
+    caves.best_path(policy, &mut SearchStats::default(), |l1, l2| l1 > l2)
+}
+
+/// Figures about all the paths from the start cave to the end cave.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PathStatistics {
+    /// How many paths there are.
+    pub paths: u64,
+    /// How many paths there are of each length.
+    pub lengths: BTreeMap<u64, u64>,
+    /// How many times paths go along each connection, in each direction.
+    pub edges: HashMap<(CaveName, CaveName), u64>,
+    /// How many times paths go through each cave.
+    pub visits: HashMap<CaveName, u64>,
+}
+
+impl PathStatistics {
+    /// The `n` connections paths go along the most, most traversed first.
+    pub fn most_traversed_edges(
+        &self,
+        n: usize,
+    ) -> Vec<(&CaveName, &CaveName, u64)> {
+        let mut edges: Vec<_> = self
+            .edges
+            .iter()
+            .map(|((from, to), &count)| (from, to, count))
+            .collect();
+        edges.sort_unstable_by(|e1, e2| e2.2.cmp(&e1.2).then(e1.cmp(e2)));
+        edges.truncate(n);
+        edges
+    }
+
+    /// The caves paths go through, most visited first.
+    pub fn visit_frequencies(&self) -> Vec<(&CaveName, u64)> {
+        let mut visits: Vec<_> = self
+            .visits
+            .iter()
+            .map(|(cave, &count)| (cave, count))
+            .collect();
+        visits.sort_unstable_by(|v1, v2| v2.1.cmp(&v1.1).then(v1.cmp(v2)));
+        visits
+    }
+}
+
+/// Gathers [`PathStatistics`] about the paths `policy` allows as they're
+/// found, so they never need to be kept, unless there are endless ones.
+pub fn path_statistics(
+    caves: &CaveSystem,
+    policy: &RevisitPolicy,
+) -> Option<PathStatistics> {
+    let mut paths = 0;
+    let mut lengths = BTreeMap::new();
+    let mut edges = HashMap::new();
+    let mut visits = vec![0; caves.names.len()];
+    caves.explore_paths(
+        policy,
+        &mut SearchStats::default(),
+        |path, length| {
+            paths += 1;
+            *lengths.entry(length).or_insert(0) += 1;
+            for pair in path.windows(2) {
+                *edges.entry((pair[0], pair[1])).or_insert(0) += 1;
+            }
+            for &cave in path {
+                visits[cave] += 1;
+            }
+        },
+    )?;
+    let name = |cave: usize| caves.names[cave].clone();
+    Some(PathStatistics {
+        paths,
+        lengths,
+        edges: edges
+            .into_iter()
+            .map(|((from, to), count)| ((name(from), name(to)), count))
+            .collect(),
+        visits: visits
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, count)| count > 0)
+            .map(|(cave, count)| (name(cave), count))
+            .collect(),
+    })
+}
+
+/// Parses a connection between two caves, as in `A-b`, optionally followed
+/// by its length, as in `A-b=7`. Connections are 1 long by default.
+fn parse_connection(s: &str) -> Result<(&str, &str, u64), String> {
+    let (caves, length) = match s.split_once('=') {
+        Some((caves, length)) => {
+            let length = length
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid length in '{}'", s))?;
+            (caves, length)
+        }
+        None => (s, 1),
+    };
+    let (cave1, cave2) = caves
+        .trim()
+        .split_once('-')
+        .ok_or_else(|| format!("Invalid connection '{}'", s))?;
+    Ok((cave1, cave2, length))
 }
 
 impl FromStr for CaveSystem {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut connections = HashMap::new();
+        let mut indices = HashMap::new();
+        let mut names = Vec::new();
+        let mut connections: Vec<Vec<(usize, u64)>> = Vec::new();
+        let mut index_of = |cave: &str| {
+            *indices.entry(cave.to_string()).or_insert_with(|| {
+                names.push(cave.to_string());
+                connections.push(Vec::new());
+                names.len() - 1
+            })
+        };
+        let mut pairs = Vec::new();
         for line in s.trim().lines() {
-            let (cave1, cave2) = line
-                .trim()
-                .split_once('-')
-                .ok_or_else(|| format!("Invalid connection '{}'", line))?;
-            connections
-                .entry(cave1.to_string())
-                .and_modify(|v: &mut Vec<_>| v.push(cave2.to_string()))
-                .or_insert_with(|| vec![cave2.to_string()]);
-            connections
-                .entry(cave2.to_string())
-                .and_modify(|v: &mut Vec<_>| v.push(cave1.to_string()))
-                .or_insert_with(|| vec![cave1.to_string()]);
+            let (cave1, cave2, length) = parse_connection(line.trim())?;
+            pairs.push((index_of(cave1), index_of(cave2), length));
+        }
+        if names.len() > MAX_CAVES {
+            return Err(format!(
+                "Found {} caves, but there can be at most {}",
+                names.len(),
+                MAX_CAVES
+            ));
+        }
+
+        let index_of = |cave: &str| {
+            indices
+                .get(cave)
+                .copied()
+                .ok_or_else(|| format!("Missing '{}' cave", cave))
+        };
+        let (start, end) = (index_of(START_CAVE)?, index_of(END_CAVE)?);
+        for (cave1, cave2, length) in pairs {
+            if cave2 != start {
+                connections[cave1].push((cave2, length));
+            }
+            if cave1 != start {
+                connections[cave2].push((cave1, length));
+            }
+        }
+        let small = names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| is_small(name))
+            .fold(0, |small, (cave, _)| small | 1 << cave);
+
+        let caves = CaveSystem {
+            names,
+            connections,
+            small,
+            start,
+            end,
+        };
+        let unreachable = Diagnostic::UnreachableEnd;
+        if caves.validate().contains(&unreachable) {
+            return Err(unreachable.to_string());
         }
-        Ok(CaveSystem(connections))
+        Ok(caves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_larger_examples() {
+        let caves: CaveSystem = include_str!("../sample2").parse().unwrap();
+        assert_eq!((part1(&caves), part2(&caves)), (Some(19), Some(103)));
+        let caves: CaveSystem = include_str!("../sample3").parse().unwrap();
+        assert_eq!((part1(&caves), part2(&caves)), (Some(226), Some(3509)));
+
+        // 67 caves
+        let chain = (0..64).map(|i| format!("c{}-c{}\n", i, i + 1));
+        let too_many =
+            format!("start-c0\n{}c64-end", chain.collect::<String>());
+        assert!(too_many.parse::<CaveSystem>().is_err());
+    }
+
+    #[test]
+    fn counts_other_rules() {
+        let caves: CaveSystem = EXAMPLE.parse().unwrap();
+        let count = |policy: &RevisitPolicy| count_paths(&caves, policy);
+        assert_eq!(count(&RevisitPolicy::default()), Some(10));
+        assert_eq!(count(&RevisitPolicy::part2()), Some(36));
+        let policy = RevisitPolicy {
+            small_revisits: 2,
+            ..RevisitPolicy::default()
+        };
+        assert_eq!(count(&policy), Some(101));
+        // Only start,A,end and going once or twice into c
+        let policy = RevisitPolicy {
+            cave_limits: HashMap::from([("b".to_string(), 0)]),
+            ..RevisitPolicy::part2()
+        };
+        assert_eq!(count(&policy), Some(3));
+        let policy = RevisitPolicy {
+            big_visits: Some(1),
+            ..RevisitPolicy::part2()
+        };
+        assert_eq!(count(&policy), Some(8));
+
+        assert_eq!(parse_cave_limit("b = 2"), Ok(("b".to_string(), 2)));
+        assert!(parse_cave_limit("b").is_err());
+        assert!(parse_cave_limit("b=-1").is_err());
+    }
+
+    #[test]
+    fn finds_extremal_paths() {
+        let caves: CaveSystem = "start-A=2\nstart-b=5\nA-b=1\nA-end=10\nb-end"
+            .parse()
+            .unwrap();
+        let path = |caves: &[&str], length| WeightedPath {
+            caves: caves.iter().map(|cave| cave.to_string()).collect(),
+            length,
+        };
+        let policy = RevisitPolicy::default();
+        assert_eq!(
+            shortest_path(&caves, &policy),
+            Some(path(&["start", "A", "b", "end"], 4))
+        );
+        assert_eq!(
+            longest_path(&caves, &policy),
+            Some(path(&["start", "b", "A", "end"], 16))
+        );
+        assert_eq!(count_paths(&caves, &policy), Some(5));
+
+        let policy = RevisitPolicy {
+            cave_limits: HashMap::from([("b".to_string(), 0)]),
+            ..RevisitPolicy::default()
+        };
+        assert_eq!(longest_path(&caves, &policy).unwrap().length, 12);
+        let policy = RevisitPolicy {
+            cave_limits: HashMap::from([("end".to_string(), 0)]),
+            ..RevisitPolicy::default()
+        };
+        assert_eq!(shortest_path(&caves, &policy), None);
+        assert!("start-A=x\nA-end".parse::<CaveSystem>().is_err());
+    }
+
+    #[test]
+    fn gathers_path_statistics() {
+        let caves: CaveSystem = EXAMPLE.parse().unwrap();
+        let stats = path_statistics(&caves, &RevisitPolicy::default()).unwrap();
+        assert_eq!(stats.paths, 10);
+        assert_eq!(stats.lengths.values().sum::<u64>(), 10);
+        assert_eq!(stats.lengths.keys().next(), Some(&2));
+        assert_eq!(stats.visits["start"], 10);
+        assert_eq!(stats.visits["end"], 10);
+        assert_eq!(stats.visits.get("d"), None);
+        let edges = stats.most_traversed_edges(1);
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].2, stats.edges.values().copied().max().unwrap());
+        assert_eq!(stats.visit_frequencies()[0].0, "A");
+    }
+
+    #[test]
+    fn validates_caves() {
+        let caves: CaveSystem = EXAMPLE.parse().unwrap();
+        assert_eq!(caves.validate(), []);
+
+        let caves: CaveSystem =
+            "start-A\nA-B\nB-end\nc-c\nD-d".parse().unwrap();
+        assert_eq!(
+            caves.validate(),
+            [
+                Diagnostic::AdjacentBigCaves("A".to_string(), "B".to_string()),
+                Diagnostic::Disconnected("c".to_string()),
+                Diagnostic::SelfLoop("c".to_string()),
+                Diagnostic::Disconnected("D".to_string()),
+                Diagnostic::Disconnected("d".to_string()),
+            ]
+        );
+        assert_eq!(part1(&caves), None);
+        let policy = RevisitPolicy {
+            big_visits: Some(2),
+            ..RevisitPolicy::default()
+        };
+        assert_eq!(count_paths(&caves, &policy), Some(2));
+        assert!(path_statistics(&caves, &RevisitPolicy::part2()).is_none());
+
+        assert!("start-a\nb-end".parse::<CaveSystem>().is_err());
     }
 }
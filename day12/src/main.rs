@@ -1,6 +1,10 @@
 use clap::{crate_description, App, Arg};
-use day12::{part1, part2, CaveSystem};
-use std::fs::read_to_string;
+use common::{read_or_example, Pretty, SearchStats, COLOR_CHOICES};
+use day12::{
+    count_paths_with_stats, longest_path, parse_cave_limit, part1_with_stats,
+    part2_with_stats, path_statistics, shortest_path, CaveSystem,
+    PathStatistics, RevisitPolicy, WeightedPath, EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
@@ -8,33 +12,188 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("stats")
+                .long("stats")
+                .help("Prints search statistics after each answer"),
+        )
+        .arg(
+            Arg::with_name("revisits")
+                .long("revisits")
+                .takes_value(true)
+                .validator(is_number)
+                .help(
+                    "Also counts paths that go back into small caves N times",
+                ),
+        )
+        .arg(
+            Arg::with_name("limit")
+                .long("limit")
+                .takes_value(true)
+                .value_name("CAVE=N")
+                .multiple(true)
+                .number_of_values(1)
+                .validator(|value| parse_cave_limit(&value).map(|_| ()))
+                .help("Also counts paths that go through CAVE up to N times"),
+        )
+        .arg(
+            Arg::with_name("big-visits")
+                .long("big-visits")
+                .takes_value(true)
+                .validator(is_number)
+                .help("Also counts paths that go through big caves N times"),
+        )
+        .arg(
+            Arg::with_name("shortest")
+                .long("shortest")
+                .help("Prints the shortest path the rules allow"),
+        )
+        .arg(
+            Arg::with_name("longest")
+                .long("longest")
+                .help("Prints the longest path the rules allow"),
+        )
+        .arg(
+            Arg::with_name("path-stats")
+                .long("path-stats")
+                .help("Prints figures about the paths the rules allow"),
+        )
+        .arg(
+            Arg::with_name("validate")
+                .long("validate")
+                .help("Prints problems found with the caves"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    match part1(&input) {
-        Some(answer) => println!("Part 1: {}", &answer),
+    if args.is_present("validate") {
+        let diagnostics = input.validate();
+        if diagnostics.is_empty() {
+            println!("No problems found");
+        }
+        for diagnostic in diagnostics {
+            println!("{}", pretty.error(diagnostic));
+        }
+    }
+
+    let mut stats = SearchStats::default();
+    match part1_with_stats(&input, &mut stats) {
+        Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
         None => println!("Part 1: Not found"),
     }
-    match part2(&input) {
-        Some(answer) => println!("Part 2: {}", &answer),
+    if args.is_present("stats") {
+        println!("Part 1 stats: {}", stats);
+    }
+    let mut stats = SearchStats::default();
+    match part2_with_stats(&input, &mut stats) {
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
         None => println!("Part 2: Not found"),
     }
+    if args.is_present("stats") {
+        println!("Part 2 stats: {}", stats);
+    }
+
+    // Already checked by the arguments' validators
+    let mut policy = RevisitPolicy::default();
+    if let Some(revisits) = args.value_of("revisits") {
+        policy.small_revisits = revisits.parse().unwrap();
+    }
+    for limit in args.values_of("limit").into_iter().flatten() {
+        let (cave, limit) = parse_cave_limit(limit).unwrap();
+        policy.cave_limits.insert(cave, limit);
+    }
+    if let Some(visits) = args.value_of("big-visits") {
+        policy.big_visits = Some(visits.parse().unwrap());
+    }
+    if policy != RevisitPolicy::default() {
+        let mut stats = SearchStats::default();
+        match count_paths_with_stats(&input, &policy, &mut stats) {
+            Some(count) => println!("Paths: {}", pretty.answer(count)),
+            None => println!("Paths: Not found"),
+        }
+        if args.is_present("stats") {
+            println!("Paths stats: {}", stats);
+        }
+    }
+    if args.is_present("shortest") {
+        print_path("Shortest", shortest_path(&input, &policy), &pretty);
+    }
+    if args.is_present("longest") {
+        print_path("Longest", longest_path(&input, &policy), &pretty);
+    }
+    if args.is_present("path-stats") {
+        match path_statistics(&input, &policy) {
+            Some(stats) => print_statistics(&stats, &pretty),
+            None => println!("Path statistics: Not found"),
+        }
+    }
+}
+
+fn print_path(label: &str, path: Option<WeightedPath>, pretty: &Pretty) {
+    match path {
+        Some(path) => println!(
+            "{} path: {} (length {})",
+            label,
+            path.caves.join(","),
+            pretty.answer(path.length)
+        ),
+        None => println!("{} path: Not found", label),
+    }
+}
+
+fn print_statistics(stats: &PathStatistics, pretty: &Pretty) {
+    println!("Paths: {}", pretty.answer(stats.paths));
+    println!("Lengths:");
+    for (length, count) in &stats.lengths {
+        println!("  {:>4}: {}", length, count);
+    }
+    println!("Most traversed connections:");
+    for (from, to, count) in stats.most_traversed_edges(5) {
+        println!("  {}-{}: {}", from, to, count);
+    }
+    println!("Visits:");
+    for (cave, count) in stats.visit_frequencies() {
+        println!("  {}: {}", cave, count);
+    }
+}
+
+fn is_number(value: String) -> Result<(), String> {
+    value
+        .parse::<usize>()
+        .map(|_| ())
+        .map_err(|err| err.to_string())
 }
 
-fn read_input(filename: &str) -> Result<CaveSystem, String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())?
-        .parse()
+fn read_input(filename: Option<&str>) -> Result<CaveSystem, String> {
+    read_or_example(filename, EXAMPLE)?.parse()
 }
@@ -0,0 +1,15 @@
+use crate::{part1, part2, CaveSystem};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let caves: CaveSystem = input.parse()?;
+    report.maybe(part1(&caves));
+    report.maybe(part2(&caves));
+    Ok(())
+}
+
+register_solution!(day: 12, solve: solve);
@@ -1,62 +1,577 @@
-use std::collections::HashSet;
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{ocr, save_png_scaled, Grid, Point2, Svg, Visualize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{hash_set, HashSet};
 use std::fmt::{Display, Formatter};
+use std::iter::once;
+use std::path::Path;
 use std::str::FromStr;
 use Fold::*;
 
-#[derive(Clone, Copy, Eq, Hash, PartialEq)]
-pub struct Dot {
-    x: i32,
-    y: i32,
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+pub type Dot = Point2;
+
+/// A line to fold the paper along, taking the dots on one side of it over
+/// to the other.
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Fold {
+    /// Along `x=line`, taking dots right of it to the left.
+    Left(i32),
+    /// Along `y=line`, taking dots below it upwards.
+    Up(i32),
+    /// Along `y=x+offset`, taking dots right of it to the left.
+    Diagonal(i32),
+    /// Along `x+y=sum`, taking dots right of it to the left.
+    AntiDiagonal(i32),
 }
 
-impl Dot {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
+impl Fold {
+    /// How far `dot` is on the side of the line the fold takes across,
+    /// if positive, or on the side it leaves alone, if negative.
+    fn side(&self, dot: &Dot) -> i32 {
+        match self {
+            Left(line) => dot.x - line,
+            Up(line) => dot.y - line,
+            Diagonal(offset) => dot.x - dot.y + offset,
+            AntiDiagonal(sum) => dot.x + dot.y - sum,
+        }
     }
 
-    fn fold(&self, fold: &Fold) -> Self {
-        match fold {
-            Left(line) => {
-                let x = if self.x > *line {
-                    2 * line - self.x
-                } else {
-                    self.x
-                };
-                let y = self.y;
-                Self::new(x, y)
+    fn apply(&self, dot: &Dot) -> Dot {
+        if self.side(dot) > 0 {
+            self.mirror(dot)
+        } else {
+            *dot
+        }
+    }
+
+    fn is_on_line(&self, dot: &Dot) -> bool {
+        self.side(dot) == 0
+    }
+
+    /// Whether the fold leaves `dot` where it is, rather than taking it
+    /// across the line.
+    fn keeps(&self, dot: &Dot) -> bool {
+        self.side(dot) <= 0
+    }
+
+    /// Where `dot` is across the line.
+    fn mirror(&self, dot: &Dot) -> Dot {
+        match self {
+            Left(line) => Dot::new(2 * line - dot.x, dot.y),
+            Up(line) => Dot::new(dot.x, 2 * line - dot.y),
+            Diagonal(offset) => Dot::new(dot.y - offset, dot.x + offset),
+            AntiDiagonal(sum) => Dot::new(sum - dot.y, sum - dot.x),
+        }
+    }
+}
+
+impl Display for Fold {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Left(line) => write!(f, "fold along x={}", line),
+            Up(line) => write!(f, "fold along y={}", line),
+            Diagonal(0) => write!(f, "fold along y=x"),
+            Diagonal(offset) => write!(f, "fold along y=x{:+}", offset),
+            AntiDiagonal(sum) => write!(f, "fold along x+y={}", sum),
+        }
+    }
+}
+
+/// What to do with dots that end up where the puzzle doesn't expect them:
+/// right on a fold line, or past the edge of the paper once folded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FoldPolicy {
+    #[default]
+    Error,
+    /// Leaves such dots out of the folded paper.
+    Drop,
+    /// Leaves dots on fold lines where they are, and moves dots past the
+    /// edge of the paper onto it.
+    Clamp,
+}
+
+impl FromStr for FoldPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "drop" => Ok(Self::Drop),
+            "clamp" => Ok(Self::Clamp),
+            _ => Err(format!("Invalid fold policy '{}'", s)),
+        }
+    }
+}
+
+/// How many bits a dense paper may use per dot, more or less what a hash set
+/// of dots takes, before its dots are kept in a hash set instead.
+const BITS_PER_DOT: usize = 64;
+
+/// A sheet of transparent paper with dots on it, which overlap when it's
+/// folded. The dots are kept as a bit per position when they're close
+/// enough together, or in a hash set otherwise.
+#[derive(Clone, Debug)]
+pub struct Paper(Dots);
+
+#[derive(Clone, Debug)]
+enum Dots {
+    Sparse(HashSet<Dot>),
+    Dense(BitGrid),
+}
+
+impl Paper {
+    pub fn new(dots: &[Dot]) -> Self {
+        Self::collect(dots.iter().copied())
+    }
+
+    /// Keeps `dots` in whichever way takes less memory, going through them
+    /// once to find their bounds and then again to keep them.
+    fn collect<I>(dots: I) -> Self
+    where
+        I: Iterator<Item = Dot> + Clone,
+    {
+        let mut count = 0;
+        let mut bounds: Option<(Dot, Dot)> = None;
+        for dot in dots.clone() {
+            count += 1;
+            bounds = Some(match bounds {
+                Some((min, max)) => (
+                    Dot::new(min.x.min(dot.x), min.y.min(dot.y)),
+                    Dot::new(max.x.max(dot.x), max.y.max(dot.y)),
+                ),
+                None => (dot, dot),
+            });
+        }
+        let dense = bounds.and_then(|(min, max)| {
+            let width =
+                usize::try_from(i64::from(max.x) - i64::from(min.x)).ok()? + 1;
+            let height =
+                usize::try_from(i64::from(max.y) - i64::from(min.y)).ok()? + 1;
+            let area = width.checked_mul(height)?;
+            (area <= count * BITS_PER_DOT).then_some((min, width, height))
+        });
+        match dense {
+            Some((min, width, height)) => {
+                let mut grid = BitGrid::new(min, width, height);
+                for dot in dots {
+                    grid.insert(dot);
+                }
+                Self(Dots::Dense(grid))
             }
-            Up(line) => {
-                let x = self.x;
-                let y = if self.y > *line {
-                    2 * line - self.y
-                } else {
-                    self.y
-                };
-                Self::new(x, y)
+            None => Self(Dots::Sparse(dots.collect())),
+        }
+    }
+
+    /// Folds the paper, leaving dots on the fold line where they are and
+    /// letting dots go past the edge of the paper.
+    pub fn fold(&self, fold: &Fold) -> Self {
+        Self::collect(self.dots().map(|dot| fold.apply(&dot)))
+    }
+
+    /// Folds the paper, handling dots on the fold line or that would go
+    /// past the top or left edge of the paper as `policy` says.
+    pub fn fold_with(
+        &self,
+        fold: &Fold,
+        policy: FoldPolicy,
+    ) -> Result<Self, String> {
+        if policy == FoldPolicy::Error {
+            for dot in self.dots() {
+                if fold.is_on_line(&dot) {
+                    return Err(format!("Dot {} is on {}", dot, fold));
+                }
+                let folded = fold.apply(&dot);
+                if folded.x < 0 || folded.y < 0 {
+                    return Err(format!(
+                        "Dot {} goes past the edge of the paper to {} on {}",
+                        dot, folded, fold
+                    ));
+                }
             }
         }
+        let dots = self.dots().filter_map(|dot| {
+            if fold.is_on_line(&dot) {
+                return (policy == FoldPolicy::Clamp).then_some(dot);
+            }
+            let folded = fold.apply(&dot);
+            if folded.x >= 0 && folded.y >= 0 {
+                Some(folded)
+            } else {
+                (policy == FoldPolicy::Clamp)
+                    .then(|| Dot::new(folded.x.max(0), folded.y.max(0)))
+            }
+        });
+        Ok(Self::collect(dots))
+    }
+
+    /// Undoes `fold` by putting every dot back on both sides of the line,
+    /// which gives the paper with the most dots that folds into this one.
+    pub fn unfold(&self, fold: &Fold) -> Self {
+        Self::collect(self.dots().flat_map(|dot| [dot, fold.mirror(&dot)]))
+    }
+
+    /// Every paper that `fold` folds into this one: each dot off the line
+    /// may have been where it is, across the line or both. There are none
+    /// if there are dots past the line already.
+    pub fn preimages(&self, fold: &Fold) -> Preimages {
+        let mut fixed = Vec::new();
+        let mut dots = Vec::new();
+        for dot in self.dots() {
+            if fold.is_on_line(&dot) {
+                fixed.push(dot);
+            } else {
+                dots.push((dot, fold.mirror(&dot)));
+            }
+        }
+        let valid = self.dots().all(|dot| fold.keeps(&dot));
+        Preimages {
+            choices: valid.then(|| vec![0; dots.len()]),
+            fixed,
+            dots,
+        }
+    }
+
+    pub fn count_dots(&self) -> usize {
+        match &self.0 {
+            Dots::Sparse(dots) => dots.len(),
+            Dots::Dense(grid) => grid.len(),
+        }
+    }
+
+    pub fn contains(&self, dot: &Dot) -> bool {
+        match &self.0 {
+            Dots::Sparse(dots) => dots.contains(dot),
+            Dots::Dense(grid) => grid.contains(dot),
+        }
+    }
+
+    /// The dots on the paper, in no particular order.
+    pub fn dots(&self) -> DotIter<'_> {
+        match &self.0 {
+            Dots::Sparse(dots) => DotIter(DotsIter::Sparse(dots.iter())),
+            Dots::Dense(grid) => DotIter(DotsIter::Dense(grid.iter())),
+        }
+    }
+
+    /// Whether the dots are kept as a bit per position.
+    pub fn is_dense(&self) -> bool {
+        matches!(self.0, Dots::Dense(_))
+    }
+
+    /// The top left and bottom right corners of the smallest rectangle
+    /// with every dot in it.
+    pub fn bounds(&self) -> (Dot, Dot) {
+        if let Dots::Dense(grid) = &self.0 {
+            return grid.bounds();
+        }
+        let min_x = self.dots().map(|dot| dot.x).min().unwrap_or(0);
+        let max_x = self.dots().map(|dot| dot.x).max().unwrap_or(0);
+        let min_y = self.dots().map(|dot| dot.y).min().unwrap_or(0);
+        let max_y = self.dots().map(|dot| dot.y).max().unwrap_or(0);
+        (Dot::new(min_x, min_y), Dot::new(max_x, max_y))
+    }
+
+    /// How many columns there are from the leftmost to the rightmost dot.
+    pub fn width(&self) -> usize {
+        let (min, max) = self.bounds();
+        (max.x - min.x + 1) as usize
+    }
+
+    /// How many rows there are from the topmost to the bottommost dot.
+    pub fn height(&self) -> usize {
+        let (min, max) = self.bounds();
+        (max.y - min.y + 1) as usize
+    }
+
+    /// The paper within its [`bounds`](Self::bounds), with `true` wherever
+    /// there's a dot.
+    pub fn grid(&self) -> Grid<bool> {
+        let (min, _) = self.bounds();
+        Grid::from_fn(self.width(), self.height(), |x, y| {
+            self.contains(&Dot::new(min.x + x as i32, min.y + y as i32))
+        })
+    }
+
+    /// Draws the paper within its [`bounds`](Self::bounds) and a blank
+    /// margin, each dot a square `scale` pixels wide.
+    pub fn render(&self, scale: usize) -> Svg {
+        let (min, _) = self.bounds();
+        let (width, height) = (self.width() as i64, self.height() as i64);
+        let scale = scale.max(1) as i64;
+        let mut svg = Svg::new(
+            i64::from(min.x) - 1,
+            i64::from(min.y) - 1,
+            width + 2,
+            height + 2,
+        )
+        .size((width + 2) * scale, (height + 2) * scale)
+        .background("white");
+        for dot in self.dots() {
+            svg.rect(dot.into(), 1, 1, "black");
+        }
+        svg
+    }
+
+    /// Writes a PNG image of the paper within its [`bounds`](Self::bounds),
+    /// each dot a square `scale` pixels wide.
+    pub fn save_png(&self, path: &Path, scale: usize) -> Result<(), String> {
+        let image = self.grid().map(|&dot| u8::from(dot));
+        save_png_scaled(image, &[[255, 255, 255], [0, 0, 0]], scale, path)
+    }
+
+    /// The letters drawn by the dots, unless they can't be read. Letters
+    /// are read from the top left corner of the paper rather than from the
+    /// first dot, since some start with a blank column.
+    pub fn read(&self) -> Option<String> {
+        let (min, max) = self.bounds();
+        let origin = Dot::new(min.x.min(0), min.y.min(0));
+        let bottom = max.y.max(origin.y + ocr::GLYPH_HEIGHT as i32 - 1);
+        ocr::decode(&self.draw(origin, Dot::new(max.x, bottom)))
+    }
+
+    /// Draws the dots from `min` to `max`, with `#` for dots and `.` for
+    /// blanks.
+    fn draw(&self, min: Dot, max: Dot) -> String {
+        (min.y..=max.y)
+            .map(|y| {
+                let mut line = (min.x..=max.x)
+                    .map(|x| {
+                        if self.contains(&Dot::new(x, y)) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>();
+                line.push('\n');
+                line
+            })
+            .collect()
     }
 }
 
-pub enum Fold {
-    Left(i32),
-    Up(i32),
+/// The papers a fold could have started from, as returned by
+/// [`Paper::preimages`].
+pub struct Preimages {
+    /// Dots on the fold line, which are in every paper.
+    fixed: Vec<Dot>,
+    /// Dots off the line, with where they'd be across it.
+    dots: Vec<(Dot, Dot)>,
+    /// Which of the dot, its mirror or both are in the next paper, until
+    /// there are no more.
+    choices: Option<Vec<u8>>,
 }
 
-struct Paper(HashSet<Dot>);
+impl Iterator for Preimages {
+    type Item = Paper;
 
-impl Paper {
-    fn new(dots: &[Dot]) -> Self {
-        Self(dots.iter().copied().collect())
+    fn next(&mut self) -> Option<Self::Item> {
+        let choices = self.choices.as_mut()?;
+        let mut dots = self.fixed.clone();
+        for (&choice, &(dot, mirror)) in choices.iter().zip(&self.dots) {
+            if choice != 1 {
+                dots.push(dot);
+            }
+            if choice != 0 {
+                dots.push(mirror);
+            }
+        }
+
+        // Count in base 3, stopping once every digit wraps around
+        match choices.iter().position(|&choice| choice < 2) {
+            Some(digit) => {
+                choices[..digit].fill(0);
+                choices[digit] += 1;
+            }
+            None => self.choices = None,
+        }
+        Some(Paper::new(&dots))
     }
+}
 
-    fn fold(&self, fold: &Fold) -> Self {
-        let dots = self.0.iter().map(|dot| dot.fold(fold)).collect();
-        Self(dots)
+/// Two papers are the same if they have the same dots, however they keep
+/// them.
+impl PartialEq for Paper {
+    fn eq(&self, other: &Self) -> bool {
+        self.count_dots() == other.count_dots()
+            && self.dots().all(|dot| other.contains(&dot))
     }
+}
+
+impl Eq for Paper {}
 
-    fn count_dots(&self) -> usize {
-        self.0.len()
+/// Dots in a rectangle, one bit per position, row by row.
+#[derive(Clone, Debug)]
+struct BitGrid {
+    min: Dot,
+    width: usize,
+    height: usize,
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl BitGrid {
+    fn new(min: Dot, width: usize, height: usize) -> Self {
+        Self {
+            min,
+            width,
+            height,
+            bits: vec![0; (width * height).div_ceil(64)],
+            len: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn bounds(&self) -> (Dot, Dot) {
+        let max = Dot::new(
+            self.min.x + self.width as i32 - 1,
+            self.min.y + self.height as i32 - 1,
+        );
+        (self.min, max)
+    }
+
+    fn index_of(&self, dot: &Dot) -> Option<usize> {
+        let x =
+            usize::try_from(i64::from(dot.x) - i64::from(self.min.x)).ok()?;
+        let y =
+            usize::try_from(i64::from(dot.y) - i64::from(self.min.y)).ok()?;
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    fn contains(&self, dot: &Dot) -> bool {
+        self.index_of(dot)
+            .is_some_and(|index| self.bits[index / 64] & 1 << (index % 64) != 0)
+    }
+
+    /// Adds a dot within the grid's rectangle.
+    fn insert(&mut self, dot: Dot) {
+        let index = self.index_of(&dot).expect("Dot outside the grid");
+        let (word, bit) = (index / 64, 1 << (index % 64));
+        if self.bits[word] & bit == 0 {
+            self.bits[word] |= bit;
+            self.len += 1;
+        }
+    }
+
+    fn iter(&self) -> BitIter<'_> {
+        BitIter {
+            grid: self,
+            word: 0,
+            bits: self.bits.first().copied().unwrap_or(0),
+        }
+    }
+}
+
+/// The dots of a [`BitGrid`], going through the bits of one word at a time.
+#[derive(Clone)]
+struct BitIter<'a> {
+    grid: &'a BitGrid,
+    word: usize,
+    /// The bits of the current word not gone through yet.
+    bits: u64,
+}
+
+impl Iterator for BitIter<'_> {
+    type Item = Dot;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.bits == 0 {
+            self.word += 1;
+            self.bits = *self.grid.bits.get(self.word)?;
+        }
+        let index = self.word * 64 + self.bits.trailing_zeros() as usize;
+        self.bits &= self.bits - 1;
+        let (x, y) = (index % self.grid.width, index / self.grid.width);
+        Some(Dot::new(
+            self.grid.min.x + x as i32,
+            self.grid.min.y + y as i32,
+        ))
+    }
+}
+
+/// The dots of a [`Paper`], as returned by [`Paper::dots`].
+#[derive(Clone)]
+pub struct DotIter<'a>(DotsIter<'a>);
+
+#[derive(Clone)]
+enum DotsIter<'a> {
+    Sparse(hash_set::Iter<'a, Dot>),
+    Dense(BitIter<'a>),
+}
+
+impl Iterator for DotIter<'_> {
+    type Item = Dot;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            DotsIter::Sparse(dots) => dots.next().copied(),
+            DotsIter::Dense(dots) => dots.next(),
+        }
+    }
+}
+
+/// Folding a sheet of transparent paper, one instruction at a time.
+pub struct Origami<'a> {
+    dots: &'a [Dot],
+    folds: &'a [Fold],
+    policy: FoldPolicy,
+}
+
+impl<'a> Origami<'a> {
+    pub fn new(dots: &'a [Dot], folds: &'a [Fold]) -> Self {
+        Self {
+            dots,
+            folds,
+            policy: FoldPolicy::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: FoldPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// The paper after every fold.
+    pub fn paper(&self) -> Result<Paper, String> {
+        self.paper_after(self.folds.len())
+    }
+
+    /// The paper after the first `folds` folds.
+    pub fn paper_after(&self, folds: usize) -> Result<Paper, String> {
+        self.folds
+            .iter()
+            .take(folds)
+            .try_fold(Paper::new(self.dots), |paper, fold| {
+                paper.fold_with(fold, self.policy)
+            })
+    }
+
+    /// The paper after every fold, with `true` wherever there's a dot.
+    pub fn grid(&self) -> Result<Grid<bool>, String> {
+        self.paper().map(|paper| paper.grid())
+    }
+}
+
+impl Visualize for Origami<'_> {
+    fn frames(&self) -> impl Iterator<Item = String> + '_ {
+        let papers =
+            self.folds
+                .iter()
+                .scan(Paper::new(self.dots), |paper, fold| {
+                    *paper = paper.fold_with(fold, self.policy).ok()?;
+                    Some(paper.to_string())
+                });
+        once(Paper::new(self.dots).to_string()).chain(papers)
     }
 }
 
@@ -68,69 +583,302 @@ pub fn part1(dots: &[Dot], folds: &[Fold]) -> usize {
     paper.count_dots()
 }
 
-pub fn part2(dots: &[Dot], folds: &[Fold]) {
-    let paper = folds
+/// Returns the paper after every fold, whose dots draw the code.
+pub fn part2(dots: &[Dot], folds: &[Fold]) -> Paper {
+    folds
         .iter()
-        .fold(Paper::new(dots), |paper, fold| paper.fold(fold));
-    println!("{}", paper);
+        .fold(Paper::new(dots), |paper, fold| paper.fold(fold))
+}
+
+/// Same as [`part1`], handling dots where the puzzle doesn't expect them as
+/// `policy` says.
+pub fn part1_with(
+    dots: &[Dot],
+    folds: &[Fold],
+    policy: FoldPolicy,
+) -> Result<usize, String> {
+    let origami = Origami::new(dots, folds).with_policy(policy);
+    Ok(origami.paper_after(1)?.count_dots())
+}
+
+/// Same as [`part2`], handling dots where the puzzle doesn't expect them as
+/// `policy` says.
+pub fn part2_with(
+    dots: &[Dot],
+    folds: &[Fold],
+    policy: FoldPolicy,
+) -> Result<Paper, String> {
+    Origami::new(dots, folds).with_policy(policy).paper()
 }
 
 impl Display for Paper {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let min_x = self.0.iter().map(|dot| dot.x).min().unwrap_or(0);
-        let max_x = self.0.iter().map(|dot| dot.x).max().unwrap_or(0);
-        let min_y = self.0.iter().map(|dot| dot.y).min().unwrap_or(0);
-        let max_y = self.0.iter().map(|dot| dot.y).max().unwrap_or(0);
-        for y in min_y..=max_y {
-            let line = (min_x..=max_x)
-                .map(|x| {
-                    if self.0.contains(&Dot::new(x, y)) {
-                        '#'
-                    } else {
-                        '.'
-                    }
-                })
-                .collect::<String>();
-            writeln!(f, "{}", line)?;
-        }
-        Ok(())
-    }
-}
-
-impl FromStr for Dot {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x_str, y_str) = s
-            .trim()
-            .split_once(',')
-            .ok_or_else(|| format!("Invalid dot '{}'", s))?;
-        let x = x_str
-            .parse()
-            .map_err(|_| format!("Invalid x coordinate '{}'", x_str))?;
-        let y = y_str
-            .parse()
-            .map_err(|_| format!("Invalid y coordinate '{}'", y_str))?;
-        Ok(Self { x, y })
+        let (min, max) = self.bounds();
+        write!(f, "{}", self.draw(min, max))
     }
 }
 
 impl FromStr for Fold {
     type Err = String;
 
+    /// Parses `fold along x=5` or `fold along y=7` as in the puzzle, as well
+    /// as diagonal lines such as `fold along y=x-2` or `fold along x+y=10`.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let fold = s.trim().to_ascii_lowercase().replace("fold along ", "");
         let (axis, val_str) = fold
             .split_once('=')
             .ok_or_else(|| format!("Invalid operation '{}'", s))?;
-        let value = val_str
-            .trim()
-            .parse()
-            .map_err(|_| format!("Invalid fold value '{}'", val_str))?;
-        match axis.trim() {
-            "y" => Ok(Up(value)),
-            "x" => Ok(Left(value)),
-            _ => Err(format!("Invalid fold axis '{}", axis)),
+        if let Ok(value) = val_str.trim().parse() {
+            match axis.trim() {
+                "y" => return Ok(Up(value)),
+                "x" => return Ok(Left(value)),
+                _ => {}
+            }
+        }
+
+        // Both sides as `a*x + b*y + c`, so the line is where they're equal
+        let (a1, b1, c1) = parse_linear(axis)
+            .ok_or_else(|| format!("Invalid fold axis '{}'", axis))?;
+        let (a2, b2, c2) = parse_linear(val_str)
+            .ok_or_else(|| format!("Invalid fold value '{}'", val_str))?;
+        match (a1 - a2, b1 - b2, c2 - c1) {
+            (a @ (1 | -1), 0, c) => Ok(Left(a * c)),
+            (0, b @ (1 | -1), c) => Ok(Up(b * c)),
+            (a @ (1 | -1), b, c) if b == -a => Ok(Diagonal(-a * c)),
+            (a @ (1 | -1), b, c) if b == a => Ok(AntiDiagonal(a * c)),
+            _ => Err(format!("Unsupported fold line '{}'", s)),
+        }
+    }
+}
+
+/// Parses a sum of `x`, `y` and whole numbers, each with an optional sign,
+/// into how many times it adds `x`, `y` and a constant.
+fn parse_linear(s: &str) -> Option<(i32, i32, i32)> {
+    let s: String = s.chars().filter(|ch| !ch.is_whitespace()).collect();
+    let mut terms = Vec::new();
+    let mut start = 0;
+    for (pos, ch) in s.char_indices().skip(1) {
+        if ch == '+' || ch == '-' {
+            terms.push(&s[start..pos]);
+            start = pos;
+        }
+    }
+    terms.push(&s[start..]);
+
+    let (mut x, mut y, mut c) = (0, 0, 0);
+    for term in terms {
+        let (sign, name) = match term.strip_prefix('-') {
+            Some(name) => (-1, name),
+            None => (1, term.strip_prefix('+').unwrap_or(term)),
+        };
+        match name {
+            "x" => x += sign,
+            "y" => y += sign,
+            _ => c += sign * name.parse::<i32>().ok()?,
+        }
+    }
+    Some((x, y, c))
+}
+
+pub fn parse_input(input: &str) -> Result<(Vec<Dot>, Vec<Fold>), String> {
+    let lines = input.lines().zip(1..).collect::<Vec<_>>();
+    let mut blocks = lines.as_slice().split(|(line, _)| line.trim().is_empty());
+
+    let dots = blocks
+        .next()
+        .ok_or_else(|| "Missing dot lines".to_string())?
+        .iter()
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let folds: Vec<Fold> = blocks
+        .next()
+        .ok_or_else(|| "Missing fold lines".to_string())?
+        .iter()
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((dots, folds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_folded_paper() {
+        let (dots, folds) = parse_input(EXAMPLE).unwrap();
+        let paper = part2(&dots, &folds);
+        assert_eq!((paper.width(), paper.height()), (5, 5));
+        assert_eq!(paper.count_dots(), 16);
+        assert!(paper.contains(&Dot::new(4, 4)));
+        assert!(!paper.contains(&Dot::new(2, 2)));
+        assert_eq!(paper.bounds(), (Dot::new(0, 0), Dot::new(4, 4)));
+        assert_eq!(
+            paper.grid().as_slice().iter().filter(|&&dot| dot).count(),
+            16
+        );
+        assert_eq!(paper.read(), None);
+    }
+
+    #[test]
+    fn reads_code() {
+        let art = "\
+            .###.####\n\
+            ..#.....#\n\
+            ..#....#.\n\
+            ..#...#..\n\
+            ..#..#...\n\
+            .###.####\n";
+        let dots: Vec<Dot> = art
+            .lines()
+            .zip(0..)
+            .flat_map(|(line, y)| {
+                line.chars()
+                    .zip(0..)
+                    .filter(|&(ch, _)| ch == '#')
+                    .map(move |(_, x)| Dot::new(x, y))
+            })
+            .collect();
+        let paper = Paper::new(&dots);
+        assert_eq!(paper.read(), Some("IZ".to_string()));
+        assert_eq!(paper.width(), 8);
+    }
+
+    #[test]
+    fn renders_any_fold() {
+        let (dots, folds) = parse_input(EXAMPLE).unwrap();
+        let origami = Origami::new(&dots, &folds);
+        assert_eq!(origami.paper_after(0).unwrap(), Paper::new(&dots));
+        assert_eq!(origami.paper_after(1).unwrap().count_dots(), 17);
+        assert_eq!(origami.paper_after(folds.len() + 1), origami.paper());
+
+        let svg = origami.paper().unwrap().render(3).to_string();
+        assert!(svg.contains(r#"viewBox="-1 -1 7 7" width="21" height="21""#));
+        assert_eq!(svg.matches("<rect").count(), 17);
+    }
+
+    #[test]
+    fn handles_unexpected_dots() {
+        let dots = [Dot::new(1, 0), Dot::new(2, 0), Dot::new(4, 1)];
+        let (on_line, too_far) = (Left(2), Left(1));
+        let paper = Paper::new(&dots);
+        assert!(paper.fold_with(&on_line, FoldPolicy::Error).is_err());
+        assert_eq!(
+            paper.fold_with(&on_line, FoldPolicy::Drop),
+            Ok(Paper::new(&[Dot::new(1, 0), Dot::new(0, 1)]))
+        );
+        assert_eq!(
+            paper.fold_with(&on_line, FoldPolicy::Clamp),
+            Ok(Paper::new(&[
+                Dot::new(1, 0),
+                Dot::new(2, 0),
+                Dot::new(0, 1)
+            ]))
+        );
+        let err = Paper::new(&dots[1..])
+            .fold_with(&too_far, FoldPolicy::Error)
+            .unwrap_err();
+        assert!(err.contains("past the edge") && err.contains("x=1"));
+        assert_eq!(
+            paper.fold_with(&too_far, FoldPolicy::Clamp),
+            Ok(Paper::new(&[
+                Dot::new(1, 0),
+                Dot::new(0, 0),
+                Dot::new(0, 1)
+            ]))
+        );
+        assert_eq!(part1_with(&dots, &[on_line], FoldPolicy::Drop), Ok(2));
+        assert_eq!("Clamp".parse(), Ok(FoldPolicy::Clamp));
+        assert!("keep".parse::<FoldPolicy>().is_err());
+    }
+
+    #[test]
+    fn unfolds() {
+        let (dots, folds) = parse_input(EXAMPLE).unwrap();
+        let paper = Paper::new(&dots).fold(&folds[0]);
+        let unfolded = paper.unfold(&folds[0]);
+        assert_eq!(unfolded.count_dots(), 2 * paper.count_dots());
+        assert_eq!(unfolded.fold(&folds[0]), paper);
+
+        let paper =
+            Paper::new(&[Dot::new(0, 0), Dot::new(1, 1), Dot::new(2, 0)]);
+        let preimages: Vec<_> = paper.preimages(&Left(2)).collect();
+        assert_eq!(preimages.len(), 9);
+        assert!(preimages.iter().all(|other| other.fold(&Left(2)) == paper));
+        assert_eq!(preimages[0], paper);
+        assert_eq!(preimages[8], paper.unfold(&Left(2)));
+        assert_eq!(paper.preimages(&Left(1)).count(), 0);
+    }
+
+    #[test]
+    fn folds_along_diagonals() {
+        let fold = |s: &str| s.parse::<Fold>().unwrap();
+        assert!(matches!(fold("fold along x=5"), Left(5)));
+        assert!(matches!(fold("fold along 7=y"), Up(7)));
+        assert!(matches!(fold("fold along x=y"), Diagonal(0)));
+        assert!(matches!(fold("fold along y = x + 3"), Diagonal(3)));
+        assert!(matches!(fold("fold along x=y+3"), Diagonal(-3)));
+        assert!(matches!(fold("fold along x+y=10"), AntiDiagonal(10)));
+        assert!(matches!(fold("fold along y=-x+4"), AntiDiagonal(4)));
+        for line in ["y=x-3", "y=x", "x+y=10", "x=4"] {
+            assert_eq!(fold(line).to_string(), format!("fold along {}", line));
+        }
+        assert!("fold along y=2x".parse::<Fold>().is_err());
+        assert!("fold along x=x".parse::<Fold>().is_err());
+        assert!("fold along z=1".parse::<Fold>().is_err());
+
+        let paper =
+            Paper::new(&[Dot::new(3, 1), Dot::new(1, 3), Dot::new(2, 2)]);
+        assert_eq!(
+            paper.fold(&Diagonal(0)),
+            Paper::new(&[Dot::new(1, 3), Dot::new(2, 2)])
+        );
+        assert_eq!(
+            paper.fold(&AntiDiagonal(2)),
+            Paper::new(&[Dot::new(1, -1), Dot::new(-1, 1), Dot::new(0, 0)])
+        );
+        assert!(paper.fold_with(&Diagonal(0), FoldPolicy::Error).is_err());
+        assert_eq!(paper.unfold(&Diagonal(1)).count_dots(), 4);
+    }
+
+    #[test]
+    fn keeps_dense_dots_as_bits() {
+        let (dots, folds) = parse_input(EXAMPLE).unwrap();
+        let dense = Paper::new(&dots);
+        let sparse = Paper(Dots::Sparse(dots.iter().copied().collect()));
+        assert!(dense.is_dense() && !sparse.is_dense());
+        assert_eq!(dense, sparse);
+        assert_eq!(dense.bounds(), sparse.bounds());
+        let (dense, sparse) =
+            folds.iter().fold((dense, sparse), |papers, fold| {
+                (papers.0.fold(fold), papers.1.fold(fold))
+            });
+        assert_eq!(dense, sparse);
+        assert_eq!(dense.to_string(), sparse.to_string());
+        assert_eq!(dense.count_dots(), 16);
+
+        let far_apart = Paper::new(&[Dot::new(0, 0), Dot::new(1000, -1000)]);
+        assert!(!far_apart.is_dense());
+        assert!(!Paper::new(&[]).is_dense());
+
+        // A million dots, folded down to one
+        let dots: Vec<_> = (0..1023)
+            .flat_map(|y| (0..1023).map(move |x| Dot::new(x, y)))
+            .collect();
+        let mut paper = Paper::new(&dots);
+        assert!(paper.is_dense());
+        for line in [511, 255, 127, 63, 31, 15, 7, 3, 1] {
+            for fold in [Left(line), Up(line)] {
+                paper = paper.fold_with(&fold, FoldPolicy::Drop).unwrap();
+            }
         }
+        assert_eq!(paper, Paper::new(&[Dot::new(0, 0)]));
     }
 }
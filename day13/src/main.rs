@@ -1,57 +1,184 @@
 use clap::{crate_description, App, Arg};
-use day13::{part1, part2, Dot, Fold};
-use std::fs::read_to_string;
+use common::{read_or_example, Color, Pretty, Visualize, COLOR_CHOICES};
+use day13::{parse_input, part1_with, part2_with, Dot, Fold, Origami, EXAMPLE};
+use std::path::Path;
 use std::process::exit;
+use std::time::Duration;
 
 fn main() {
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("visualize")
+                .long("visualize")
+                .help("Animates the puzzle on the terminal before solving it"),
+        )
+        .arg(
+            Arg::with_name("delay")
+                .long("delay")
+                .takes_value(true)
+                .default_value("100")
+                .validator(|value| {
+                    value
+                        .parse::<u64>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .help("Milliseconds between animation frames"),
+        )
+        .arg(
+            Arg::with_name("render")
+                .long("render")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Writes a drawing of the paper to PATH, .png or SVG"),
+        )
+        .arg(
+            Arg::with_name("scale")
+                .long("scale")
+                .takes_value(true)
+                .default_value("10")
+                .validator(is_positive)
+                .help("Pixels per dot in the drawing"),
+        )
+        .arg(
+            Arg::with_name("after")
+                .long("after")
+                .takes_value(true)
+                .value_name("FOLDS")
+                .validator(|value| {
+                    value
+                        .parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .help("Draws the paper after the first FOLDS folds"),
+        )
+        .arg(
+            Arg::with_name("draw")
+                .long("draw")
+                .help("Prints the folded paper with its dots in color"),
+        )
+        .arg(
+            Arg::with_name("fold-policy")
+                .long("fold-policy")
+                .takes_value(true)
+                .possible_values(&["error", "drop", "clamp"])
+                .default_value("error")
+                .help("What to do with dots on fold lines or folded too far"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let (dots, folds) = match read_input(args.value_of("INPUT").unwrap()) {
+    let (dots, folds) = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
+            exit(2);
+        }
+    };
+
+    // Already checked by the arguments' validators
+    let policy = args.value_of("fold-policy").unwrap().parse().unwrap();
+    let origami = Origami::new(&dots, &folds).with_policy(policy);
+    let (part1, paper) = match part1_with(&dots, &folds, policy)
+        .and_then(|part1| Ok((part1, part2_with(&dots, &folds, policy)?)))
+    {
+        Ok(answers) => answers,
+        Err(err) => {
+            println!("{}", pretty.error(format!("Failed to fold: {}", err)));
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&dots, &folds));
-    println!("Part 2:");
-    part2(&dots, &folds);
+    if args.is_present("visualize") {
+        let delay = args.value_of("delay").unwrap().parse().unwrap();
+        if let Err(err) = origami.visualize(Duration::from_millis(delay)) {
+            println!(
+                "{}",
+                pretty.error(format!("Failed to visualize: {}", err))
+            );
+        }
+    }
+
+    if let Some(path) = args.value_of("render") {
+        // Already checked by the arguments' validators
+        let scale = args.value_of("scale").unwrap().parse().unwrap();
+        let paper = match args.value_of("after") {
+            Some(after) => origami.paper_after(after.parse().unwrap()),
+            None => Ok(paper.clone()),
+        };
+        let path = Path::new(path);
+        let is_png = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("png"))
+            .unwrap_or(false);
+        let saved = paper.and_then(|paper| {
+            if is_png {
+                paper.save_png(path, scale)
+            } else {
+                paper
+                    .render(scale)
+                    .save(path)
+                    .map_err(|err| err.to_string())
+            }
+        });
+        if let Err(err) = saved {
+            println!("{}", pretty.error(format!("Failed to render: {}", err)));
+        }
+    }
+
+    if args.is_present("draw") {
+        let grid = paper.grid();
+        let drawing = pretty.grid(&grid, |&dot| {
+            if dot {
+                ('#', Some(Color::Yellow))
+            } else {
+                ('.', None)
+            }
+        });
+        print!("{}", drawing);
+    }
+
+    println!("Part 1: {}", pretty.answer(part1));
+    match paper.read() {
+        Some(code) => println!("Part 2: {}", pretty.answer(code)),
+        None => print!("Part 2:\n{}", pretty.answer(paper)),
+    }
+}
+
+fn is_positive(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(scale) if scale > 0 => Ok(()),
+        _ => Err("must be a positive number".to_string()),
+    }
 }
 
-fn read_input(filename: &str) -> Result<(Vec<Dot>, Vec<Fold>), String> {
-    let contents = read_to_string(filename).map_err(|err| err.to_string())?;
-    let lines = contents.lines().zip(1..).collect::<Vec<_>>();
-    let mut blocks = lines.as_slice().split(|(line, _)| line.trim().is_empty());
-
-    let dots = blocks
-        .next()
-        .ok_or_else(|| "Missing dot lines".to_string())?
-        .iter()
-        .map(|(line, line_num)| {
-            line.parse()
-                .map_err(|err| format!("Line {}: {}", line_num, err))
-        })
-        .collect::<Result<_, _>>()?;
-
-    let folds: Vec<Fold> = blocks
-        .next()
-        .ok_or_else(|| "Missing fold lines".to_string())?
-        .iter()
-        .map(|(line, line_num)| {
-            line.parse()
-                .map_err(|err| format!("Line {}: {}", line_num, err))
-        })
-        .collect::<Result<_, _>>()?;
-
-    Ok((dots, folds))
+fn read_input(filename: Option<&str>) -> Result<(Vec<Dot>, Vec<Fold>), String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
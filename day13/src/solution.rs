@@ -0,0 +1,20 @@
+use crate::{parse_input, part1_with, part2_with, FoldPolicy};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let (dots, folds) = parse_input(input)?;
+    let policy = FoldPolicy::default();
+    report.found(part1_with(&dots, &folds, policy)?);
+    let paper = part2_with(&dots, &folds, policy)?;
+    match paper.read() {
+        Some(code) => report.found(code),
+        None => report.found(paper.to_string().trim_end()),
+    }
+    Ok(())
+}
+
+register_solution!(day: 13, solve: solve);
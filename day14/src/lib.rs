@@ -1,12 +1,35 @@
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use serde_with::{As, Same};
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+/// Element counts grow exponentially with the number of steps.
+#[cfg(not(feature = "bigint"))]
+pub type Count = u64;
+#[cfg(feature = "bigint")]
+pub type Count = u128;
+
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct InsertionRule {
     pair: [char; 2],
     insert: char,
 }
 
-pub struct RuleMap(HashMap<[char; 2], char>);
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct RuleMap(
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<(Same, Same)>>"))]
+    HashMap<[char; 2], char>,
+);
 
 impl RuleMap {
     pub fn new(rules: Vec<InsertionRule>) -> Self {
@@ -19,58 +42,152 @@ impl RuleMap {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Polymer {
-    pair_count: HashMap<[char; 2], u64>,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<(Same, Same)>>"))]
+    pair_count: HashMap<[char; 2], Count>,
     end: char,
 }
 
 impl Polymer {
-    fn grow(&self, rules: &RuleMap) -> Option<Self> {
+    /// Inserts an element into every pair, as long as the polymer's length
+    /// still fits in a [`Count`], so that its elements can be counted.
+    fn grow(&self, rules: &RuleMap) -> Result<Self, String> {
+        let too_many = || "Too many elements to count".to_string();
         let mut pair_count = HashMap::new();
+        let mut length: Count = 1;
         for (pair, &count) in self.pair_count.iter() {
-            let insert = rules.get_insertion(pair)?;
+            let insert = rules.get_insertion(pair).ok_or_else(|| {
+                format!("No insertion rule for pair '{}{}'", pair[0], pair[1])
+            })?;
             for new_pair in [[pair[0], insert], [insert, pair[1]]] {
-                pair_count
-                    .entry(new_pair)
-                    .and_modify(|total| *total += count)
-                    .or_insert(count);
+                length = length.checked_add(count).ok_or_else(too_many)?;
+                *pair_count.entry(new_pair).or_default() += count;
             }
         }
 
-        Some(Self {
+        Ok(Self {
             pair_count,
             end: self.end,
         })
     }
 
-    fn frequency_delta(&self) -> u64 {
+    /// The polymer after `steps` insertion steps.
+    pub fn grow_n(&self, rules: &RuleMap, steps: u32) -> Result<Self, String> {
+        let mut polymer = self.clone();
+        for step in 1..=steps {
+            polymer = polymer
+                .grow(rules)
+                .map_err(|err| format!("Step {}: {}", step, err))?;
+        }
+        Ok(polymer)
+    }
+
+    /// How many of each element there are in the polymer. Growing makes sure
+    /// these add up to no more than a [`Count`] holds.
+    pub fn element_counts(&self) -> HashMap<char, Count> {
         let mut freq = [(self.end, 1)].into_iter().collect::<HashMap<_, _>>();
         for (pair, &count) in self.pair_count.iter() {
             freq.entry(pair[0])
                 .and_modify(|total| *total += count)
                 .or_insert(count);
         }
+        freq
+    }
 
+    /// The difference between how many there are of the most and least
+    /// common elements.
+    pub fn frequency_delta(&self) -> Count {
+        let freq = self.element_counts();
         let max = freq.values().max().unwrap_or(&0);
         let min = freq.values().min().unwrap_or(&0);
         max - min
     }
 }
 
-fn solve(template: &Polymer, rules: &RuleMap, steps: u32) -> Option<u64> {
+/// The polymer after an insertion step.
+pub struct Growth<'a> {
+    step: u32,
+    polymer: &'a Polymer,
+}
+
+impl Growth<'_> {
+    pub fn step(&self) -> u32 {
+        self.step
+    }
+
+    pub fn polymer(&self) -> &Polymer {
+        self.polymer
+    }
+}
+
+impl Display for Growth<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut pairs = self.polymer.pair_count.iter().collect::<Vec<_>>();
+        pairs.sort_unstable();
+        let counts = pairs
+            .iter()
+            .map(|(pair, count)| format!("{}{}={}", pair[0], pair[1], count))
+            .collect::<Vec<_>>();
+        write!(f, "Step {}: {}", self.step, counts.join(" "))
+    }
+}
+
+/// Grows `template` for the given number of steps and returns the difference
+/// between the most and least common elements, calling `observe` with the
+/// pair counts after every step.
+pub fn solve<F>(
+    template: &Polymer,
+    rules: &RuleMap,
+    steps: u32,
+    mut observe: F,
+) -> Option<Count>
+where
+    F: FnMut(Growth),
+{
     let mut polymer = template.clone();
-    for _ in 1..=steps {
-        polymer = polymer.grow(rules)?;
+    for step in 1..=steps {
+        polymer = polymer.grow(rules).ok()?;
+        observe(Growth {
+            step,
+            polymer: &polymer,
+        });
     }
     Some(polymer.frequency_delta())
 }
 
-pub fn part1(template: &Polymer, rules: &RuleMap) -> Option<u64> {
-    solve(template, rules, 10)
+pub fn part1(template: &Polymer, rules: &RuleMap) -> Option<Count> {
+    part1_observed(template, rules, |_| ())
 }
 
-pub fn part2(template: &Polymer, rules: &RuleMap) -> Option<u64> {
-    solve(template, rules, 40)
+pub fn part2(template: &Polymer, rules: &RuleMap) -> Option<Count> {
+    part2_observed(template, rules, |_| ())
+}
+
+/// Same as [`part1`], calling `observe` with the pair counts after every
+/// step.
+pub fn part1_observed<F>(
+    template: &Polymer,
+    rules: &RuleMap,
+    observe: F,
+) -> Option<Count>
+where
+    F: FnMut(Growth),
+{
+    solve(template, rules, 10, observe)
+}
+
+/// Same as [`part2`], calling `observe` with the pair counts after every
+/// step.
+pub fn part2_observed<F>(
+    template: &Polymer,
+    rules: &RuleMap,
+    observe: F,
+) -> Option<Count>
+where
+    F: FnMut(Growth),
+{
+    solve(template, rules, 40, observe)
 }
 
 impl FromStr for Polymer {
@@ -91,11 +208,10 @@ impl FromStr for Polymer {
                 .or_insert(1);
         }
 
-        if pair_count.is_empty() {
-            return Err("Invalid polymer template length".to_string());
-        }
-
-        let end = s.chars().last().unwrap();
+        let end = match s.chars().last() {
+            Some(end) if !pair_count.is_empty() => end,
+            _ => return Err("Invalid polymer template length".to_string()),
+        };
 
         Ok(Self { pair_count, end })
     }
@@ -122,3 +238,89 @@ impl FromStr for InsertionRule {
         Ok(Self { pair, insert })
     }
 }
+
+pub fn parse_input(input: &str) -> Result<(Polymer, RuleMap), String> {
+    let lines = input.lines().zip(1..).collect::<Vec<_>>();
+    let mut blocks = lines.as_slice().split(|(line, _)| line.trim().is_empty());
+
+    let template = blocks
+        .next()
+        .and_then(|block| block.iter().next())
+        .ok_or_else(|| "Missing template line".to_string())
+        .and_then(|(line, _)| line.parse())?;
+
+    let rule_map = blocks
+        .next()
+        .ok_or_else(|| "Missing rules".to_string())?
+        .iter()
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect::<Result<Vec<InsertionRule>, _>>()
+        .map(RuleMap::new)?;
+
+    Ok((template, rule_map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_elements() {
+        let (template, rules) = parse_input(EXAMPLE).unwrap();
+        assert_eq!(
+            template.element_counts(),
+            HashMap::from([('N', 2), ('C', 1), ('B', 1)])
+        );
+        let mut counts = HashMap::new();
+        part1_observed(&template, &rules, |growth| {
+            if growth.step() == 10 {
+                counts = growth.polymer().element_counts();
+            }
+        });
+        assert_eq!(
+            counts,
+            HashMap::from([('B', 1749), ('C', 298), ('H', 161), ('N', 865)])
+        );
+    }
+
+    #[test]
+    fn grows_any_number_of_steps() {
+        let (template, rules) = parse_input(EXAMPLE).unwrap();
+        assert_eq!(template.grow_n(&rules, 0).unwrap().frequency_delta(), 1);
+        let polymer = template.grow_n(&rules, 10).unwrap();
+        assert_eq!(polymer.frequency_delta(), 1588);
+        assert_eq!(
+            polymer.grow_n(&rules, 30).unwrap().frequency_delta(),
+            2188189693529
+        );
+        let counts = template.grow_n(&rules, 5).unwrap().element_counts();
+        assert_eq!(counts.values().sum::<Count>(), 97);
+
+        let err = template.grow_n(&rules, 200).err().unwrap();
+        assert!(err.starts_with("Step "));
+
+        // Counting elements mustn't overflow once growing succeeds, which
+        // with 64-bit counts goes up to step 62
+        let mut polymer = template.clone();
+        for step in 1.. {
+            let Ok(grown) = polymer.grow(&rules) else {
+                break;
+            };
+            polymer = grown;
+            let length = (3 << step) + 1;
+            let counts = polymer.element_counts();
+            assert_eq!(counts.values().sum::<Count>(), length, "{}", step);
+        }
+        assert!(template.grow_n(&rules, 62).is_ok());
+        let counts = template.grow_n(&rules, 64).map(|p| p.element_counts());
+        assert_eq!(counts.is_ok(), cfg!(feature = "bigint"));
+        let (template, rules) = parse_input("NX\n\nNN -> C").unwrap();
+        assert_eq!(
+            template.grow_n(&rules, 1).err(),
+            Some("Step 1: No insertion rule for pair 'NX'".to_string())
+        );
+    }
+}
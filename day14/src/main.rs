@@ -1,59 +1,134 @@
 use clap::{crate_description, App, Arg};
-use day14::{part1, part2, InsertionRule, Polymer, RuleMap};
-use std::fs::read_to_string;
+use common::{read_or_example, Pretty, COLOR_CHOICES};
+use day14::{
+    parse_input, part1_observed, part2_observed, Count, Growth, Polymer,
+    RuleMap, EXAMPLE,
+};
+use std::collections::HashMap;
 use std::process::exit;
 
 fn main() {
-    let args = App::new(crate_description!())
-        .arg(
-            Arg::with_name("INPUT")
-                .help("File with puzzle input")
-                .required(true)
-                .index(1),
-        )
-        .get_matches();
+    let args =
+        App::new(crate_description!())
+            .arg(
+                Arg::with_name("INPUT")
+                    .help("File with puzzle input")
+                    .required_unless("example")
+                    .index(1),
+            )
+            .arg(
+                Arg::with_name("example")
+                    .long("example")
+                    .conflicts_with("INPUT")
+                    .help("Solves the example from the puzzle description"),
+            )
+            .arg(Arg::with_name("explain").long("explain").help(
+                "Prints the intermediate steps taken to reach the answers",
+            ))
+            .arg(
+                Arg::with_name("steps")
+                    .long("steps")
+                    .takes_value(true)
+                    .validator(|value| {
+                        value
+                            .parse::<u32>()
+                            .map(|_| ())
+                            .map_err(|err| err.to_string())
+                    })
+                    .help("Also grows the polymer for N steps"),
+            )
+            .arg(
+                Arg::with_name("counts")
+                    .long("counts")
+                    .help("Prints how many of each element there are"),
+            )
+            .arg(
+                Arg::with_name("color")
+                    .long("color")
+                    .takes_value(true)
+                    .possible_values(&COLOR_CHOICES)
+                    .default_value("auto")
+                    .help("When to color the output"),
+            )
+            .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let (template, rules) = match read_input(args.value_of("INPUT").unwrap()) {
+    let (template, rules) = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    match part1(&template, &rules) {
-        Some(answer) => println!("Part 1: {}", &answer),
+    let explain = args.is_present("explain");
+    let mut counts = HashMap::new();
+    let answer = part1_observed(&template, &rules, |event| {
+        observe(event, explain, &mut counts)
+    });
+    match answer {
+        Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
         None => println!("Part 1: Not found"),
     }
-    match part2(&template, &rules) {
-        Some(answer) => println!("Part 2: {}", &answer),
+    if args.is_present("counts") {
+        print_counts("Part 1", &counts);
+    }
+    let answer = part2_observed(&template, &rules, |event| {
+        observe(event, explain, &mut counts)
+    });
+    match answer {
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
         None => println!("Part 2: Not found"),
     }
-}
+    if args.is_present("counts") {
+        print_counts("Part 2", &counts);
+    }
 
-fn read_input(filename: &str) -> Result<(Polymer, RuleMap), String> {
-    let contents = read_to_string(filename).map_err(|err| err.to_string())?;
-    let lines = contents.lines().zip(1..).collect::<Vec<_>>();
-    let mut blocks = lines.as_slice().split(|(line, _)| line.trim().is_empty());
+    if let Some(steps) = args.value_of("steps") {
+        // Already checked by the argument's validator
+        let steps = steps.parse().unwrap();
+        match template.grow_n(&rules, steps) {
+            Ok(polymer) => {
+                let delta = polymer.frequency_delta();
+                println!("After {} steps: {}", steps, pretty.answer(delta));
+                if args.is_present("counts") {
+                    let label = format!("After {} steps", steps);
+                    print_counts(&label, &polymer.element_counts());
+                }
+            }
+            Err(err) => println!(
+                "{}",
+                pretty.error(format!("Failed to grow polymer: {}", err))
+            ),
+        }
+    }
+}
 
-    let template = blocks
-        .next()
-        .and_then(|block| block.iter().next())
-        .ok_or_else(|| "Missing template line".to_string())
-        .and_then(|(line, _)| line.parse())?;
+/// Prints the pair counts after an insertion step if `explain` is set, and
+/// keeps the element counts.
+fn observe(event: Growth, explain: bool, counts: &mut HashMap<char, Count>) {
+    if explain {
+        println!("{}", event);
+    }
+    *counts = event.polymer().element_counts();
+}
 
-    let rule_map = blocks
-        .next()
-        .ok_or_else(|| "Missing rules".to_string())?
+fn print_counts(label: &str, counts: &HashMap<char, Count>) {
+    let mut counts: Vec<_> = counts.iter().collect();
+    counts.sort_unstable();
+    let counts: Vec<_> = counts
         .iter()
-        .map(|(line, line_num)| {
-            line.parse()
-                .map_err(|err| format!("Line {}: {}", line_num, err))
-        })
-        .collect::<Result<Vec<InsertionRule>, _>>()
-        .map(RuleMap::new)?;
-
-    Ok((template, rule_map))
+        .map(|(element, count)| format!("{}={}", element, count))
+        .collect();
+    println!("{} elements: {}", label, counts.join(" "));
+}
+
+fn read_input(filename: Option<&str>) -> Result<(Polymer, RuleMap), String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
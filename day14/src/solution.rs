@@ -0,0 +1,29 @@
+use crate::parse_input;
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let (template, rules) = parse_input(input)?;
+    report.maybe(crate::solve(
+        &template,
+        &rules,
+        params.get("part1_steps")?,
+        |_| (),
+    ));
+    report.maybe(crate::solve(
+        &template,
+        &rules,
+        params.get("part2_steps")?,
+        |_| (),
+    ));
+    Ok(())
+}
+
+register_solution!(
+    day: 14,
+    params: &[("part1_steps", 10), ("part2_steps", 40)],
+    solve: solve,
+);
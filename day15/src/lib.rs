@@ -1,110 +1,138 @@
-use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap};
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{
+    parse_digit_grid, shortest_path_with_stats, Connectivity, Grid, SearchStats,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
+use Backend::*;
 
-#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct Coord {
-    x: i32,
-    y: i32,
-}
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
 
-impl Coord {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
-    }
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct RiskMap(Grid<i32>);
 
-    fn adjacent(&self) -> impl Iterator<Item = Self> + '_ {
-        [(0, -1), (0, 1), (-1, 0), (1, 0)]
-            .into_iter()
-            .map(|(dx, dy)| Coord::new(self.x + dx, self.y + dy))
-    }
+/// Ways of searching for the path with the lowest total risk.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Backend {
+    /// Guided towards the target by the Manhattan distance.
+    #[default]
+    AStar,
+    /// Explores positions in order of total risk alone.
+    Dijkstra,
+}
+
+impl common::Backend for Backend {
+    const ALL: &'static [Self] = &[AStar, Dijkstra];
 
-    fn add(&self, x_add: i32, y_add: i32) -> Self {
-        Self {
-            x: self.x + x_add,
-            y: self.y + y_add,
+    fn name(&self) -> &'static str {
+        match self {
+            AStar => "a-star",
+            Dijkstra => "dijkstra",
         }
     }
 }
 
-pub struct RiskMap(HashMap<Coord, i32>);
-
 impl RiskMap {
-    fn get_risk(&self, coord: &Coord) -> Option<i32> {
-        self.0.get(coord).copied()
-    }
-
-    fn lowest_total_risk(&self) -> Option<i32> {
-        let target = self.0.keys().max()?;
-
-        let mut lowest = HashMap::from([(Coord::default(), 0)]);
-        let mut heap = BinaryHeap::from([Reverse((0, Coord::default()))]);
-
-        while let Some(Reverse((curr_risk, coord))) = heap.pop() {
-            for adjacent in coord.adjacent() {
-                if let Some(adj_risk) = self.get_risk(&adjacent) {
-                    if lowest
-                        .get(&adjacent)
-                        .map(|&r| r > curr_risk + adj_risk)
-                        .unwrap_or(true)
-                    {
-                        let new_risk = curr_risk + adj_risk;
-                        lowest.insert(adjacent.clone(), new_risk);
-                        heap.push(Reverse((new_risk, adjacent)));
-                    }
-                }
-            }
+    fn lowest_total_risk(
+        &self,
+        backend: Backend,
+        stats: &mut SearchStats,
+    ) -> Option<i32> {
+        if self.0.is_empty() {
+            return None;
         }
+        let target = (self.0.width() - 1, self.0.height() - 1);
 
-        lowest.remove(target)
+        // Every step costs at least the lowest risk level, so the Manhattan
+        // distance to the target times it never overestimates the remaining
+        // risk, even with risk levels of 0
+        let min_risk = self.0.values().copied().min().unwrap_or(0).max(0);
+        shortest_path_with_stats(
+            (0, 0),
+            |&(x, y)| {
+                self.0
+                    .neighbors(x, y, Connectivity::Four)
+                    .map(|adjacent| (adjacent, self.0[adjacent]))
+            },
+            |&pos| pos == target,
+            |&(x, y)| match backend {
+                AStar => (target.0 - x + target.1 - y) as i32 * min_risk,
+                Dijkstra => 0,
+            },
+            stats,
+        )
     }
 
-    fn enlarge(&self, x_mult: i32, y_mult: i32) -> Self {
+    fn enlarge(&self, x_mult: usize, y_mult: usize) -> Self {
         let wrap = |num| (num - 1) % 9 + 1;
-        let x_dim = 1 + self.0.keys().map(|coord| coord.x).max().unwrap_or(-1);
-        let y_dim = 1 + self.0.keys().map(|coord| coord.y).max().unwrap_or(-1);
-        let map = self
-            .0
-            .iter()
-            .flat_map(|(coord, risk)| {
-                (0..x_mult).flat_map(move |mx| {
-                    (0..y_mult).map(move |my| {
-                        (
-                            coord.add(mx * x_dim, my * y_dim),
-                            wrap(risk + mx + my),
-                        )
-                    })
-                })
-            })
-            .collect();
+        let (x_dim, y_dim) = (self.0.width(), self.0.height());
+        let map = Grid::from_fn(x_dim * x_mult, y_dim * y_mult, |x, y| {
+            let increase = (x / x_dim + y / y_dim) as i32;
+            wrap(self.0[(x % x_dim, y % y_dim)] + increase)
+        });
         Self(map)
     }
 }
 
 pub fn part1(risk_map: &RiskMap) -> Option<i32> {
-    risk_map.lowest_total_risk()
+    part1_with(risk_map, Backend::default())
 }
 
 pub fn part2(risk_map: &RiskMap) -> Option<i32> {
-    risk_map.enlarge(5, 5).lowest_total_risk()
+    part2_with(risk_map, Backend::default())
+}
+
+pub fn part1_with(risk_map: &RiskMap, backend: Backend) -> Option<i32> {
+    part1_with_stats(risk_map, backend, &mut SearchStats::default())
+}
+
+pub fn part2_with(risk_map: &RiskMap, backend: Backend) -> Option<i32> {
+    part2_with_stats(risk_map, backend, &mut SearchStats::default())
+}
+
+pub fn part1_with_stats(
+    risk_map: &RiskMap,
+    backend: Backend,
+    stats: &mut SearchStats,
+) -> Option<i32> {
+    risk_map.lowest_total_risk(backend, stats)
+}
+
+pub fn part2_with_stats(
+    risk_map: &RiskMap,
+    backend: Backend,
+    stats: &mut SearchStats,
+) -> Option<i32> {
+    risk_map.enlarge(5, 5).lowest_total_risk(backend, stats)
 }
 
 impl FromStr for RiskMap {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.lines()
-            .zip(0..)
-            .flat_map(|(line, y)| {
-                line.chars().zip(0..).map(move |(ch, x)| {
-                    ch.to_digit(10)
-                        .map(|num| {
-                            (Coord::new(x, y), i32::try_from(num).unwrap())
-                        })
-                        .ok_or_else(|| format!("Invalid risk level '{}'", ch))
-                })
-            })
-            .collect::<Result<HashMap<_, _>, _>>()
-            .map(Self)
+        parse_digit_grid(s).map(|grid| Self(grid.map(|&risk| i32::from(risk))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backends_agree_with_zero_risks() {
+        let risk_map: RiskMap = "09006\n03710".parse().unwrap();
+        assert_eq!(part1_with(&risk_map, AStar), Some(10));
+        assert_eq!(part1_with(&risk_map, Dijkstra), Some(10));
+
+        let risk_map: RiskMap = EXAMPLE.parse().unwrap();
+        for backend in [AStar, Dijkstra] {
+            assert_eq!(part1_with(&risk_map, backend), Some(40));
+            assert_eq!(part2_with(&risk_map, backend), Some(315));
+        }
     }
 }
@@ -0,0 +1,15 @@
+use crate::{part1, part2, RiskMap};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let risk_map: RiskMap = input.parse()?;
+    report.maybe(part1(&risk_map));
+    report.maybe(part2(&risk_map));
+    Ok(())
+}
+
+register_solution!(day: 15, solve: solve);
@@ -1,13 +1,29 @@
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{BitReader, BitWriter};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::str::FromStr;
 use Packet::*;
 use PacketType::*;
 
-#[derive(Clone)]
-pub struct Message(Vec<char>);
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Message(Vec<u8>);
 
 type PacketVersion = u64;
 
+/// Packet values may be arbitrarily wide literals and products thereof.
+#[cfg(not(feature = "bigint"))]
+pub type Value = u64;
+#[cfg(feature = "bigint")]
+pub type Value = u128;
+
 #[derive(Debug, Eq, PartialEq)]
 enum PacketType {
     OperSum,
@@ -40,7 +56,8 @@ impl TryFrom<u64> for PacketType {
 
 #[derive(Debug, Eq, PartialEq)]
 enum Packet {
-    LiteralValue(PacketVersion, u64),
+    /// The value, unless it's too wide for a [`Value`].
+    LiteralValue(PacketVersion, Option<Value>),
     Operator(PacketVersion, PacketType, Vec<Packet>),
 }
 
@@ -54,14 +71,18 @@ impl Packet {
         }
     }
 
-    fn value(&self) -> Option<u64> {
+    fn value(&self) -> Option<Value> {
         match self {
-            LiteralValue(_, value) => Some(*value),
+            LiteralValue(_, value) => *value,
             Operator(_, OperSum, packets) => {
-                packets.iter().map(Packet::value).sum()
+                packets.iter().try_fold(0 as Value, |sum, packet| {
+                    sum.checked_add(packet.value()?)
+                })
             }
             Operator(_, OperProduct, packets) => {
-                packets.iter().map(Packet::value).product()
+                packets.iter().try_fold(1 as Value, |product, packet| {
+                    product.checked_mul(packet.value()?)
+                })
             }
             Operator(_, OperMinimum, packets) => {
                 packets.iter().map(Packet::value).min().flatten()
@@ -105,61 +126,53 @@ impl Packet {
 }
 
 impl Message {
-    fn get_packet(&mut self) -> Option<Packet> {
-        let (packet, _) = self.get_inner_packet()?;
-        self.drop_padding()?;
+    fn get_packet(&self) -> Option<Packet> {
+        let mut reader = PacketReader(BitReader::new(&self.0));
+        let packet = reader.get_packet()?;
+        reader.drop_padding()?;
         Some(packet)
     }
+}
 
-    fn get_inner_packet(&mut self) -> Option<(Packet, usize)> {
-        let version = self.pop_bits(3)?;
-        let type_id = self.pop_bits(3)?;
-        let mut num_bits = 6;
+struct PacketReader<'a>(BitReader<'a>);
+
+impl PacketReader<'_> {
+    fn get_packet(&mut self) -> Option<Packet> {
+        let version = self.0.pop(3)?;
+        let type_id = self.0.pop(3)?;
 
         let packet = match type_id.try_into() {
-            Ok(Literal) => {
-                let (literal, literal_bits) = self.get_literal()?;
-                num_bits += literal_bits;
-                LiteralValue(version, literal)
-            }
+            Ok(Literal) => LiteralValue(version, self.get_literal()?),
             Ok(packet_type) => {
-                let (sub_packets, sub_packet_bits) = self.get_sub_packets()?;
-                num_bits += sub_packet_bits;
-                Operator(version, packet_type, sub_packets)
+                Operator(version, packet_type, self.get_sub_packets()?)
             }
             _ => {
                 return None;
             }
         };
 
-        Some((packet, num_bits))
+        Some(packet)
     }
 
-    fn get_sub_packets(&mut self) -> Option<(Vec<Packet>, usize)> {
-        let mut num_bits = 0;
+    fn get_sub_packets(&mut self) -> Option<Vec<Packet>> {
         let mut sub_packets = Vec::new();
-        let length_type_id = self.pop_bits(1)?;
+        let length_type_id = self.0.pop(1)?;
 
         match length_type_id {
             0 => {
-                let total_bit_len = self.pop_bits(15)?.try_into().ok()?;
-                while num_bits < total_bit_len {
-                    let (packet, sub_bits) = self.get_inner_packet()?;
-                    num_bits += sub_bits;
-                    sub_packets.push(packet);
+                let total_bit_len = usize::try_from(self.0.pop(15)?).ok()?;
+                let end = self.0.offset() + total_bit_len;
+                while self.0.offset() < end {
+                    sub_packets.push(self.get_packet()?);
                 }
-                if num_bits != total_bit_len {
+                if self.0.offset() != end {
                     return None;
                 }
-                num_bits += 15;
             }
             1 => {
-                let total_sub_packets = self.pop_bits(11)?;
-                num_bits += 11;
+                let total_sub_packets = self.0.pop(11)?;
                 for _ in 1..=total_sub_packets {
-                    let (packet, sub_bits) = self.get_inner_packet()?;
-                    num_bits += sub_bits;
-                    sub_packets.push(packet);
+                    sub_packets.push(self.get_packet()?);
                 }
             }
             _ => {
@@ -167,82 +180,54 @@ impl Message {
             }
         }
 
-        Some((sub_packets, 1 + num_bits))
+        Some(sub_packets)
     }
 
-    fn get_literal(&mut self) -> Option<(u64, usize)> {
-        let mut num_bits = 0;
-        let mut literal = 0;
+    /// Reads a literal to its end, giving its value unless it's too wide for
+    /// a [`Value`], or `None` if the message ends first.
+    fn get_literal(&mut self) -> Option<Option<Value>> {
+        let mut literal = Some(0);
         let mut keep_reading = 1;
         while keep_reading == 1 {
-            keep_reading = self.pop_bits(1)?;
-            literal = literal << 4 | self.pop_bits(4)?;
-            num_bits += 5;
+            keep_reading = self.0.pop(1)?;
+            let group = Value::from(self.0.pop(4)?);
+            literal = literal
+                .filter(|value: &Value| value.leading_zeros() >= 4)
+                .map(|value| value << 4 | group);
         }
-        Some((literal, num_bits))
-    }
-
-    fn pop_bits(&mut self, num_bits: usize) -> Option<u64> {
-        if num_bits > self.0.len() {
-            return None;
-        }
-
-        let bits = self.0.split_off(self.0.len() - num_bits);
-        u64::from_str_radix(
-            bits.into_iter().rev().collect::<String>().as_str(),
-            2,
-        )
-        .ok()
+        Some(literal)
     }
 
     fn drop_padding(&mut self) -> Option<()> {
-        if self.0.is_empty() || self.pop_bits(self.0.len())? == 0 {
-            Some(())
-        } else {
-            None
+        while !self.0.is_empty() {
+            if self.0.pop(self.0.remaining().min(64))? != 0 {
+                return None;
+            }
         }
+        Some(())
     }
 }
 
 pub fn part1(message: &Message) -> Option<u64> {
-    message
-        .clone()
-        .get_packet()
-        .map(|packet| packet.sum_versions())
+    message.get_packet().map(|packet| packet.sum_versions())
 }
 
-pub fn part2(message: &Message) -> Option<u64> {
-    message
-        .clone()
-        .get_packet()
-        .and_then(|packet| packet.value())
+pub fn part2(message: &Message) -> Option<Value> {
+    message.get_packet().and_then(|packet| packet.value())
 }
 
 impl FromStr for Message {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.trim()
-            .chars()
-            .rev()
-            .map(|ch| {
-                ch.to_digit(16).ok_or_else(|| {
-                    format!("Invalid hexadecimal character '{}'", ch)
-                })
-            })
-            .collect::<Result<Vec<_>, _>>()
-            .map(|vec| {
-                Self(
-                    vec.into_iter()
-                        .flat_map(|num| {
-                            format!("{:04b}", num)
-                                .chars()
-                                .rev()
-                                .collect::<Vec<_>>()
-                        })
-                        .collect::<Vec<char>>(),
-                )
-            })
+        let mut bits = BitWriter::new();
+        for ch in s.trim().chars() {
+            let num = ch.to_digit(16).ok_or_else(|| {
+                format!("Invalid hexadecimal character '{}'", ch)
+            })?;
+            bits.push(u64::from(num), 4);
+        }
+        Ok(Self(bits.into_bytes()))
     }
 }
 
@@ -250,25 +235,56 @@ impl FromStr for Message {
 mod tests {
     use super::*;
 
+    /// Writes a literal packet of version 1 with `groups` groups of ones.
+    fn wide_literal(bits: &mut BitWriter, groups: usize) {
+        bits.push(1, 3);
+        bits.push(4, 3);
+        for group in 1..=groups {
+            bits.push(u64::from(group < groups), 1);
+            bits.push(0xF, 4);
+        }
+    }
+
+    #[test]
+    fn wide_values() {
+        // Too wide for a u64, but not a u128
+        let mut bits = BitWriter::new();
+        wide_literal(&mut bits, 17);
+        let message = Message(bits.into_bytes());
+        assert_eq!(part1(&message), Some(1));
+        assert_eq!(part2(&message), Value::try_from((1u128 << 68) - 1).ok());
+
+        // The sum of two of the widest values
+        let mut bits = BitWriter::new();
+        bits.push(0, 6);
+        bits.push(1, 1);
+        bits.push(2, 11);
+        wide_literal(&mut bits, Value::BITS as usize / 4);
+        wide_literal(&mut bits, Value::BITS as usize / 4);
+        let message = Message(bits.into_bytes());
+        assert_eq!(part1(&message), Some(2));
+        assert_eq!(part2(&message), None);
+    }
+
     #[test]
     fn literal() {
-        let mut message: Message = "D2FE28".parse().unwrap();
+        let message: Message = "D2FE28".parse().unwrap();
         let packet = message.get_packet().unwrap();
-        assert_eq!(packet, LiteralValue(6, 2021));
+        assert_eq!(packet, LiteralValue(6, Some(2021)));
         assert_eq!(packet.sum_versions(), 6);
         assert_eq!(packet.value(), Some(2021));
     }
 
     #[test]
     fn operator_less_than() {
-        let mut message: Message = "38006F45291200".parse().unwrap();
+        let message: Message = "38006F45291200".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(
             packet,
             Operator(
                 1,
                 OperLessThan,
-                vec![LiteralValue(6, 10), LiteralValue(2, 20)]
+                vec![LiteralValue(6, Some(10)), LiteralValue(2, Some(20))]
             )
         );
         assert_eq!(packet.sum_versions(), 9);
@@ -277,7 +293,7 @@ mod tests {
 
     #[test]
     fn operator_maximum() {
-        let mut message: Message = "EE00D40C823060".parse().unwrap();
+        let message: Message = "EE00D40C823060".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(
             packet,
@@ -285,9 +301,9 @@ mod tests {
                 7,
                 OperMaximum,
                 vec![
-                    LiteralValue(2, 1),
-                    LiteralValue(4, 2),
-                    LiteralValue(1, 3)
+                    LiteralValue(2, Some(1)),
+                    LiteralValue(4, Some(2)),
+                    LiteralValue(1, Some(3))
                 ]
             )
         );
@@ -297,7 +313,7 @@ mod tests {
 
     #[test]
     fn operator_minimum() {
-        let mut message: Message = "8A004A801A8002F478".parse().unwrap();
+        let message: Message = "8A004A801A8002F478".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(
             packet,
@@ -307,7 +323,11 @@ mod tests {
                 vec![Operator(
                     1,
                     OperMinimum,
-                    vec![Operator(5, OperMinimum, vec![LiteralValue(6, 15)])]
+                    vec![Operator(
+                        5,
+                        OperMinimum,
+                        vec![LiteralValue(6, Some(15))]
+                    )]
                 )]
             )
         );
@@ -317,8 +337,7 @@ mod tests {
 
     #[test]
     fn operator_sum() {
-        let mut message: Message =
-            "620080001611562C8802118E34".parse().unwrap();
+        let message: Message = "620080001611562C8802118E34".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(
             packet,
@@ -329,12 +348,18 @@ mod tests {
                     Operator(
                         0,
                         OperSum,
-                        vec![LiteralValue(0, 10), LiteralValue(5, 11)]
+                        vec![
+                            LiteralValue(0, Some(10)),
+                            LiteralValue(5, Some(11))
+                        ]
                     ),
                     Operator(
                         1,
                         OperSum,
-                        vec![LiteralValue(0, 12), LiteralValue(3, 13)]
+                        vec![
+                            LiteralValue(0, Some(12)),
+                            LiteralValue(3, Some(13))
+                        ]
                     )
                 ]
             )
@@ -345,8 +370,7 @@ mod tests {
 
     #[test]
     fn operator_sum2() {
-        let mut message: Message =
-            "C0015000016115A2E0802F182340".parse().unwrap();
+        let message: Message = "C0015000016115A2E0802F182340".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(
             packet,
@@ -357,12 +381,18 @@ mod tests {
                     Operator(
                         0,
                         OperSum,
-                        vec![LiteralValue(0, 10), LiteralValue(6, 11)]
+                        vec![
+                            LiteralValue(0, Some(10)),
+                            LiteralValue(6, Some(11))
+                        ]
                     ),
                     Operator(
                         4,
                         OperSum,
-                        vec![LiteralValue(7, 12), LiteralValue(0, 13)]
+                        vec![
+                            LiteralValue(7, Some(12)),
+                            LiteralValue(0, Some(13))
+                        ]
                     )
                 ]
             )
@@ -373,7 +403,7 @@ mod tests {
 
     #[test]
     fn operator_sum3() {
-        let mut message: Message =
+        let message: Message =
             "A0016C880162017C3686B18A3D4780".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(
@@ -388,11 +418,11 @@ mod tests {
                         3,
                         OperSum,
                         vec![
-                            LiteralValue(7, 6),
-                            LiteralValue(6, 6),
-                            LiteralValue(5, 12),
-                            LiteralValue(2, 15),
-                            LiteralValue(2, 15)
+                            LiteralValue(7, Some(6)),
+                            LiteralValue(6, Some(6)),
+                            LiteralValue(5, Some(12)),
+                            LiteralValue(2, Some(15)),
+                            LiteralValue(2, Some(15))
                         ]
                     )]
                 )]
@@ -404,57 +434,56 @@ mod tests {
 
     #[test]
     fn operator_sum4() {
-        let mut message: Message = "C200B40A82".parse().unwrap();
+        let message: Message = "C200B40A82".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(packet.value(), Some(3));
     }
 
     #[test]
     fn operator_product() {
-        let mut message: Message = "04005AC33890".parse().unwrap();
+        let message: Message = "04005AC33890".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(packet.value(), Some(54));
     }
 
     #[test]
     fn operator_minimum2() {
-        let mut message: Message = "880086C3E88112".parse().unwrap();
+        let message: Message = "880086C3E88112".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(packet.value(), Some(7));
     }
 
     #[test]
     fn operator_maximum2() {
-        let mut message: Message = "CE00C43D881120".parse().unwrap();
+        let message: Message = "CE00C43D881120".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(packet.value(), Some(9));
     }
 
     #[test]
     fn operator_less_than2() {
-        let mut message: Message = "D8005AC2A8F0".parse().unwrap();
+        let message: Message = "D8005AC2A8F0".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(packet.value(), Some(1));
     }
 
     #[test]
     fn operator_greater_than() {
-        let mut message: Message = "F600BC2D8F".parse().unwrap();
+        let message: Message = "F600BC2D8F".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(packet.value(), Some(0));
     }
 
     #[test]
     fn operator_equal_to() {
-        let mut message: Message = "9C005AC2F8F0".parse().unwrap();
+        let message: Message = "9C005AC2F8F0".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(packet.value(), Some(0));
     }
 
     #[test]
     fn operator_sum_equal_to_product() {
-        let mut message: Message =
-            "9C0141080250320F1802104A08".parse().unwrap();
+        let message: Message = "9C0141080250320F1802104A08".parse().unwrap();
         let packet = message.get_packet().unwrap();
         assert_eq!(packet.value(), Some(1));
     }
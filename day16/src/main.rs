@@ -1,6 +1,6 @@
 use clap::{crate_description, App, Arg};
-use day16::{part1, part2, Message};
-use std::fs::read_to_string;
+use common::{read_or_example, Pretty, COLOR_CHOICES};
+use day16::{part1, part2, Message, EXAMPLE};
 use std::process::exit;
 
 fn main() {
@@ -8,33 +8,49 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
     match part1(&input) {
-        Some(answer) => println!("Part 1: {}", &answer),
+        Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
         None => println!("Part 1: Not found"),
     }
     match part2(&input) {
-        Some(answer) => println!("Part 2: {}", &answer),
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
         None => println!("Part 2: Not found"),
     }
 }
 
-fn read_input(filename: &str) -> Result<Message, String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())
-        .and_then(|s| s.parse())
+fn read_input(filename: Option<&str>) -> Result<Message, String> {
+    read_or_example(filename, EXAMPLE).and_then(|s| s.parse())
 }
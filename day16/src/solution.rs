@@ -0,0 +1,15 @@
+use crate::{part1, part2, Message};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let message: Message = input.parse()?;
+    report.maybe(part1(&message));
+    report.maybe(part2(&message));
+    Ok(())
+}
+
+register_solution!(day: 16, solve: solve);
@@ -1,11 +1,29 @@
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{Interval, Svg};
 use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Target {
-    start_x: i32,
-    end_x: i32,
-    start_y: i32,
-    end_y: i32,
+    x_range: Interval,
+    y_range: Interval,
+}
+
+impl Target {
+    fn new(start_x: i32, end_x: i32, start_y: i32, end_y: i32) -> Self {
+        Self {
+            x_range: Interval::new(start_x, end_x),
+            y_range: Interval::new(start_y, end_y),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -21,35 +39,34 @@ impl Position {
     }
 
     fn in_target(&self, target: &Target) -> bool {
-        self.x >= target.start_x
-            && self.x <= target.end_x
-            && self.y >= target.start_y
-            && self.y <= target.end_y
+        target.x_range.contains(self.x) && target.y_range.contains(self.y)
     }
 
     fn past_target(&self, target: &Target) -> bool {
-        (self.x > 0 && self.x > target.end_x)
-            || (self.x < 0 && self.x < target.start_x)
-            || self.y < target.start_y
+        (self.x > 0 && self.x > target.x_range.end())
+            || (self.x < 0 && self.x < target.x_range.start())
+            || self.y < target.y_range.start()
     }
 }
 
 fn viable_velocities(target: &Target) -> impl Iterator<Item = Velocity> + '_ {
-    let dx_range = if target.start_x <= 0 && target.end_x >= 0 {
-        target.start_x..=target.end_x
-    } else if target.start_x > 0 {
-        1..=target.end_x
+    let (start_x, end_x) = (target.x_range.start(), target.x_range.end());
+    let (start_y, end_y) = (target.y_range.start(), target.y_range.end());
+    let dx_range = if start_x <= 0 && end_x >= 0 {
+        start_x..=end_x
+    } else if start_x > 0 {
+        1..=end_x
     } else {
-        // target.end_x < 0
-        target.start_x..=-1
+        // end_x < 0
+        start_x..=-1
     };
-    let dy_range = if target.start_y <= 0 && target.end_y >= 0 {
-        target.start_y..=target.end_y.max(-target.start_y - 1)
-    } else if target.start_y > 0 {
-        1..=target.end_y
+    let dy_range = if start_y <= 0 && end_y >= 0 {
+        start_y..=end_y.max(-start_y - 1)
+    } else if start_y > 0 {
+        1..=end_y
     } else {
-        // target.end_y < 0
-        target.start_y..=-target.start_y - 1
+        // end_y < 0
+        start_y..=-start_y - 1
     };
 
     dx_range.flat_map(move |delta_x| {
@@ -105,6 +122,19 @@ impl Velocity {
 
         false
     }
+
+    /// Positions of the probe from launch until it enters the target.
+    fn trajectory(&self, target: &Target) -> Vec<(i64, i64)> {
+        let mut velocity = self.clone();
+        let mut position = Position::default();
+        let mut path = vec![(0, 0)];
+        while !position.in_target(target) {
+            position.update(&velocity);
+            velocity.update();
+            path.push((i64::from(position.x), i64::from(position.y)));
+        }
+        path
+    }
 }
 
 pub fn part1(target: &Target) -> Option<i32> {
@@ -117,6 +147,36 @@ pub fn part2(target: &Target) -> usize {
     viable_velocities(target).count()
 }
 
+/// Draws the target area and the trajectory of every probe that hits it,
+/// with up pointing up.
+pub fn render(target: &Target) -> Svg {
+    let min_x = i64::from(target.x_range.start().min(0));
+    let max_x = i64::from(target.x_range.end().max(0));
+    let min_y = i64::from(target.y_range.start());
+    let max_y = i64::from(part1(target).unwrap_or(0).max(0));
+    let mut svg =
+        Svg::new(min_x - 1, -max_y - 1, max_x - min_x + 3, max_y - min_y + 3)
+            .background("white");
+    svg.rect(
+        (
+            i64::from(target.x_range.start()),
+            -i64::from(target.y_range.end()),
+        ),
+        target.x_range.size() as i64,
+        target.y_range.size() as i64,
+        "lightgreen",
+    );
+    for velocity in viable_velocities(target) {
+        let path = velocity
+            .trajectory(target)
+            .into_iter()
+            .map(|(x, y)| (x, -y))
+            .collect::<Vec<_>>();
+        svg.polyline(&path, "steelblue");
+    }
+    svg
+}
+
 impl FromStr for Target {
     type Err = String;
 
@@ -136,14 +196,13 @@ impl FromStr for Target {
             })
         })
         .collect::<Result<Vec<_>, _>>()
-        .map(|vec| match *vec.as_slice() {
-            [start_x, end_x, start_y, end_y] => Self {
-                start_x,
-                end_x,
-                start_y,
-                end_y,
-            },
-            _ => unreachable!(),
+        .and_then(|vec| match *vec.as_slice() {
+            [start_x, end_x, start_y, end_y]
+                if start_x <= end_x && start_y <= end_y =>
+            {
+                Ok(Self::new(start_x, end_x, start_y, end_y))
+            }
+            _ => Err("Invalid target range".to_string()),
         })
     }
 }
@@ -154,12 +213,7 @@ mod tests {
 
     #[test]
     fn solves_sample() {
-        let target = Target {
-            start_x: 20,
-            end_x: 30,
-            start_y: -10,
-            end_y: -5,
-        };
+        let target = Target::new(20, 30, -10, -5);
         let velocity = Velocity::new(6, 9);
         assert_eq!(velocity.max_height(), 45);
         assert_eq!(part1(&target), Some(45));
@@ -168,12 +222,7 @@ mod tests {
 
     #[test]
     fn solves_negative_x_range() {
-        let target = Target {
-            start_x: -30,
-            end_x: -20,
-            start_y: -10,
-            end_y: -5,
-        };
+        let target = Target::new(-30, -20, -10, -5);
         let velocity = Velocity::new(-6, 9);
         assert_eq!(velocity.max_height(), 45);
         assert_eq!(part1(&target), Some(45));
@@ -182,12 +231,7 @@ mod tests {
 
     #[test]
     fn solves_input1() {
-        let target = Target {
-            start_x: 269,
-            end_x: 292,
-            start_y: -68,
-            end_y: -44,
-        };
+        let target = Target::new(269, 292, -68, -44);
         let velocity = Velocity::new(23, 67);
         assert_eq!(velocity.max_height(), 2278);
         assert_eq!(part1(&target), Some(2278));
@@ -196,12 +240,7 @@ mod tests {
 
     #[test]
     fn solves_input2() {
-        let target = Target {
-            start_x: 288,
-            end_x: 330,
-            start_y: -96,
-            end_y: -50,
-        };
+        let target = Target::new(288, 330, -96, -50);
         let velocity = Velocity::new(25, 95);
         assert_eq!(velocity.max_height(), 4560);
         assert_eq!(part1(&target), Some(4560));
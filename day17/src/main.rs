@@ -1,6 +1,6 @@
 use clap::{crate_description, App, Arg};
-use day17::{part1, part2, Target};
-use std::fs::read_to_string;
+use common::{read_or_example, Pretty, COLOR_CHOICES};
+use day17::{part1, part2, render, Target, EXAMPLE};
 use std::process::exit;
 
 fn main() {
@@ -8,30 +8,59 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("render")
+                .long("render")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Writes an SVG drawing of the puzzle to PATH"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
+    if let Some(path) = args.value_of("render") {
+        if let Err(err) = render(&input).save(path) {
+            println!("{}", pretty.error(format!("Failed to render: {}", err)));
+        }
+    }
+
     match part1(&input) {
-        Some(answer) => println!("Part 1: {}", &answer),
+        Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
         None => println!("Part 1: Not found"),
     }
-    println!("Part 2: {}", part2(&input));
+    println!("Part 2: {}", pretty.answer(part2(&input)));
 }
 
-fn read_input(filename: &str) -> Result<Target, String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())
-        .and_then(|s| s.parse())
+fn read_input(filename: Option<&str>) -> Result<Target, String> {
+    read_or_example(filename, EXAMPLE).and_then(|s| s.parse())
 }
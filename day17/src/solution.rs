@@ -0,0 +1,15 @@
+use crate::{part1, part2, Target};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let target: Target = input.parse()?;
+    report.maybe(part1(&target));
+    report.found(part2(&target));
+    Ok(())
+}
+
+register_solution!(day: 17, solve: solve);
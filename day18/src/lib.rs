@@ -1,140 +1,215 @@
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{NodeId, Tree};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::ops::Add;
 use std::str::FromStr;
-use SnailfishNumber::*;
+use Element::*;
+use Reduction::*;
 use StackElement::*;
 
-#[derive(Clone)]
-pub enum SnailfishNumber {
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+enum Element {
     Regular(u8),
-    Pair(Box<SnailfishNumber>, Box<SnailfishNumber>),
+    Pair,
+}
+
+/// A snailfish number stored as a tree where every `Pair` node has exactly
+/// two children, which lets reduction rewrite nodes in place.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SnailfishNumber {
+    tree: Tree<Element>,
+    root: NodeId,
+}
+
+/// A snailfish number after one of the actions taken while adding.
+pub enum Reduction<'a> {
+    Added(&'a SnailfishNumber),
+    Exploded(&'a SnailfishNumber),
+    Split(&'a SnailfishNumber),
+}
+
+impl Display for Reduction<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Added(number) => write!(f, "after addition: {}", number),
+            Exploded(number) => write!(f, "after explode:  {}", number),
+            Split(number) => write!(f, "after split:    {}", number),
+        }
+    }
 }
 
 impl Add for SnailfishNumber {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        Self::new(self, other).reduce()
+        self.add_observed(other, &mut |_| ())
     }
 }
 
 impl SnailfishNumber {
-    fn new(left: SnailfishNumber, right: SnailfishNumber) -> Self {
-        Pair(Box::new(left), Box::new(right))
+    fn add_observed<F>(mut self, other: Self, observe: &mut F) -> Self
+    where
+        F: FnMut(Reduction),
+    {
+        let right = self.tree.graft(&other.tree, other.root);
+        self.root = self.tree.add_with_children(Pair, vec![self.root, right]);
+        observe(Added(&self));
+        self.reduce(observe);
+        self
     }
 
-    fn reduce(self) -> Self {
-        let mut number = self;
+    fn reduce<F>(&mut self, observe: &mut F)
+    where
+        F: FnMut(Reduction),
+    {
         loop {
-            let (new_number, exploded, _, _) = number.explode(0);
-            number = new_number;
-            if !exploded {
-                let (new_number, split) = number.split();
-                number = new_number;
-                if !split {
-                    return number;
-                }
+            if self.explode() {
+                observe(Exploded(self));
+            } else if self.split() {
+                observe(Split(self));
+            } else {
+                break;
             }
         }
     }
 
-    fn explode(self, level: u8) -> (Self, bool, Option<u8>, Option<u8>) {
-        assert!(level <= 4);
-        match self {
-            Regular(number) => (Regular(number), false, None, None),
-            Pair(left, right) if level == 4 => match (*left, *right) {
-                (Regular(left_number), Regular(right_number)) => {
-                    (Regular(0), true, Some(left_number), Some(right_number))
-                }
-                _ => panic!("Pair found nested inside more than four pairs"),
-            },
-            Pair(left, right) => {
-                let (new_left, exploded, left_remain, right_remain) =
-                    left.explode(level + 1);
-                if let Some(number) = right_remain {
-                    return (
-                        Self::new(new_left, right.add_leftmost(number)),
-                        exploded,
-                        left_remain,
-                        None,
-                    );
-                } else if exploded {
-                    return (
-                        Self::new(new_left, *right),
-                        exploded,
-                        left_remain,
-                        None,
-                    );
-                }
+    fn regular_value(&self, id: NodeId) -> Option<u8> {
+        match self.tree.value(id) {
+            Regular(number) => Some(*number),
+            Pair => None,
+        }
+    }
 
-                let (new_right, exploded, left_remain, right_remain) =
-                    right.explode(level + 1);
-                if let Some(number) = left_remain {
-                    (
-                        Self::new(new_left.add_rightmost(number), new_right),
-                        exploded,
-                        None,
-                        right_remain,
-                    )
-                } else {
-                    (
-                        Self::new(new_left, new_right),
-                        exploded,
-                        None,
-                        right_remain,
-                    )
+    fn explode(&mut self) -> bool {
+        // Walk the regular numbers from left to right, remembering the last
+        // one seen, until a pair nested inside four pairs is found
+        let mut previous = None;
+        let mut exploding = None;
+        let mut stack = vec![(self.root, 0)];
+        while let Some((id, depth)) = stack.pop() {
+            match (self.tree.value(id), exploding) {
+                (Regular(_), Some((_, right_number))) => {
+                    self.add_to_regular(id, right_number);
+                    break;
+                }
+                (Regular(_), None) => previous = Some(id),
+                (Pair, None) if depth >= 4 => {
+                    let [left, right] = *self.tree.children(id) else {
+                        unreachable!()
+                    };
+                    match (self.regular_value(left), self.regular_value(right))
+                    {
+                        (Some(left_number), Some(right_number)) => {
+                            if let Some(previous) = previous {
+                                self.add_to_regular(previous, left_number);
+                            }
+                            exploding = Some((id, right_number));
+                        }
+                        _ => panic!(
+                            "Pair found nested inside more than four pairs"
+                        ),
+                    }
                 }
+                (Pair, _) => stack.extend(
+                    self.tree
+                        .children(id)
+                        .iter()
+                        .rev()
+                        .map(|&c| (c, depth + 1)),
+                ),
             }
         }
-    }
 
-    fn add_leftmost(self, number: u8) -> Self {
-        match self {
-            Regular(n) => Regular(n + number),
-            Pair(left, right) => Self::new(left.add_leftmost(number), *right),
+        match exploding {
+            Some((pair, _)) => {
+                *self.tree.value_mut(pair) = Regular(0);
+                self.tree.set_children(pair, Vec::new());
+                true
+            }
+            None => false,
         }
     }
 
-    fn add_rightmost(self, number: u8) -> Self {
-        match self {
-            Regular(n) => Regular(n + number),
-            Pair(left, right) => Self::new(*left, right.add_rightmost(number)),
+    fn add_to_regular(&mut self, id: NodeId, number: u8) {
+        if let Regular(n) = self.tree.value_mut(id) {
+            *n += number;
         }
     }
 
-    fn split(self) -> (Self, bool) {
-        match self {
-            Regular(number) if number >= 10 => {
-                let left = number / 2;
-                let right = number - left;
-                (Self::new(Regular(left), Regular(right)), true)
-            }
-            Regular(number) => (Regular(number), false),
-            Pair(left, right) => {
-                let (new_left, split) = left.split();
-                if split {
-                    (Self::new(new_left, *right), split)
-                } else {
-                    let (new_right, split) = right.split();
-                    (Self::new(new_left, new_right), split)
+    fn split(&mut self) -> bool {
+        let mut stack = vec![self.root];
+        while let Some(id) = stack.pop() {
+            match *self.tree.value(id) {
+                Regular(number) if number >= 10 => {
+                    let left = self.tree.add(Regular(number / 2));
+                    let right = self.tree.add(Regular(number - number / 2));
+                    *self.tree.value_mut(id) = Pair;
+                    self.tree.set_children(id, vec![left, right]);
+                    return true;
                 }
+                Regular(_) => (),
+                Pair => stack.extend(self.tree.children(id).iter().rev()),
             }
         }
+        false
     }
 
     fn magnitude(&self) -> u32 {
-        match self {
-            Regular(num) => *num as u32,
-            Pair(left, right) => 3 * left.magnitude() + 2 * right.magnitude(),
+        self.node_magnitude(self.root)
+    }
+
+    fn node_magnitude(&self, id: NodeId) -> u32 {
+        match (self.tree.value(id), self.tree.children(id)) {
+            (Regular(number), _) => *number as u32,
+            (Pair, [left, right]) => {
+                3 * self.node_magnitude(*left) + 2 * self.node_magnitude(*right)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn fmt_node(&self, id: NodeId, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.tree.value(id), self.tree.children(id)) {
+            (Regular(number), _) => write!(f, "{}", number),
+            (Pair, [left, right]) => {
+                write!(f, "[")?;
+                self.fmt_node(*left, f)?;
+                write!(f, ",")?;
+                self.fmt_node(*right, f)?;
+                write!(f, "]")
+            }
+            _ => unreachable!(),
         }
     }
 }
 
 pub fn part1(numbers: &[SnailfishNumber]) -> Option<u32> {
+    part1_observed(numbers, |_| ())
+}
+
+/// Same as [`part1`], calling `observe` with the running sum after every
+/// addition, explode and split.
+pub fn part1_observed<F>(
+    numbers: &[SnailfishNumber],
+    mut observe: F,
+) -> Option<u32>
+where
+    F: FnMut(Reduction),
+{
     numbers
         .iter()
         .cloned()
-        .reduce(|result, number| result + number)
+        .reduce(|result, number| result.add_observed(number, &mut observe))
         .map(|result| result.magnitude())
 }
 
@@ -156,21 +231,22 @@ pub fn part2(numbers: &[SnailfishNumber]) -> Option<u32> {
 enum StackElement {
     OpenBracket,
     Comma,
-    Number(SnailfishNumber),
+    Number(NodeId),
 }
 
 impl FromStr for SnailfishNumber {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tree = Tree::new();
         let mut stack = Vec::new();
         for (ch, pos) in s.trim().chars().zip(1..) {
             match ch {
                 '[' => stack.push(OpenBracket),
                 ',' => stack.push(Comma),
-                d if d.is_digit(10) => {
-                    let number = u8::try_from(d.to_digit(10).unwrap()).unwrap();
-                    stack.push(Number(Regular(number)));
+                '0'..='9' => {
+                    let number = ch as u8 - b'0';
+                    stack.push(Number(tree.add(Regular(number))));
                 }
                 ']' => {
                     match (stack.pop(), stack.pop(), stack.pop(), stack.pop()) {
@@ -180,7 +256,8 @@ impl FromStr for SnailfishNumber {
                             Some(Number(l)),
                             Some(OpenBracket),
                         ) => {
-                            stack.push(Number(SnailfishNumber::new(l, r)));
+                            let pair = tree.add_with_children(Pair, vec![l, r]);
+                            stack.push(Number(pair));
                         }
                         _ => return Err("Invalid snailfish number".to_string()),
                     }
@@ -195,7 +272,7 @@ impl FromStr for SnailfishNumber {
         }
 
         match (stack.pop(), stack.pop()) {
-            (Some(Number(number)), None) => Ok(number),
+            (Some(Number(root)), None) => Ok(Self { tree, root }),
             _ => Err("Invalid snailfish number".to_string()),
         }
     }
@@ -203,9 +280,17 @@ impl FromStr for SnailfishNumber {
 
 impl Display for SnailfishNumber {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Regular(num) => write!(f, "{}", num),
-            Pair(left, right) => write!(f, "[{},{}]", left, right),
-        }
+        self.fmt_node(self.root, f)
     }
 }
+
+pub fn parse_input(input: &str) -> Result<Vec<SnailfishNumber>, String> {
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect()
+}
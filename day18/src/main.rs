@@ -1,7 +1,8 @@
 use clap::{crate_description, App, Arg};
-use day18::{part1, part2, SnailfishNumber};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use common::{read_or_example, Pretty, COLOR_CHOICES};
+use day18::{
+    parse_input, part1_observed, part2, Reduction, SnailfishNumber, EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
@@ -9,41 +10,62 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("explain")
+                .long("explain")
+                .help("Prints the intermediate steps taken to reach part 1"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    match part1(&input) {
-        Some(answer) => println!("Part 1: {}", &answer),
+    let explain = args.is_present("explain");
+    let observe = |event: Reduction| {
+        if explain {
+            println!("{}", event);
+        }
+    };
+
+    match part1_observed(&input, observe) {
+        Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
         None => println!("Part 1: Not found"),
     }
     match part2(&input) {
-        Some(answer) => println!("Part 2: {}", &answer),
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
         None => println!("Part 2: Not found"),
     }
 }
 
-fn read_input(filename: &str) -> Result<Vec<SnailfishNumber>, String> {
-    let input_file = File::open(filename).map_err(|err| err.to_string())?;
-
-    BufReader::new(input_file)
-        .lines()
-        .zip(1..)
-        .map(|(line, line_num)| {
-            line.map_err(|err| (line_num, err.to_string()))
-                .and_then(|value| value.parse().map_err(|err| (line_num, err)))
-        })
-        .collect::<Result<_, _>>()
-        .map_err(|(line_num, err)| format!("Line {}: {}", line_num, err))
+fn read_input(filename: Option<&str>) -> Result<Vec<SnailfishNumber>, String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
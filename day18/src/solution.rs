@@ -0,0 +1,15 @@
+use crate::{parse_input, part1, part2};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let numbers = parse_input(input)?;
+    report.maybe(part1(&numbers));
+    report.maybe(part2(&numbers));
+    Ok(())
+}
+
+register_solution!(day: 18, solve: solve);
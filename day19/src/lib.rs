@@ -1,19 +1,18 @@
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::Plane::{self, *};
+use common::Point3 as Coordinates;
+use common::SearchStats;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
-use Plane::*;
 
-#[derive(Clone, Default, Eq, Hash, PartialEq)]
-pub struct Coordinates {
-    x: i32,
-    y: i32,
-    z: i32,
-}
-
-enum Plane {
-    XY,
-    XZ,
-    YZ,
-}
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
 
 /// Return an iterator of planes which rotates a 3-D set of coordinates
 /// around all possible orientations; while there are 24 unique orientations,
@@ -27,45 +26,15 @@ fn all_rotations() -> impl Iterator<Item = Plane> {
     .into_iter()
 }
 
-impl Coordinates {
-    fn distance(&self, other: &Self) -> Self {
-        let x = other.x - self.x;
-        let y = other.y - self.y;
-        let z = other.z - self.z;
-        Self { x, y, z }
-    }
-
-    fn rotate(&self, plane: &Plane) -> Self {
-        let (x, y, z) = match plane {
-            Plane::XY => (self.y, -self.x, self.z),
-            Plane::XZ => (self.z, self.y, -self.x),
-            Plane::YZ => (self.x, self.z, -self.y),
-        };
-        Self { x, y, z }
-    }
-
-    fn move_by(&self, shift: &Self) -> Self {
-        let x = self.x + shift.x;
-        let y = self.y + shift.y;
-        let z = self.z + shift.z;
-        Self { x, y, z }
-    }
-
-    fn manhattan_distance(&self, other: &Self) -> i32 {
-        (other.x - self.x).abs()
-            + (other.y - self.y).abs()
-            + (other.z - self.z).abs()
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Scanner {
     position: Coordinates,
     beacons: HashSet<Coordinates>,
 }
 
 impl Scanner {
-    fn rotate(&self, plane: &Plane) -> Self {
+    fn rotate(&self, plane: Plane) -> Self {
         let position = self.position.rotate(plane);
         let beacons = self
             .beacons
@@ -75,46 +44,53 @@ impl Scanner {
         Self { position, beacons }
     }
 
-    fn move_by(&self, shift: &Coordinates) -> Self {
-        let position = self.position.move_by(shift);
-        let beacons = self
-            .beacons
-            .iter()
-            .map(|beacon| beacon.move_by(shift))
-            .collect();
+    fn move_by(&self, shift: Coordinates) -> Self {
+        let position = self.position + shift;
+        let beacons =
+            self.beacons.iter().map(|beacon| *beacon + shift).collect();
         Self { position, beacons }
     }
 
-    fn can_align_to(&self, other: &Self) -> Option<Coordinates> {
+    fn can_align_to(
+        &self,
+        other: &Self,
+        stats: &mut SearchStats,
+    ) -> Option<Coordinates> {
         let mut counter = HashMap::new();
-        for distance in self.beacons.iter().flat_map(|my_beacon| {
-            other
-                .beacons
-                .iter()
-                .map(|their_beacon| my_beacon.distance(their_beacon))
-        }) {
-            if *counter
-                .entry(distance.clone())
-                .and_modify(|count| *count += 1)
-                .or_insert(1)
-                >= 12
-            {
-                return Some(distance);
-            }
-        }
-        None
+        let shift = self
+            .beacons
+            .iter()
+            .flat_map(|my_beacon| {
+                other
+                    .beacons
+                    .iter()
+                    .map(|their_beacon| *their_beacon - *my_beacon)
+            })
+            .find(|distance| {
+                *counter
+                    .entry(*distance)
+                    .and_modify(|count| *count += 1)
+                    .or_insert(1)
+                    >= 12
+            });
+        stats.record_visited(counter.len());
+        shift
     }
 
-    fn align_to(&self, other: &Self) -> Option<Scanner> {
+    fn align_to(
+        &self,
+        other: &Self,
+        stats: &mut SearchStats,
+    ) -> Option<Scanner> {
         let mut scanner = self.clone();
         let mut planes = all_rotations();
         loop {
-            if let Some(shift) = scanner.can_align_to(other) {
-                return Some(scanner.move_by(&shift));
+            if let Some(shift) = scanner.can_align_to(other, stats) {
+                return Some(scanner.move_by(shift));
             }
 
             if let Some(plane) = planes.next() {
-                scanner = scanner.rotate(&plane);
+                scanner = scanner.rotate(plane);
             } else {
                 break;
             }
@@ -124,15 +100,29 @@ impl Scanner {
 }
 
 pub fn solve(scanners: &[Scanner]) -> Option<(usize, i32)> {
+    solve_with_stats(scanners, &mut SearchStats::default())
+}
+
+/// Same as [`solve`], counting aligned scanners as expanded states, beacons
+/// seen by more than one scanner as pruned duplicates and the offsets
+/// tallied while aligning a pair of scanners as visited states.
+pub fn solve_with_stats(
+    scanners: &[Scanner],
+    stats: &mut SearchStats,
+) -> Option<(usize, i32)> {
     let mut aligned = Vec::new();
     let mut aligning = Vec::from([scanners.first()?.clone()]);
     let mut pending = scanners.iter().skip(1).collect::<Vec<_>>();
 
     while let Some(aligning_scanner) = aligning.pop() {
+        stats.expanded += 1;
         let mut skipped = Vec::new();
         while let Some(pending_scanner) = pending.pop() {
-            if let Some(scanner) = pending_scanner.align_to(&aligning_scanner) {
+            if let Some(scanner) =
+                pending_scanner.align_to(&aligning_scanner, stats)
+            {
                 aligning.push(scanner);
+                stats.record_frontier(aligning.len());
             } else {
                 skipped.push(pending_scanner);
             }
@@ -146,11 +136,12 @@ pub fn solve(scanners: &[Scanner]) -> Option<(usize, i32)> {
         return None;
     }
 
-    let unique_beacons = aligned
+    let all_beacons = aligned
         .iter()
         .flat_map(|scanner| scanner.beacons.iter())
-        .collect::<HashSet<_>>()
-        .len();
+        .collect::<Vec<_>>();
+    let unique_beacons = all_beacons.iter().collect::<HashSet<_>>().len();
+    stats.duplicates += (all_beacons.len() - unique_beacons) as u64;
 
     let max_distance = aligned
         .iter()
@@ -165,21 +156,15 @@ pub fn solve(scanners: &[Scanner]) -> Option<(usize, i32)> {
     Some((unique_beacons, max_distance))
 }
 
-impl FromStr for Coordinates {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.split(',')
-            .map(|num| {
-                num.trim().parse::<i32>().map_err(|err| {
-                    format!("Invalid coordinate '{}': {}", num, err)
-                })
-            })
-            .collect::<Result<Vec<_>, _>>()
-            .and_then(|vec| match vec[..] {
-                [x, y, z] => Ok(Coordinates { x, y, z }),
-                _ => Err(format!("Invalid coordinates '{}'", s)),
-            })
+impl Display for Scanner {
+    /// Writes the beacons relative to the scanner, which is how they're
+    /// parsed, but without the scanner number.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "--- scanner ---")?;
+        for beacon in &self.beacons {
+            writeln!(f, "{}", *beacon - self.position)?;
+        }
+        Ok(())
     }
 }
 
@@ -201,3 +186,33 @@ impl FromStr for Scanner {
         Ok(Self { position, beacons })
     }
 }
+
+pub fn parse_input(input: &str) -> Result<Vec<Scanner>, String> {
+    input
+        .split("\n\n")
+        .map(|scanner| scanner.parse())
+        .collect::<Result<Vec<_>, _>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::hash_set;
+    use proptest::prelude::*;
+
+    fn scanner() -> impl Strategy<Value = Scanner> {
+        let beacon = (any::<i32>(), any::<i32>(), any::<i32>())
+            .prop_map(|(x, y, z)| Coordinates::new(x, y, z));
+        hash_set(beacon, 0..30).prop_map(|beacons| Scanner {
+            position: Coordinates::default(),
+            beacons,
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn display_round_trips(scanner in scanner()) {
+            prop_assert_eq!(scanner.to_string().parse(), Ok(scanner));
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use clap::{crate_description, App, Arg};
-use day19::{solve, Scanner};
-use std::fs::read_to_string;
+use common::{read_or_example, Pretty, SearchStats, COLOR_CHOICES};
+use day19::{parse_input, solve_with_stats, Scanner, EXAMPLE};
 use std::process::exit;
 
 fn main() {
@@ -8,33 +8,61 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("stats")
+                .long("stats")
+                .help("Prints search statistics after each answer"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    match solve(&input) {
+    let mut stats = SearchStats::default();
+    match solve_with_stats(&input, &mut stats) {
         Some((part1, part2)) => {
-            println!("Part 1: {}\nPart 2: {}", part1, part2)
+            println!(
+                "Part 1: {}\nPart 2: {}",
+                pretty.answer(part1),
+                pretty.answer(part2)
+            )
         }
         None => println!("Part 1: Not found\nPart 2: Not found"),
     }
+    if args.is_present("stats") {
+        println!("Stats: {}", stats);
+    }
 }
 
-fn read_input(filename: &str) -> Result<Vec<Scanner>, String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())?
-        .split("\n\n")
-        .map(|scanner| scanner.parse())
-        .collect::<Result<Vec<_>, _>>()
+fn read_input(filename: Option<&str>) -> Result<Vec<Scanner>, String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
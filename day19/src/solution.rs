@@ -0,0 +1,16 @@
+use crate::parse_input;
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let scanners = parse_input(input)?;
+    let solution = crate::solve(&scanners);
+    report.maybe(solution.map(|(beacons, _)| beacons));
+    report.maybe(solution.map(|(_, distance)| distance));
+    Ok(())
+}
+
+register_solution!(day: 19, solve: solve);
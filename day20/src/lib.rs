@@ -1,38 +1,34 @@
-use std::collections::BTreeSet;
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{Animate, Grid, Svg, Visualize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use serde_with::{As, Same};
 use std::convert::{TryFrom, TryInto};
 use std::fmt::{Display, Formatter};
+use std::iter::successors;
 use std::str::FromStr;
 
 use PixelState::*;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
 #[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum PixelState {
     Dark,
     Light,
 }
 
-impl PixelState {
-    fn reverse(&self) -> Self {
-        match *self {
-            Light => Dark,
-            Dark => Light,
-        }
-    }
-}
-
-#[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub struct Pixel {
-    x: i32,
-    y: i32,
-}
-
-impl Pixel {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
-    }
-}
-
-pub struct EnhanceAlgo([PixelState; 512]);
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct EnhanceAlgo(
+    #[cfg_attr(feature = "serde", serde(with = "As::<[Same; 512]>"))]
+    [PixelState; 512],
+);
 
 impl EnhanceAlgo {
     fn pixel_state(&self, index: usize) -> PixelState {
@@ -41,28 +37,21 @@ impl EnhanceAlgo {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Image {
-    pixel_state: PixelState,
-    pixels: BTreeSet<Pixel>,
+    background: PixelState,
+    pixels: Grid<PixelState>,
 }
 
 impl Image {
-    fn new(pixel_state: PixelState, pixels: BTreeSet<Pixel>) -> Self {
-        Self {
-            pixel_state,
-            pixels,
-        }
+    fn get_pixel_state(&self, x: i64, y: i64) -> PixelState {
+        self.pixels
+            .get_signed(x, y)
+            .copied()
+            .unwrap_or(self.background)
     }
 
-    fn get_pixel_state(&self, x: i32, y: i32) -> PixelState {
-        if self.pixels.contains(&Pixel::new(x, y)) {
-            self.pixel_state
-        } else {
-            self.pixel_state.reverse()
-        }
-    }
-
-    fn get_pixel_index(&self, x: i32, y: i32) -> usize {
+    fn get_pixel_index(&self, x: i64, y: i64) -> usize {
         (y - 1..=y + 1)
             .flat_map(move |ny| {
                 (x - 1..=x + 1)
@@ -71,66 +60,125 @@ impl Image {
             .fold(0, |acc, bit| acc * 2 + bit)
     }
 
-    fn get_boundaries(&self) -> (Pixel, Pixel) {
-        let min_x = self.pixels.iter().map(|pixel| pixel.x).min().unwrap_or(0);
-        let max_x = self.pixels.iter().map(|pixel| pixel.x).max().unwrap_or(0);
-        let min_y = self.pixels.iter().map(|pixel| pixel.y).min().unwrap_or(0);
-        let max_y = self.pixels.iter().map(|pixel| pixel.y).max().unwrap_or(0);
-        (
-            Pixel::new(min_x - 1, min_y - 1),
-            Pixel::new(max_x + 1, max_y + 1),
-        )
-    }
-
     fn enhance(&self, algo: &EnhanceAlgo) -> Self {
-        let reverse = (self.pixel_state == Light
-            && algo.pixel_state(0) == Light)
-            || (self.pixel_state == Dark && algo.pixel_state(511) == Dark);
-
-        let pixel_state = if reverse {
-            self.pixel_state.reverse()
-        } else {
-            self.pixel_state
+        // The infinite background is either all dark or all lit, so every
+        // background pixel maps to the first or last algorithm entry
+        let background = match self.background {
+            Dark => algo.pixel_state(0),
+            Light => algo.pixel_state(511),
         };
 
-        let (min, max) = self.get_boundaries();
-
-        let pixels = (min.y..=max.y)
-            .flat_map(move |y| {
-                (min.x..=max.x).filter_map(move |x| {
-                    if algo.pixel_state(self.get_pixel_index(x, y))
-                        == pixel_state
-                    {
-                        Some(Pixel::new(x, y))
-                    } else {
-                        None
-                    }
-                })
-            })
-            .collect();
+        // The image grows by one pixel on each side at every step
+        let pixels = Grid::from_fn(
+            self.pixels.width() + 2,
+            self.pixels.height() + 2,
+            |x, y| {
+                algo.pixel_state(
+                    self.get_pixel_index(x as i64 - 1, y as i64 - 1),
+                )
+            },
+        );
 
-        Self {
-            pixel_state,
-            pixels,
-        }
+        Self { background, pixels }
     }
 
     fn count_lit_pixels(&self) -> Option<usize> {
-        if self.pixel_state == Light {
-            Some(self.pixels.len())
+        if self.background == Dark {
+            Some(
+                self.pixels
+                    .values()
+                    .filter(|&&state| state == Light)
+                    .count(),
+            )
         } else {
             None
         }
     }
 }
 
+/// Repeated enhancement of an image.
+pub struct Enhancement<'a> {
+    algo: &'a EnhanceAlgo,
+    image: &'a Image,
+    steps: usize,
+}
+
+impl<'a> Enhancement<'a> {
+    pub fn new(algo: &'a EnhanceAlgo, image: &'a Image, steps: usize) -> Self {
+        Self { algo, image, steps }
+    }
+
+    /// Draws the image after every step, one square per lit pixel.
+    pub fn render(&self) -> Svg {
+        let image = (0..self.steps)
+            .fold(self.image.clone(), |image, _| image.enhance(self.algo));
+        let (width, height) = (image.pixels.width(), image.pixels.height());
+        let (background, foreground) = match image.background {
+            Dark => ("black", "white"),
+            Light => ("white", "black"),
+        };
+        let mut svg =
+            Svg::new(0, 0, width as i64, height as i64).background(background);
+        for y in 0..height {
+            for x in 0..width {
+                if image.pixels.get(x, y) != Some(&image.background) {
+                    svg.rect((x as i64, y as i64), 1, 1, foreground);
+                }
+            }
+        }
+        svg
+    }
+}
+
+impl Visualize for Enhancement<'_> {
+    fn frames(&self) -> impl Iterator<Item = String> + '_ {
+        successors(Some(self.image.clone()), |image| {
+            Some(image.enhance(self.algo))
+        })
+        .take(self.steps + 1)
+        .map(|image| image.to_string())
+    }
+}
+
+impl Animate for Enhancement<'_> {
+    /// Dark and light pixels.
+    const PALETTE: &'static [[u8; 3]] = &[[0, 0, 0], [255, 255, 255]];
+
+    /// Every frame shows the area of the last one, so that the image stays
+    /// in place as it grows.
+    fn images(&self) -> impl Iterator<Item = Grid<u8>> + '_ {
+        let width = self.image.pixels.width() + 2 * self.steps;
+        let height = self.image.pixels.height() + 2 * self.steps;
+        successors(Some(self.image.clone()), |image| {
+            Some(image.enhance(self.algo))
+        })
+        .take(self.steps + 1)
+        .zip((0..=self.steps as i64).rev())
+        .map(move |(image, margin)| {
+            Grid::from_fn(width, height, |x, y| {
+                image.get_pixel_state(x as i64 - margin, y as i64 - margin)
+                    as u8
+            })
+        })
+    }
+}
+
 pub fn part1(algo: &EnhanceAlgo, initial_image: &Image) -> Option<usize> {
-    initial_image.enhance(algo).enhance(algo).count_lit_pixels()
+    lit_after(algo, initial_image, 2)
 }
 
 pub fn part2(algo: &EnhanceAlgo, initial_image: &Image) -> Option<usize> {
+    lit_after(algo, initial_image, 50)
+}
+
+/// Number of lit pixels after enhancing the image the given number of times.
+pub fn lit_after(
+    algo: &EnhanceAlgo,
+    initial_image: &Image,
+    steps: usize,
+) -> Option<usize> {
     let mut image = initial_image.clone();
-    for _ in 1..=50 {
+    for _ in 1..=steps {
         image = image.enhance(algo);
     }
     image.count_lit_pixels()
@@ -176,24 +224,22 @@ impl FromStr for Image {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         s.trim()
             .lines()
-            .zip(0..)
-            .flat_map(move |(line, y)| {
-                line.trim().chars().zip(0..).map(move |(ch, x)| {
-                    PixelState::try_from(ch)
-                        .map_err(|_| format!("Invalid pixel '{}' in image", ch))
-                        .map(|state| (state, Pixel::new(x, y)))
-                })
+            .map(|line| {
+                line.trim()
+                    .chars()
+                    .map(|ch| {
+                        PixelState::try_from(ch).map_err(|_| {
+                            format!("Invalid pixel '{}' in image", ch)
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
             })
             .collect::<Result<Vec<_>, _>>()
-            .map(|vec| {
-                vec.into_iter()
-                    .filter_map(|(state, pixel)| match state {
-                        Light => Some(pixel),
-                        Dark => None,
-                    })
-                    .collect::<BTreeSet<_>>()
+            .and_then(Grid::from_rows)
+            .map(|pixels| Self {
+                background: Dark,
+                pixels,
             })
-            .map(|pixels| Self::new(Light, pixels))
     }
 }
 
@@ -208,13 +254,22 @@ impl Display for PixelState {
 
 impl Display for Image {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let (min, max) = self.get_boundaries();
-        for y in min.y..=max.y {
-            for x in min.x..=max.x {
-                write!(f, "{}", self.get_pixel_state(x, y))?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+        write!(f, "{}", self.pixels)
     }
 }
+
+pub fn parse_input(input: &str) -> Result<(EnhanceAlgo, Image), String> {
+    let mut blocks = input.split("\n\n");
+
+    let algo = blocks
+        .next()
+        .ok_or_else(|| "Missing algorithm line".to_string())
+        .and_then(|line| line.parse())?;
+
+    let image = blocks
+        .next()
+        .ok_or_else(|| "Missing image".to_string())
+        .and_then(|line| line.parse())?;
+
+    Ok((algo, image))
+}
@@ -1,51 +1,125 @@
 use clap::{crate_description, App, Arg};
-use day20::{part1, part2, EnhanceAlgo, Image};
-use std::fs::read_to_string;
+use common::{read_or_example, Animate, Pretty, Visualize, COLOR_CHOICES};
+use day20::{
+    parse_input, part1, part2, EnhanceAlgo, Enhancement, Image, EXAMPLE,
+};
+use std::path::Path;
 use std::process::exit;
+use std::time::Duration;
 
 fn main() {
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("visualize")
+                .long("visualize")
+                .help("Animates the puzzle on the terminal before solving it"),
+        )
+        .arg(
+            Arg::with_name("delay")
+                .long("delay")
+                .takes_value(true)
+                .default_value("100")
+                .validator(|value| {
+                    value
+                        .parse::<u64>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .help("Milliseconds between animation frames"),
+        )
+        .arg(
+            Arg::with_name("render")
+                .long("render")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Writes an SVG drawing of the puzzle to PATH"),
+        )
+        .arg(
+            Arg::with_name("export")
+                .long("export")
+                .takes_value(true)
+                .value_name("PATH")
+                .help(
+                    "Writes every step to PATH as an animated GIF if it ends \
+                    in .gif, or else as PNG frames in directory PATH",
+                ),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let (algo, image) = match read_input(args.value_of("INPUT").unwrap()) {
+    let (algo, image) = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
+    if args.is_present("visualize") {
+        let delay = args.value_of("delay").unwrap().parse().unwrap();
+        if let Err(err) = Enhancement::new(&algo, &image, 50)
+            .visualize(Duration::from_millis(delay))
+        {
+            println!(
+                "{}",
+                pretty.error(format!("Failed to visualize: {}", err))
+            );
+        }
+    }
+
+    if let Some(path) = args.value_of("export") {
+        let delay = args.value_of("delay").unwrap().parse().unwrap();
+        if let Err(err) = Enhancement::new(&algo, &image, 50)
+            .export(Path::new(path), Duration::from_millis(delay))
+        {
+            println!("{}", pretty.error(format!("Failed to export: {}", err)));
+        }
+    }
+
+    if let Some(path) = args.value_of("render") {
+        if let Err(err) =
+            Enhancement::new(&algo, &image, 50).render().save(path)
+        {
+            println!("{}", pretty.error(format!("Failed to render: {}", err)));
+        }
+    }
+
     match part1(&algo, &image) {
-        Some(answer) => println!("Part 1: {}", &answer),
+        Some(answer) => println!("Part 1: {}", pretty.answer(answer)),
         None => println!("Part 1: Not found"),
     }
     match part2(&algo, &image) {
-        Some(answer) => println!("Part 2: {}", &answer),
+        Some(answer) => println!("Part 2: {}", pretty.answer(answer)),
         None => println!("Part 2: Not found"),
     }
 }
 
-fn read_input(filename: &str) -> Result<(EnhanceAlgo, Image), String> {
-    let contents = read_to_string(filename).map_err(|err| err.to_string())?;
-    let mut blocks = contents.split("\n\n");
-
-    let algo = blocks
-        .next()
-        .ok_or_else(|| "Missing algorithm line".to_string())
-        .and_then(|line| line.parse())?;
-
-    let image = blocks
-        .next()
-        .ok_or_else(|| "Missing image".to_string())
-        .and_then(|line| line.parse())?;
-
-    Ok((algo, image))
+fn read_input(filename: Option<&str>) -> Result<(EnhanceAlgo, Image), String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
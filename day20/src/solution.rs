@@ -0,0 +1,19 @@
+use crate::{lit_after, parse_input};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let (algo, image) = parse_input(input)?;
+    report.maybe(lit_after(&algo, &image, params.get("part1_steps")?));
+    report.maybe(lit_after(&algo, &image, params.get("part2_steps")?));
+    Ok(())
+}
+
+register_solution!(
+    day: 20,
+    params: &[("part1_steps", 2), ("part2_steps", 50)],
+    solve: solve,
+);
@@ -1,6 +1,27 @@
-use std::cmp::max;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::max;
 use Player::*;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
+/// The number of universes in which a player wins.
+#[cfg(not(feature = "bigint"))]
+pub type Count = u64;
+#[cfg(feature = "bigint")]
+pub type Count = u128;
+
 #[derive(Clone, Eq, PartialEq)]
 enum Player {
     Player1,
@@ -38,10 +59,10 @@ impl DeterministicGame {
         }
     }
 
-    fn looser_score(&self) -> Option<u64> {
-        if self.score1 >= 1_000 {
+    fn looser_score(&self, target: u64) -> Option<u64> {
+        if self.score1 >= target {
             Some(self.score2)
-        } else if self.score2 >= 1_000 {
+        } else if self.score2 >= target {
             Some(self.score1)
         } else {
             None
@@ -50,11 +71,17 @@ impl DeterministicGame {
 }
 
 pub fn part1(player1: u64, player2: u64) -> u64 {
+    deterministic_game(player1, player2, 1_000)
+}
+
+/// Plays with the deterministic die until a player reaches the target score
+/// and returns the looser's score times the number of rolls.
+pub fn deterministic_game(player1: u64, player2: u64, target: u64) -> u64 {
     let mut game = DeterministicGame::new(player1, player2);
 
     for (die_sum, rolls) in (6..).step_by(9).zip((3..).step_by(3)) {
         game.play(die_sum);
-        if let Some(looser_score) = game.looser_score() {
+        if let Some(looser_score) = game.looser_score(target) {
             return rolls * looser_score;
         }
     }
@@ -69,7 +96,7 @@ struct QuantumGame {
     score1: u64,
     score2: u64,
     turn: Player,
-    count: u64,
+    count: Count,
 }
 
 impl QuantumGame {
@@ -84,7 +111,10 @@ impl QuantumGame {
         }
     }
 
-    fn play(&self, die_sum: u64, count: u64) -> Self {
+    /// The game after the player whose turn it is moves by `die_sum`, in
+    /// `count` times as many universes, or `None` if they don't fit in a
+    /// [`Count`].
+    fn play(&self, die_sum: u64, count: Count) -> Option<Self> {
         let mut new_state = self.clone();
         if self.turn == Player1 {
             new_state.position1 = (new_state.position1 + die_sum) % 10;
@@ -95,14 +125,14 @@ impl QuantumGame {
             new_state.score2 += new_state.position2 + 1;
             new_state.turn = Player1;
         }
-        new_state.count *= count;
-        new_state
+        new_state.count = new_state.count.checked_mul(count)?;
+        Some(new_state)
     }
 
-    fn winner(&self) -> Option<Player> {
-        if self.score1 >= 21 {
+    fn winner(&self, target: u64) -> Option<Player> {
+        if self.score1 >= target {
             Some(Player1)
-        } else if self.score2 >= 21 {
+        } else if self.score2 >= target {
             Some(Player2)
         } else {
             None
@@ -110,9 +140,17 @@ impl QuantumGame {
     }
 }
 
-pub fn part2(player1: u64, player2: u64) -> u64 {
-    let mut wins1 = 0;
-    let mut wins2 = 0;
+pub fn part2(player1: u64, player2: u64) -> Count {
+    // Under 2^51 universes whatever the starting positions
+    quantum_game(player1, player2, 21).unwrap()
+}
+
+/// Plays with the Dirac die until a player reaches the target score and
+/// returns the number of universes in which the most successful player wins,
+/// or `None` if there are too many universes to count.
+pub fn quantum_game(player1: u64, player2: u64, target: u64) -> Option<Count> {
+    let mut wins1: Count = 0;
+    let mut wins2: Count = 0;
     let mut stack = vec![QuantumGame::new(player1, player2)];
 
     // Each player rolls the 3-face die three times. The sum of the three rolls
@@ -124,14 +162,50 @@ pub fn part2(player1: u64, player2: u64) -> u64 {
         for (die_sum, count) in
             [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)]
         {
-            let new_game = game.play(die_sum, count);
-            match new_game.winner() {
-                Some(winner) if winner == Player1 => wins1 += new_game.count,
-                Some(_winner) => wins2 += new_game.count,
+            let new_game = game.play(die_sum, count)?;
+            match new_game.winner(target) {
+                Some(Player1) => wins1 = wins1.checked_add(new_game.count)?,
+                Some(_winner) => wins2 = wins2.checked_add(new_game.count)?,
                 _ => stack.push(new_game),
             }
         }
     }
 
-    max(wins1, wins2)
+    Some(max(wins1, wins2))
+}
+
+/// Parses each player's starting position, which must be on the track,
+/// between 1 and 10.
+pub fn parse_input(input: &str) -> Result<(u64, u64), String> {
+    input
+        .trim()
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            line.trim()
+                .split_once(':')
+                .ok_or_else(|| format!("Invalid input line '{}'", line))
+                .and_then(|(_, num)| {
+                    num.trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid number '{}'", num))
+                })
+                .and_then(|position| {
+                    if (1..=10).contains(&position) {
+                        Ok(position)
+                    } else {
+                        Err(format!(
+                            "Position {} must be between 1 and 10",
+                            position
+                        ))
+                    }
+                })
+                .map_err(|err| format!("Line {}: {}", index + 1, err))
+        })
+        .collect::<Result<Vec<u64>, _>>()
+        .and_then(|vec| {
+            vec.try_into()
+                .map_err(|_| "Input must have exactly two lines".to_string())
+        })
+        .map(|numbers: [u64; 2]| (numbers[0], numbers[1]))
 }
@@ -1,6 +1,6 @@
 use clap::{crate_description, App, Arg};
-use day21::{part1, part2};
-use std::fs::read_to_string;
+use common::{read_or_example, Pretty, COLOR_CHOICES};
+use day21::{parse_input, part1, part2, EXAMPLE};
 use std::process::exit;
 
 fn main() {
@@ -8,44 +8,44 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let (player1, player2) = match read_input(args.value_of("INPUT").unwrap()) {
+    let (player1, player2) = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(player1, player2));
-    println!("Part 2: {}", part2(player1, player2));
+    println!("Part 1: {}", pretty.answer(part1(player1, player2)));
+    println!("Part 2: {}", pretty.answer(part2(player1, player2)));
 }
 
-fn read_input(filename: &str) -> Result<(u64, u64), String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())?
-        .trim()
-        .lines()
-        .map(|line| {
-            line.trim()
-                .split_once(':')
-                .ok_or_else(|| format!("Invalid input line '{}'", line))
-                .and_then(|(_, num)| {
-                    num.trim()
-                        .parse()
-                        .map_err(|_| format!("Invalid number '{}'", num))
-                })
-        })
-        .collect::<Result<Vec<u64>, _>>()
-        .and_then(|vec| {
-            vec.try_into()
-                .map_err(|_| "Input must have exactly two lines".to_string())
-        })
-        .map(|numbers: [u64; 2]| (numbers[0], numbers[1]))
+fn read_input(filename: Option<&str>) -> Result<(u64, u64), String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
@@ -0,0 +1,24 @@
+use crate::{deterministic_game, parse_input, quantum_game};
+use alloc::string::String;
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let (player1, player2) = parse_input(input)?;
+    report.found(deterministic_game(
+        player1,
+        player2,
+        params.get("part1_target")?,
+    ));
+    report.maybe(quantum_game(player1, player2, params.get("part2_target")?));
+    Ok(())
+}
+
+register_solution!(
+    day: 21,
+    params: &[("part1_target", 1000), ("part2_target", 21)],
+    solve: solve,
+);
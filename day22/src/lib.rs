@@ -1,249 +1,195 @@
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{Cuboid, CuboidSet, Interval};
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::cmp::{max, min};
-use std::collections::HashSet;
-use std::mem::swap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
+use Backend::*;
 use Operation::*;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample2");
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Operation {
     On,
     Off,
 }
 
-#[derive(Clone, Copy)]
-pub struct Range {
-    start: i32,
-    end: i32,
-}
-
-#[derive(Clone)]
-pub struct Region {
-    x_range: Range,
-    y_range: Range,
-    z_range: Range,
-}
-
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Step {
     operation: Operation,
-    region: Region,
+    region: Cuboid,
 }
 
-#[derive(Clone, Eq, Hash, PartialEq)]
-struct Coord {
-    x: i32,
-    y: i32,
-    z: i32,
+/// Ways of keeping track of the lit cubes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Backend {
+    /// Splits the lit cuboids so that they never overlap.
+    #[default]
+    Splitting,
+    /// Keeps every overlap between regions, counted positively or
+    /// negatively so that no cube is counted twice.
+    InclusionExclusion,
 }
 
-impl Range {
-    fn try_from_bounds(start: i32, end: i32) -> Option<Self> {
-        if end >= start {
-            Some(Self { start, end })
-        } else {
-            None
-        }
-    }
-
-    fn len(&self) -> u64 {
-        u64::try_from(self.end - self.start + 1).unwrap_or(0)
-    }
+impl common::Backend for Backend {
+    const ALL: &'static [Self] = &[Splitting, InclusionExclusion];
 
-    fn overlap(&self, other: &Self) -> Option<Self> {
-        if other.start <= self.start && other.end >= self.end {
-            Some(*self)
-        } else if other.start >= self.start && other.start <= self.end {
-            Self::try_from_bounds(other.start, min(self.end, other.end))
-        } else if other.end >= self.start && other.end <= self.end {
-            Self::try_from_bounds(max(self.start, other.start), other.end)
-        } else {
-            None
+    fn name(&self) -> &'static str {
+        match self {
+            Splitting => "splitting",
+            InclusionExclusion => "inclusion-exclusion",
         }
     }
 }
 
-impl Region {
-    fn new(x_range: Range, y_range: Range, z_range: Range) -> Self {
-        Self {
-            x_range,
-            y_range,
-            z_range,
-        }
-    }
+/// The lit cubes after a reboot step, split into disjoint cuboids.
+pub struct Reboot<'a> {
+    operation: &'a Operation,
+    region: Cuboid,
+    cubes: &'a CuboidSet,
+}
 
-    fn init_coordinates(&self) -> impl Iterator<Item = Coord> + '_ {
-        let x_start = max(-50, self.x_range.start);
-        let x_end = min(50, self.x_range.end);
-        let y_start = max(-50, self.y_range.start);
-        let y_end = min(50, self.y_range.end);
-        let z_start = max(-50, self.z_range.start);
-        let z_end = min(50, self.z_range.end);
-        (x_start..=x_end).flat_map(move |x| {
-            (y_start..=y_end).flat_map(move |y| {
-                (z_start..=z_end).map(move |z| Coord::new(x, y, z))
-            })
-        })
+impl Display for Reboot<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: {} cubes lit in {} cuboids",
+            self.operation,
+            self.region,
+            self.cubes.volume(),
+            self.cubes.len()
+        )
     }
+}
 
-    fn count_cubes(&self) -> u64 {
-        self.x_range.len() * self.y_range.len() * self.z_range.len()
+fn execute<F>(
+    cubes: &mut CuboidSet,
+    operation: &Operation,
+    region: Cuboid,
+    observe: &mut F,
+) where
+    F: FnMut(Reboot),
+{
+    match operation {
+        On => cubes.insert(region),
+        Off => cubes.remove(&region),
     }
+    observe(Reboot {
+        operation,
+        region,
+        cubes,
+    });
+}
 
-    fn overlap(&self, other: &Self) -> Option<Self> {
-        let x_range = self.x_range.overlap(&other.x_range)?;
-        let y_range = self.y_range.overlap(&other.y_range)?;
-        let z_range = self.z_range.overlap(&other.z_range)?;
-        Some(Self {
-            x_range,
-            y_range,
-            z_range,
-        })
-    }
-
-    fn split_off(&self, sub_region: &Region) -> Vec<Self> {
-        let mut remain = Vec::new();
-
-        if let Some(x_range) = Range::try_from_bounds(
-            self.x_range.start,
-            sub_region.x_range.start - 1,
-        ) {
-            remain.push(Region::new(x_range, self.y_range, self.z_range));
-        }
-
-        if let Some(x_range) =
-            Range::try_from_bounds(sub_region.x_range.end + 1, self.x_range.end)
-        {
-            remain.push(Region::new(x_range, self.y_range, self.z_range));
-        }
-
-        if let Some(y_range) = Range::try_from_bounds(
-            self.y_range.start,
-            sub_region.y_range.start - 1,
-        ) {
-            remain.push(Region::new(sub_region.x_range, y_range, self.z_range));
-        }
-
-        if let Some(y_range) =
-            Range::try_from_bounds(sub_region.y_range.end + 1, self.y_range.end)
-        {
-            remain.push(Region::new(sub_region.x_range, y_range, self.z_range));
-        }
-
-        if let Some(z_range) = Range::try_from_bounds(
-            self.z_range.start,
-            sub_region.z_range.start - 1,
-        ) {
-            remain.push(Region::new(
-                sub_region.x_range,
-                sub_region.y_range,
-                z_range,
-            ));
+fn signed_volume<'a, I>(steps: I) -> u128
+where
+    I: Iterator<Item = (&'a Operation, Cuboid)>,
+{
+    let mut counts: HashMap<Cuboid, i64> = HashMap::new();
+    for (operation, region) in steps {
+        let overlaps = counts
+            .iter()
+            .filter_map(|(cuboid, &count)| {
+                cuboid.intersect(&region).map(|overlap| (overlap, count))
+            })
+            .collect::<Vec<_>>();
+        for (overlap, count) in overlaps {
+            *counts.entry(overlap).or_default() -= count;
         }
-
-        if let Some(z_range) =
-            Range::try_from_bounds(sub_region.z_range.end + 1, self.z_range.end)
-        {
-            remain.push(Region::new(
-                sub_region.x_range,
-                sub_region.y_range,
-                z_range,
-            ));
+        if *operation == On {
+            *counts.entry(region).or_default() += 1;
         }
-
-        remain
+        counts.retain(|_, count| *count != 0);
     }
-}
 
-impl Coord {
-    fn new(x: i32, y: i32, z: i32) -> Self {
-        Self { x, y, z }
-    }
+    let volume = counts
+        .iter()
+        .map(|(cuboid, &count)| i128::from(count) * cuboid.volume() as i128)
+        .sum::<i128>();
+    volume as u128
 }
 
-struct InitArea(HashSet<Coord>);
-
-impl InitArea {
-    fn new() -> Self {
-        Self(HashSet::new())
-    }
-
-    fn execute(&mut self, step: &Step) {
-        match step.operation {
-            On => self.0.extend(step.region.init_coordinates()),
-            Off => {
-                for coord in step.region.init_coordinates() {
-                    self.0.remove(&coord);
-                }
-            }
-        };
-    }
-
-    fn count_cubes(&self) -> usize {
-        self.0.len()
-    }
+fn init_area() -> Cuboid {
+    let init_range = Interval::new(-50, 50);
+    Cuboid::new(init_range, init_range, init_range)
 }
 
-pub fn part1(steps: &[Step]) -> usize {
-    let mut init_area = InitArea::new();
-    for step in steps {
-        init_area.execute(step);
-    }
-    init_area.count_cubes()
+pub fn part1(steps: &[Step]) -> u128 {
+    part1_with(steps, Backend::default())
 }
 
-struct Reactor(Vec<Region>);
-
-impl Reactor {
-    fn new() -> Self {
-        Self(Vec::new())
-    }
+pub fn part2(steps: &[Step]) -> u128 {
+    part2_with(steps, Backend::default())
+}
 
-    fn turn_on(&mut self, region: Region) {
-        let mut pending = vec![region];
-        while let Some(sub_region) = pending.pop() {
-            if let Some(overlap) =
-                self.0.iter().find_map(|r| r.overlap(&sub_region))
-            {
-                pending.extend(sub_region.split_off(&overlap));
-            } else {
-                self.0.push(sub_region);
-            }
+pub fn part1_with(steps: &[Step], backend: Backend) -> u128 {
+    match backend {
+        Splitting => part1_observed(steps, |_| ()),
+        InclusionExclusion => {
+            let init_area = init_area();
+            signed_volume(steps.iter().filter_map(|step| {
+                step.region
+                    .intersect(&init_area)
+                    .map(|region| (&step.operation, region))
+            }))
         }
     }
+}
 
-    fn turn_off(&mut self, region: &Region) {
-        let mut regions = self
-            .0
-            .drain(..)
-            .flat_map(|r| {
-                if let Some(overlap) = r.overlap(region) {
-                    r.split_off(&overlap)
-                } else {
-                    vec![r]
-                }
-            })
-            .collect();
-        swap(&mut self.0, &mut regions);
+pub fn part2_with(steps: &[Step], backend: Backend) -> u128 {
+    match backend {
+        Splitting => part2_observed(steps, |_| ()),
+        InclusionExclusion => signed_volume(
+            steps.iter().map(|step| (&step.operation, step.region)),
+        ),
     }
+}
 
-    fn execute(&mut self, step: &Step) {
-        match step.operation {
-            On => self.turn_on(step.region.clone()),
-            Off => self.turn_off(&step.region),
+/// Same as [`part1`], calling `observe` with the lit cubes after every step
+/// that touches the initialization area.
+pub fn part1_observed<F>(steps: &[Step], mut observe: F) -> u128
+where
+    F: FnMut(Reboot),
+{
+    let init_area = init_area();
+    let mut cubes = CuboidSet::new();
+    for step in steps {
+        if let Some(region) = step.region.intersect(&init_area) {
+            execute(&mut cubes, &step.operation, region, &mut observe);
         }
     }
+    cubes.volume()
+}
 
-    fn count_cubes(&self) -> u64 {
-        self.0.iter().map(|region| region.count_cubes()).sum()
+/// Same as [`part2`], calling `observe` with the lit cubes after every step.
+pub fn part2_observed<F>(steps: &[Step], mut observe: F) -> u128
+where
+    F: FnMut(Reboot),
+{
+    let mut cubes = CuboidSet::new();
+    for step in steps {
+        execute(&mut cubes, &step.operation, step.region, &mut observe);
     }
+    cubes.volume()
 }
 
-pub fn part2(steps: &[Step]) -> u64 {
-    let mut reactor = Reactor::new();
-    for step in steps {
-        reactor.execute(step);
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            On => write!(f, "on"),
+            Off => write!(f, "off"),
+        }
     }
-    reactor.count_cubes()
 }
 
 impl FromStr for Operation {
@@ -258,53 +204,36 @@ impl FromStr for Operation {
     }
 }
 
-impl FromStr for Range {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.split("..")
-            .map(|num| {
-                num.parse()
-                    .map_err(|_| format!("Invalid range number '{}'", num))
-            })
-            .collect::<Result<Vec<i32>, _>>()
-            .and_then(|vec| match *vec.as_slice() {
-                [start, end] if end >= start => Ok(Range { start, end }),
-                _ => Err(format!("Invalid range '{}'", s)),
-            })
+fn parse_region(s: &str) -> Result<Cuboid, String> {
+    lazy_static! {
+        static ref REGEX: Regex = Regex::new(concat!(
+            r"^x=(\-?\d+\.\.\-?\d+),",
+            r"y=(\-?\d+\.\.\-?\d+),",
+            r"z=(\-?\d+\.\.\-?\d+)$",
+        ))
+        .unwrap();
     }
-}
-
-impl FromStr for Region {
-    type Err = String;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        lazy_static! {
-            static ref REGEX: Regex = Regex::new(concat!(
-                r"^x=(\-?\d+\.\.\-?\d+),",
-                r"y=(\-?\d+\.\.\-?\d+),",
-                r"z=(\-?\d+\.\.\-?\d+)$",
-            ))
-            .unwrap();
-        }
-
-        let captures = REGEX
-            .captures(s.trim())
-            .ok_or(format!("Invalid region '{}'", s))?;
+    let captures = REGEX
+        .captures(s.trim())
+        .ok_or(format!("Invalid region '{}'", s))?;
+
+    captures
+        .iter()
+        .skip(1)
+        .map(|cap| cap.unwrap().as_str().parse())
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|vec| match *vec.as_slice() {
+            [x_range, y_range, z_range] => {
+                Ok(Cuboid::new(x_range, y_range, z_range))
+            }
+            _ => Err(format!("Invalid region '{}'", s)),
+        })
+}
 
-        captures
-            .iter()
-            .skip(1)
-            .map(|cap| cap.unwrap().as_str().parse())
-            .collect::<Result<Vec<_>, _>>()
-            .and_then(|vec| match *vec.as_slice() {
-                [x_range, y_range, z_range] => Ok(Self {
-                    x_range,
-                    y_range,
-                    z_range,
-                }),
-                _ => Err(format!("Invalid region '{}'", s)),
-            })
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.operation, self.region)
     }
 }
 
@@ -316,7 +245,62 @@ impl FromStr for Step {
             .split_once(' ')
             .ok_or_else(|| format!("Invalid step '{}'", s))?;
         let operation = oper_str.parse()?;
-        let region = region_str.parse()?;
+        let region = parse_region(region_str)?;
         Ok(Self { operation, region })
     }
 }
+
+pub fn parse_input(input: &str) -> Result<Vec<Step>, String> {
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn interval(
+        coord: impl Strategy<Value = i32> + Clone,
+    ) -> impl Strategy<Value = Interval> {
+        (coord.clone(), coord)
+            .prop_map(|(a, b)| Interval::new(a.min(b), a.max(b)))
+    }
+
+    fn step(
+        coord: impl Strategy<Value = i32> + Clone,
+    ) -> impl Strategy<Value = Step> {
+        let interval = || interval(coord.clone());
+        (any::<bool>(), interval(), interval(), interval()).prop_map(
+            |(on, x, y, z)| Step {
+                operation: if on { On } else { Off },
+                region: Cuboid::new(x, y, z),
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn display_round_trips(step in step(any::<i32>())) {
+            prop_assert_eq!(step.to_string().parse(), Ok(step));
+        }
+
+        #[test]
+        fn backends_agree(
+            steps in prop::collection::vec(step(-60..60), 0..12)
+        ) {
+            for part in [part1_with, part2_with] {
+                prop_assert_eq!(
+                    part(&steps, Splitting),
+                    part(&steps, InclusionExclusion)
+                );
+            }
+        }
+    }
+}
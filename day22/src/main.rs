@@ -1,43 +1,82 @@
 use clap::{crate_description, App, Arg};
-use day22::{part1, part2, Step};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use common::{read_or_example, Backend as _, Pretty, COLOR_CHOICES};
+use day22::{
+    parse_input, part1_observed, part1_with, part2_observed, part2_with,
+    Backend, Reboot, Step, EXAMPLE,
+};
 use std::process::exit;
 
 fn main() {
-    let args = App::new(crate_description!())
-        .arg(
-            Arg::with_name("INPUT")
-                .help("File with puzzle input")
-                .required(true)
-                .index(1),
-        )
-        .get_matches();
+    let backends = Backend::names();
+    let args =
+        App::new(crate_description!())
+            .arg(
+                Arg::with_name("INPUT")
+                    .help("File with puzzle input")
+                    .required_unless("example")
+                    .index(1),
+            )
+            .arg(
+                Arg::with_name("example")
+                    .long("example")
+                    .conflicts_with("INPUT")
+                    .help("Solves the example from the puzzle description"),
+            )
+            .arg(Arg::with_name("explain").long("explain").help(
+                "Prints the intermediate steps taken to reach the answers",
+            ))
+            .arg(
+                Arg::with_name("backend")
+                    .long("backend")
+                    .takes_value(true)
+                    .possible_values(&backends)
+                    .conflicts_with("explain")
+                    .help("Algorithm used to solve the puzzle"),
+            )
+            .arg(
+                Arg::with_name("color")
+                    .long("color")
+                    .takes_value(true)
+                    .possible_values(&COLOR_CHOICES)
+                    .default_value("auto")
+                    .help("When to color the output"),
+            )
+            .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
-}
+    if let Some(name) = args.value_of("backend") {
+        // Already checked by the argument's possible values
+        let backend = Backend::from_name(name).unwrap();
+        println!("Part 1: {}", pretty.answer(part1_with(&input, backend)));
+        println!("Part 2: {}", pretty.answer(part2_with(&input, backend)));
+        return;
+    }
 
-fn read_input(filename: &str) -> Result<Vec<Step>, String> {
-    let input_file = File::open(filename).map_err(|err| err.to_string())?;
+    let explain = args.is_present("explain");
+    let observe = |event: Reboot| {
+        if explain {
+            println!("{}", event);
+        }
+    };
+
+    println!("Part 1: {}", pretty.answer(part1_observed(&input, observe)));
+    println!("Part 2: {}", pretty.answer(part2_observed(&input, observe)));
+}
 
-    BufReader::new(input_file)
-        .lines()
-        .zip(1..)
-        .map(|(line, line_num)| {
-            line.map_err(|err| (line_num, err.to_string()))
-                .and_then(|line| line.parse().map_err(|err| (line_num, err)))
-        })
-        .collect::<Result<_, _>>()
-        .map_err(|(line_num, err)| format!("Line {}: {}", line_num, err))
+fn read_input(filename: Option<&str>) -> Result<Vec<Step>, String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
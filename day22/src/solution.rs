@@ -0,0 +1,15 @@
+use crate::{parse_input, part1, part2};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let steps = parse_input(input)?;
+    report.found(part1(&steps));
+    report.found(part2(&steps));
+    Ok(())
+}
+
+register_solution!(day: 22, solve: solve);
@@ -1,12 +1,27 @@
-use std::cmp::Reverse;
-use std::collections::{BTreeMap, BinaryHeap, HashSet};
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+#[cfg(not(feature = "parallel"))]
+use common::branch_and_bound_with_stats;
+#[cfg(feature = "parallel")]
+use common::par_branch_and_bound_with_stats as branch_and_bound_with_stats;
+use common::{Problem, SearchStats};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use serde_with::{As, Same};
+use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
-use std::rc::Rc;
 use std::str::FromStr;
 use Amphipod::*;
 use Space::*;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
 #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Amphipod {
     Amber,
     Bronze,
@@ -23,6 +38,15 @@ impl Amphipod {
             Desert => 1_000,
         }
     }
+
+    fn room_x(&self) -> i32 {
+        match self {
+            Amber => 3,
+            Bronze => 5,
+            Copper => 7,
+            Desert => 9,
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -33,6 +57,7 @@ enum Space {
 }
 
 #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 struct Position {
     x: i32,
     y: i32,
@@ -64,53 +89,24 @@ impl Burrow {
         .flat_map(|(xs, space)| {
             xs.into_iter().map(move |x| (Position::new(x, 1), space))
         })
-        .chain(
-            [(3, Amber), (5, Bronze), (7, Copper), (9, Desert)]
-                .into_iter()
-                .flat_map(|(x, amphipod)| {
-                    (2..=depth)
-                        .into_iter()
-                        .map(move |y| (Position::new(x, y), Room(amphipod)))
-                }),
-        )
+        .chain([Amber, Bronze, Copper, Desert].into_iter().flat_map(
+            |amphipod| {
+                (2..=depth).map(move |y| {
+                    (Position::new(amphipod.room_x(), y), Room(amphipod))
+                })
+            },
+        ))
         .collect::<BTreeMap<_, _>>();
 
         Self(spaces)
     }
 
-    fn min_energy(&self, initial_state: &BurrowState) -> Option<u32> {
-        let initial_state_rc = Rc::new(initial_state.clone());
-        let mut min_energy = BTreeMap::from([(initial_state_rc.clone(), 0)]);
-        let mut min_heap = BinaryHeap::from([(Reverse(0), initial_state_rc)]);
-
-        let mut min = None;
-        while let Some((Reverse(state_energy), state)) = min_heap.pop() {
-            for (next_state, move_energy) in self.next_states(&state) {
-                let next_energy = state_energy + move_energy;
-                if min_energy
-                    .get(&next_state)
-                    .map(|e| *e <= next_energy)
-                    .unwrap_or(false)
-                {
-                    continue;
-                }
-
-                if self.is_organized(&next_state) {
-                    if min.map(|e| next_energy < e).unwrap_or(true) {
-                        min = Some(next_energy);
-                    }
-                    continue;
-                }
-
-                let next_state_rc = Rc::new(next_state);
-                min_heap.push((Reverse(next_energy), next_state_rc.clone()));
-                min_energy
-                    .entry(next_state_rc)
-                    .and_modify(|e| *e = next_energy)
-                    .or_insert(next_energy);
-            }
-        }
-        min
+    fn min_energy(
+        &self,
+        initial_state: &BurrowState,
+        stats: &mut SearchStats,
+    ) -> Option<u32> {
+        branch_and_bound_with_stats(self, initial_state.clone(), stats)
     }
 
     fn is_organized(&self, state: &BurrowState) -> bool {
@@ -195,8 +191,39 @@ impl Burrow {
     }
 }
 
+impl Problem for Burrow {
+    type State = BurrowState;
+    type Cost = u32;
+
+    fn successors(&self, state: &BurrowState) -> Vec<(BurrowState, u32)> {
+        self.next_states(state).collect()
+    }
+
+    fn is_goal(&self, state: &BurrowState) -> bool {
+        self.is_organized(state)
+    }
+
+    /// Energy for every amphipod outside its room to step into it, as if
+    /// nothing stood in the way.
+    fn lower_bound(&self, state: &BurrowState) -> u32 {
+        state
+            .0
+            .iter()
+            .filter(|(pos, amphipod)| pos.x != amphipod.room_x())
+            .map(|(pos, amphipod)| {
+                let steps = (pos.y - 1) + (pos.x - amphipod.room_x()).abs() + 1;
+                steps as u32 * amphipod.move_energy()
+            })
+            .sum()
+    }
+}
+
 #[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub struct BurrowState(BTreeMap<Position, Amphipod>);
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct BurrowState(
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<(Same, Same)>>"))]
+    BTreeMap<Position, Amphipod>,
+);
 
 impl BurrowState {
     fn remove(&self, position: Position) -> Self {
@@ -217,10 +244,24 @@ impl BurrowState {
 }
 
 pub fn part1(initial_state: &BurrowState) -> Option<u32> {
-    Burrow::new(false).min_energy(initial_state)
+    part1_with_stats(initial_state, &mut SearchStats::default())
 }
 
 pub fn part2(initial_state: &BurrowState) -> Option<u32> {
+    part2_with_stats(initial_state, &mut SearchStats::default())
+}
+
+pub fn part1_with_stats(
+    initial_state: &BurrowState,
+    stats: &mut SearchStats,
+) -> Option<u32> {
+    Burrow::new(false).min_energy(initial_state, stats)
+}
+
+pub fn part2_with_stats(
+    initial_state: &BurrowState,
+    stats: &mut SearchStats,
+) -> Option<u32> {
     let state = initial_state
         .0
         .iter()
@@ -250,7 +291,7 @@ pub fn part2(initial_state: &BurrowState) -> Option<u32> {
         )
         .collect::<BTreeMap<_, _>>();
 
-    Burrow::new(true).min_energy(&BurrowState(state))
+    Burrow::new(true).min_energy(&BurrowState(state), stats)
 }
 
 impl TryFrom<char> for Amphipod {
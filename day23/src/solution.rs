@@ -0,0 +1,15 @@
+use crate::{part1, part2, BurrowState};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let state: BurrowState = input.parse()?;
+    report.maybe(part1(&state));
+    report.maybe(part2(&state));
+    Ok(())
+}
+
+register_solution!(day: 23, solve: solve);
@@ -1,10 +1,22 @@
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::SearchStats;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use Instruction::*;
 use Operand::*;
 use Variable::*;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Variable {
     W,
     X,
@@ -12,13 +24,15 @@ pub enum Variable {
     Z,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Operand {
     Var(Variable),
     Num(i64),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Instruction {
     Inp(Variable),
     Add(Variable, Operand),
@@ -77,8 +91,14 @@ impl Instruction {
         Some(())
     }
 
-    fn next_states(&self, mut states: States) -> Option<States> {
+    fn next_states(
+        &self,
+        mut states: States,
+        stats: &mut SearchStats,
+    ) -> Option<States> {
         let mut next_states = HashMap::new();
+        stats.expanded += states.len() as u64;
+        let mut produced = 0;
 
         if let Inp(var) = self {
             for (regs, (min, max)) in states.drain() {
@@ -87,6 +107,7 @@ impl Instruction {
                     next_regs[*var as usize] = input;
                     let next_min = min * 10 + input;
                     let next_max = max * 10 + input;
+                    produced += 1;
                     next_states
                         .entry(next_regs)
                         .and_modify(|bounds: &mut Bounds| {
@@ -99,6 +120,7 @@ impl Instruction {
         } else {
             for (mut regs, (min, max)) in states.drain() {
                 self.evaluate(&mut regs)?;
+                produced += 1;
                 next_states
                     .entry(regs)
                     .and_modify(|bounds: &mut Bounds| {
@@ -109,6 +131,10 @@ impl Instruction {
             }
         }
 
+        // States that ended up with the same registers were merged
+        stats.duplicates += produced - next_states.len() as u64;
+        stats.record_frontier(next_states.len());
+        stats.record_visited(next_states.len());
         Some(next_states)
     }
 }
@@ -129,9 +155,18 @@ fn run(
 }
 
 pub fn solve(instructions: &[Instruction]) -> Option<(i64, i64)> {
+    solve_with_stats(instructions, &mut SearchStats::default())
+}
+
+/// Same as [`solve`], counting register states as expanded after every
+/// instruction and those merged with an identical one as pruned duplicates.
+pub fn solve_with_stats(
+    instructions: &[Instruction],
+    stats: &mut SearchStats,
+) -> Option<(i64, i64)> {
     let mut states = HashMap::from([([0; 4], (0, 0))]);
     for instruction in instructions {
-        states = instruction.next_states(states)?;
+        states = instruction.next_states(states, stats)?;
     }
 
     let (min, max) = states
@@ -157,6 +192,39 @@ pub fn solve(instructions: &[Instruction]) -> Option<(i64, i64)> {
     Some((min, max))
 }
 
+impl Display for Variable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            W => write!(f, "w"),
+            X => write!(f, "x"),
+            Y => write!(f, "y"),
+            Z => write!(f, "z"),
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Var(var) => write!(f, "{}", var),
+            Num(num) => write!(f, "{}", num),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Inp(var) => write!(f, "inp {}", var),
+            Add(var, operand) => write!(f, "add {} {}", var, operand),
+            Mul(var, operand) => write!(f, "mul {} {}", var, operand),
+            Div(var, operand) => write!(f, "div {} {}", var, operand),
+            Mod(var, operand) => write!(f, "mod {} {}", var, operand),
+            Eql(var, operand) => write!(f, "eql {} {}", var, operand),
+        }
+    }
+}
+
 impl FromStr for Variable {
     type Err = String;
 
@@ -189,30 +257,72 @@ impl FromStr for Instruction {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut tokens = s.split_whitespace().rev().collect::<Vec<_>>();
-        if tokens.len() < 2 || tokens.len() > 3 {
-            return Err(format!("Invalid instruction '{}'", s));
+        let tokens = s.split_whitespace().collect::<Vec<_>>();
+        let (instruction, variable, operand) = match tokens.as_slice() {
+            [instruction, variable] => (instruction, variable, None),
+            [instruction, variable, operand] => {
+                (instruction, variable, Some(operand.parse::<Operand>()?))
+            }
+            _ => return Err(format!("Invalid instruction '{}'", s)),
+        };
+        let instruction = instruction.to_lowercase();
+        let variable = variable.parse::<Variable>()?;
+
+        match (instruction.as_str(), operand) {
+            ("inp", None) => Ok(Inp(variable)),
+            ("inp", Some(_)) => {
+                Err(format!("Unexpected operand for '{}'", instruction))
+            }
+            ("add", Some(operand)) => Ok(Add(variable, operand)),
+            ("mul", Some(operand)) => Ok(Mul(variable, operand)),
+            ("div", Some(operand)) => Ok(Div(variable, operand)),
+            ("mod", Some(operand)) => Ok(Mod(variable, operand)),
+            ("eql", Some(operand)) => Ok(Eql(variable, operand)),
+            ("add" | "mul" | "div" | "mod" | "eql", None) => {
+                Err(format!("Missing operand for '{}'", instruction))
+            }
+            _ => Err(format!("Unknown instruction '{}'", instruction)),
         }
+    }
+}
 
-        let instruction = tokens.pop().unwrap().to_lowercase();
-        let variable = tokens.pop().unwrap().parse::<Variable>()?;
+pub fn parse_input(input: &str) -> Result<Vec<Instruction>, String> {
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, line_num)| {
+            line.parse()
+                .map_err(|err| format!("Line {}: {}", line_num, err))
+        })
+        .collect()
+}
 
-        if instruction == "inp" {
-            return Ok(Inp(variable));
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
 
-        let operand = tokens
-            .pop()
-            .ok_or_else(|| format!("Missing operand for '{}'", instruction))?
-            .parse::<Operand>()?;
-
-        match instruction.as_str() {
-            "add" => Ok(Add(variable, operand)),
-            "mul" => Ok(Mul(variable, operand)),
-            "div" => Ok(Div(variable, operand)),
-            "mod" => Ok(Mod(variable, operand)),
-            "eql" => Ok(Eql(variable, operand)),
-            _ => Err(format!("Unknown instruction '{}'", instruction)),
+    fn variable() -> impl Strategy<Value = Variable> {
+        prop_oneof![Just(W), Just(X), Just(Y), Just(Z)]
+    }
+
+    fn instruction() -> impl Strategy<Value = Instruction> {
+        let operand =
+            prop_oneof![variable().prop_map(Var), any::<i64>().prop_map(Num)];
+        prop_oneof![
+            variable().prop_map(Inp),
+            (variable(), operand.clone()).prop_map(|(v, o)| Add(v, o)),
+            (variable(), operand.clone()).prop_map(|(v, o)| Mul(v, o)),
+            (variable(), operand.clone()).prop_map(|(v, o)| Div(v, o)),
+            (variable(), operand.clone()).prop_map(|(v, o)| Mod(v, o)),
+            (variable(), operand).prop_map(|(v, o)| Eql(v, o)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn display_round_trips(instruction in instruction()) {
+            prop_assert_eq!(instruction.to_string().parse(), Ok(instruction));
         }
     }
 }
@@ -1,7 +1,6 @@
 use clap::{crate_description, App, Arg};
-use day24::{solve, Instruction};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use common::{read_or_example, Pretty, SearchStats, COLOR_CHOICES};
+use day24::{parse_input, solve_with_stats, Instruction, EXAMPLE};
 use std::process::exit;
 
 fn main() {
@@ -9,38 +8,60 @@ fn main() {
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("stats")
+                .long("stats")
+                .help("Prints search statistics after each answer"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    if let Some((min, max)) = solve(&input) {
-        println!("Part 1: {}\nPart 2: {}", max, min);
+    let mut stats = SearchStats::default();
+    if let Some((min, max)) = solve_with_stats(&input, &mut stats) {
+        println!(
+            "Part 1: {}\nPart 2: {}",
+            pretty.answer(max),
+            pretty.answer(min)
+        );
     } else {
         println!("Part 1: Not found\nPart 2: Not found");
     }
+    if args.is_present("stats") {
+        println!("Stats: {}", stats);
+    }
 }
 
-fn read_input(filename: &str) -> Result<Vec<Instruction>, String> {
-    let input_file = File::open(filename).map_err(|err| err.to_string())?;
-
-    BufReader::new(input_file)
-        .lines()
-        .zip(1..)
-        .map(|(line, line_num)| {
-            line.map_err(|err| (line_num, err.to_string()))
-                .and_then(|value| value.parse().map_err(|err| (line_num, err)))
-        })
-        .collect::<Result<_, _>>()
-        .map_err(|(line_num, err)| format!("Line {}: {}", line_num, err))
+fn read_input(filename: Option<&str>) -> Result<Vec<Instruction>, String> {
+    read_or_example(filename, EXAMPLE)
+        .and_then(|contents| parse_input(&contents))
 }
@@ -0,0 +1,16 @@
+use crate::parse_input;
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let instructions = parse_input(input)?;
+    let solution = crate::solve(&instructions);
+    report.maybe(solution.map(|(_, max)| max));
+    report.maybe(solution.map(|(min, _)| min));
+    Ok(())
+}
+
+register_solution!(day: 24, solve: solve);
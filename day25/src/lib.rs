@@ -1,83 +1,101 @@
-use std::collections::HashMap;
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+pub mod solution;
+
+use common::{Animate, Grid, Visualize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::iter::successors;
 use std::str::FromStr;
 use Herd::*;
 
+/// The example input from the puzzle description.
+pub const EXAMPLE: &str = include_str!("../sample");
+
 #[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 enum Herd {
     East,
     South,
 }
 
-#[derive(Clone, Eq, Hash, PartialEq)]
-struct Position {
-    x: i32,
-    y: i32,
-}
-
-impl Position {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
-    }
-}
-
 #[derive(Clone)]
-pub struct Region {
-    x_dim: i32,
-    y_dim: i32,
-    positions: HashMap<Position, Herd>,
-}
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Region(Grid<Option<Herd>>);
 
 impl Region {
     fn step(&self) -> Option<Self> {
-        if let Some(next_region) = self.move_herd(&East) {
-            next_region.move_herd(&South)
-        } else {
-            self.move_herd(&South)
+        match self.move_herd(East) {
+            Some(next_region) => {
+                Some(next_region.move_herd(South).unwrap_or(next_region))
+            }
+            None => self.move_herd(South),
         }
     }
 
-    fn move_herd(&self, herd: &Herd) -> Option<Self> {
+    fn move_herd(&self, herd: Herd) -> Option<Self> {
         let mut moved = false;
-        let positions = self
-            .positions
-            .iter()
-            .map(|(pos, sea_cucumber)| {
-                if sea_cucumber == herd {
-                    if let Some(next_pos) = self.move_if_free(herd, pos) {
-                        moved = true;
-                        (next_pos, *sea_cucumber)
-                    } else {
-                        (pos.clone(), *sea_cucumber)
-                    }
-                } else {
-                    (pos.clone(), *sea_cucumber)
+        let mut positions = self.0.clone();
+        for (pos, sea_cucumber) in self.0.iter() {
+            if *sea_cucumber == Some(herd) {
+                let next_pos = self.next_position(herd, pos);
+                if self.0[next_pos].is_none() {
+                    positions[pos] = None;
+                    positions[next_pos] = Some(herd);
+                    moved = true;
                 }
-            })
-            .collect();
+            }
+        }
         if moved {
-            Some(Self {
-                x_dim: self.x_dim,
-                y_dim: self.y_dim,
-                positions,
-            })
+            Some(Self(positions))
         } else {
             None
         }
     }
 
-    fn move_if_free(&self, herd: &Herd, pos: &Position) -> Option<Position> {
-        let next_pos = match herd {
-            East => Position::new((pos.x + 1) % self.x_dim, pos.y),
-            South => Position::new(pos.x, (pos.y + 1) % self.y_dim),
-        };
-        if self.positions.contains_key(&next_pos) {
-            None
-        } else {
-            Some(next_pos)
+    fn next_position(
+        &self,
+        herd: Herd,
+        (x, y): (usize, usize),
+    ) -> (usize, usize) {
+        match herd {
+            East => ((x + 1) % self.0.width(), y),
+            South => (x, (y + 1) % self.0.height()),
         }
     }
 }
 
+impl Visualize for Region {
+    fn frames(&self) -> impl Iterator<Item = String> + '_ {
+        successors(Some(self.clone()), Region::step).map(|region| {
+            region
+                .0
+                .map(|sea_cucumber| match sea_cucumber {
+                    Some(East) => '>',
+                    Some(South) => 'v',
+                    None => '.',
+                })
+                .to_string()
+        })
+    }
+}
+
+impl Animate for Region {
+    /// Sea floor, east-facing herd and south-facing herd.
+    const PALETTE: &'static [[u8; 3]] =
+        &[[10, 40, 80], [240, 130, 40], [60, 200, 120]];
+
+    fn images(&self) -> impl Iterator<Item = Grid<u8>> + '_ {
+        successors(Some(self.clone()), Region::step).map(|region| {
+            region.0.map(|sea_cucumber| match sea_cucumber {
+                None => 0,
+                Some(East) => 1,
+                Some(South) => 2,
+            })
+        })
+    }
+}
+
 pub fn part1(initial_region: &Region) -> u32 {
     let mut steps = 1;
     let mut region = initial_region.clone();
@@ -104,26 +122,19 @@ impl FromStr for Region {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let positions = s
-            .lines()
-            .zip(0..)
-            .flat_map(|(line, y)| {
-                line.chars().zip(0..).filter(|(ch, _)| *ch != '.').map(
-                    move |(ch, x)| {
-                        Herd::try_from(ch)
-                            .map(|herd| (Position::new(x, y), herd))
-                            .map_err(|_| format!("Invalid input '{}'", ch))
-                    },
-                )
+        s.lines()
+            .map(|line| {
+                line.chars()
+                    .map(|ch| match ch {
+                        '.' => Ok(None),
+                        _ => Herd::try_from(ch)
+                            .map(Some)
+                            .map_err(|_| format!("Invalid input '{}'", ch)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
             })
-            .collect::<Result<HashMap<_, _>, _>>()?;
-
-        let x_dim = 1 + positions.keys().map(|pos| pos.x).max().unwrap_or(0);
-        let y_dim = 1 + positions.keys().map(|pos| pos.y).max().unwrap_or(0);
-        Ok(Self {
-            x_dim,
-            y_dim,
-            positions,
-        })
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(Grid::from_rows)
+            .map(Self)
     }
 }
@@ -1,33 +1,98 @@
 use clap::{crate_description, App, Arg};
-use day25::{part1, Region};
-use std::fs::read_to_string;
+use common::{read_or_example, Animate, Pretty, Visualize, COLOR_CHOICES};
+use day25::{part1, Region, EXAMPLE};
+use std::path::Path;
 use std::process::exit;
+use std::time::Duration;
 
 fn main() {
     let args = App::new(crate_description!())
         .arg(
             Arg::with_name("INPUT")
                 .help("File with puzzle input")
-                .required(true)
+                .required_unless("example")
                 .index(1),
         )
+        .arg(
+            Arg::with_name("example")
+                .long("example")
+                .conflicts_with("INPUT")
+                .help("Solves the example from the puzzle description"),
+        )
+        .arg(
+            Arg::with_name("visualize")
+                .long("visualize")
+                .help("Animates the puzzle on the terminal before solving it"),
+        )
+        .arg(
+            Arg::with_name("delay")
+                .long("delay")
+                .takes_value(true)
+                .default_value("100")
+                .validator(|value| {
+                    value
+                        .parse::<u64>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .help("Milliseconds between animation frames"),
+        )
+        .arg(
+            Arg::with_name("export")
+                .long("export")
+                .takes_value(true)
+                .value_name("PATH")
+                .help(
+                    "Writes every step to PATH as an animated GIF if it ends \
+                    in .gif, or else as PNG frames in directory PATH",
+                ),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&COLOR_CHOICES)
+                .default_value("auto")
+                .help("When to color the output"),
+        )
         .get_matches();
+    let pretty = Pretty::new(args.value_of("color").unwrap().parse().unwrap());
 
     println!(crate_description!());
 
-    let input = match read_input(args.value_of("INPUT").unwrap()) {
+    let input = match read_input(args.value_of("INPUT")) {
         Ok(data) => data,
         Err(err) => {
-            println!("Failed to read input: {}", err);
+            println!(
+                "{}",
+                pretty.error(format!("Failed to read input: {}", err))
+            );
             exit(2);
         }
     };
 
-    println!("Part 1: {}", part1(&input));
+    if args.is_present("visualize") {
+        let delay = args.value_of("delay").unwrap().parse().unwrap();
+        if let Err(err) = input.visualize(Duration::from_millis(delay)) {
+            println!(
+                "{}",
+                pretty.error(format!("Failed to visualize: {}", err))
+            );
+        }
+    }
+
+    if let Some(path) = args.value_of("export") {
+        let delay = args.value_of("delay").unwrap().parse().unwrap();
+        if let Err(err) =
+            input.export(Path::new(path), Duration::from_millis(delay))
+        {
+            println!("{}", pretty.error(format!("Failed to export: {}", err)));
+        }
+    }
+
+    println!("Part 1: {}", pretty.answer(part1(&input)));
 }
 
-fn read_input(filename: &str) -> Result<Region, String> {
-    read_to_string(filename)
-        .map_err(|err| err.to_string())
-        .and_then(|s| s.parse())
+fn read_input(filename: Option<&str>) -> Result<Region, String> {
+    read_or_example(filename, EXAMPLE).and_then(|s| s.parse())
 }
@@ -0,0 +1,14 @@
+use crate::{part1, Region};
+use common::{register_solution, Params, Report};
+
+fn solve(
+    input: &str,
+    _params: &Params,
+    report: &mut Report,
+) -> Result<(), String> {
+    let region: Region = input.parse()?;
+    report.found(part1(&region));
+    Ok(())
+}
+
+register_solution!(day: 25, solve: solve);